@@ -0,0 +1,129 @@
+//! Report cycle-count measurements over UART
+//!
+//! Requires the `"bench"` feature of this examples package.
+//!
+//! Connect a serial receiver to pin 14. Once per second, the example
+//! reports the minimum, mean, and maximum number of CPU cycles for
+//! each probe. The DMA probes measure the UART writes that send the
+//! report.
+
+#![no_std]
+#![no_main]
+
+#[cfg(target_arch = "arm")]
+extern crate panic_halt;
+#[cfg(target_arch = "arm")]
+extern crate t4_startup;
+
+use core::fmt::{self, Write};
+use hal::{
+    bench::{self, Probe},
+    ral,
+};
+use imxrt_async_hal as hal;
+const BAUD: u32 = 115_200;
+
+const CLOCK_FREQUENCY_HZ: u32 = 24_000_000; // XTAL
+const CLOCK_DIVIDER: u32 = 1;
+
+const PROBES: [(&str, Probe); 4] = [
+    ("DMA setup", Probe::DmaSetup),
+    ("DMA interrupt", Probe::DmaInterrupt),
+    ("GPIO interrupt", Probe::GpioInterrupt),
+    ("I2C FIFO", Probe::I2cFifo),
+];
+
+/// A fixed-size buffer for formatting a report line
+struct Line {
+    buffer: [u8; 96],
+    len: usize,
+}
+
+impl Line {
+    const fn new() -> Self {
+        Line {
+            buffer: [0; 96],
+            len: 0,
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+}
+
+impl Write for Line {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let bytes = s.as_bytes();
+        let end = self.len + bytes.len();
+        if end > self.buffer.len() {
+            return Err(fmt::Error);
+        }
+        self.buffer[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    let mut cp = cortex_m::Peripherals::take().unwrap();
+    bench::enable(&mut cp.DCB, &mut cp.DWT);
+
+    let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+    let pins = teensy4_pins::t40::into_pins(pads);
+    let gpt = hal::ral::gpt::GPT2::take().unwrap();
+
+    let ccm = hal::ral::ccm::CCM::take().unwrap();
+    ral::modify_reg!(ral::ccm, ccm, CSCDR1, UART_CLK_SEL: 1 /* Oscillator */, UART_CLK_PODF: CLOCK_DIVIDER - 1);
+    // LPUART2 clock gate on
+    ral::modify_reg!(ral::ccm, ccm, CCGR0, CG14: 0b11);
+    // DMA clock gate on
+    ral::modify_reg!(ral::ccm, ccm, CCGR5, CG3: 0b11);
+
+    let (mut timer, _, _) = t4_startup::new_gpt(gpt, &ccm);
+
+    let mut channels = hal::dma::channels(
+        hal::ral::dma0::DMA0::take().unwrap(),
+        hal::ral::dmamux::DMAMUX::take().unwrap(),
+    );
+
+    let uart2 = hal::ral::lpuart::LPUART2::take()
+        .and_then(hal::instance::uart)
+        .unwrap();
+    let mut uart = hal::UART::new(uart2, pins.p14, pins.p15);
+    let mut channel = channels[7].take().unwrap();
+    channel.set_interrupt_on_completion(true);
+    uart.set_baud(BAUD, CLOCK_FREQUENCY_HZ / CLOCK_DIVIDER)
+        .unwrap();
+
+    let report_loop = async {
+        loop {
+            t4_startup::gpt_delay_ms(&mut timer, 1_000).await;
+            // Take every measurement before the report adds its own DMA samples
+            let snapshot = [
+                bench::stats(PROBES[0].1),
+                bench::stats(PROBES[1].1),
+                bench::stats(PROBES[2].1),
+                bench::stats(PROBES[3].1),
+            ];
+            bench::reset();
+
+            for ((name, _), stats) in PROBES.iter().zip(snapshot.iter()) {
+                let mut line = Line::new();
+                let _ = match stats.mean() {
+                    Some(mean) => writeln!(
+                        line,
+                        "{}: count={} min={} mean={} max={}\r",
+                        name, stats.count, stats.min, mean, stats.max
+                    ),
+                    None => writeln!(line, "{}: no samples\r", name),
+                };
+                uart.dma_write(&mut channel, line.as_bytes()).await.unwrap();
+            }
+        }
+    };
+
+    async_embedded::task::block_on(report_loop);
+    unreachable!();
+}
@@ -12,6 +12,9 @@
 //! data even after the future was forgotten. The LED turns on as soon
 //! as the buffer reads non-zero. The example is sensitive to how the
 //! stack is laid out in each function call.
+//!
+//! `UART::dma_read_static` reads into a `&'static mut` buffer, so it
+//! doesn't have this problem.
 
 #![no_std]
 #![no_main]
@@ -25,25 +25,18 @@ fn main() -> ! {
     ral::modify_reg!(ral::ccm, ccm, CCGR1, CG10: 0b11, CG11: 0b11);
 
     let gpt = hal::ral::gpt::GPT1::take().unwrap();
-    ral::write_reg!(
-        ral::gpt,
-        gpt,
-        CR,
-        EN_24M: 1, // Enable crystal oscillator
-        CLKSRC: 0b101 // Crystal oscillator clock source
-    );
-    ral::write_reg!(ral::gpt, gpt, PR, PRESCALER24M: 4); // 1MHz / 5 == 200KHz
-
-    let (mut blink_timer, mut gpio_timer, _) = hal::GPT::new(gpt);
+    // 1MHz peripheral clock / 5 == 200KHz
+    let (mut blink_timer, mut gpio_timer, _) =
+        hal::GPT::with_clock(gpt, hal::gpt::ClockSource::Peripheral { hz: 1_000_000 }, 5);
     let blink_loop = async {
         loop {
-            blink_timer.delay(250_000u32 / 5).await;
+            blink_timer.delay_ms(250).await;
             led.toggle();
         }
     };
     let gpio_loop = async {
         loop {
-            gpio_timer.delay(333_000u32 / 5).await;
+            gpio_timer.delay_ms(333).await;
             pin14.toggle();
         }
     };
@@ -9,10 +9,10 @@
 #[cfg(target_arch = "arm")]
 extern crate teensy4_fcb;
 
-use hal::ral;
 use imxrt_async_hal as hal;
 
-/// Specify the vector table offset before main() is called
+/// Specify the vector table offset, and copy the ITCM code (with the `"itcm"` feature) and
+/// DTCM DMA buffers, before main() is called
 ///
 /// # Safety
 ///
@@ -25,44 +25,31 @@ unsafe fn pre_init() {
     }
     const SCB_VTOR: *mut u32 = 0xE000_ED08 as *mut u32;
     core::ptr::write_volatile(SCB_VTOR, &__svectors as *const _ as u32);
+    #[cfg(feature = "itcm")]
+    hal::itcm::init();
+    copy_dma_buffers();
 }
 
-/// Configure a GPT for another example
+/// Copy the `.dma_buffer` section from flash into DTCM
 ///
-/// See the gpt example for more information on GPT timer
-/// configuration.
-pub fn new_gpt(
-    gpt: ral::gpt::Instance,
-    ccm: &ral::ccm::Instance,
-) -> (hal::GPT, hal::GPT, hal::GPT) {
-    // Select 24MHz crystal oscillator, divide by 24 == 1MHz clock
-    ral::modify_reg!(ral::ccm, ccm, CSCMR1, PERCLK_PODF: DIVIDE_24, PERCLK_CLK_SEL: 1);
-
-    // Enable GPT clock gates...
-    match &*gpt as *const _ {
-        ral::gpt::GPT1 => ral::modify_reg!(ral::ccm, ccm, CCGR1, CG10: 0b11, CG11: 0b11),
-        ral::gpt::GPT2 => ral::modify_reg!(ral::ccm, ccm, CCGR0, CG12: 0b11, CG13: 0b11),
-        _ => unreachable!("There are only two GPT peripherals"),
+/// # Safety
+///
+/// Call once, before any code touches a `.dma_buffer` static.
+#[cfg_attr(not(target_arch = "arm"), allow(unused))]
+unsafe fn copy_dma_buffers() {
+    extern "C" {
+        static mut __sdma_buffer: u32;
+        static mut __edma_buffer: u32;
+        static __sidma_buffer: u32;
+    }
+    let start = &mut __sdma_buffer as *mut u32;
+    let end = &mut __edma_buffer as *mut u32;
+    let load = &__sidma_buffer as *const u32;
+    let len = (end as usize - start as usize) / core::mem::size_of::<u32>();
+    for idx in 0..len {
+        core::ptr::write_volatile(start.add(idx), core::ptr::read(load.add(idx)));
     }
-
-    ral::write_reg!(
-        ral::gpt,
-        gpt,
-        CR,
-        EN_24M: 1, // Enable crystal oscillator
-        CLKSRC: 0b101 // Crystal oscillator clock source
-    );
-    ral::write_reg!(ral::gpt, gpt, PR, PRESCALER24M: 4); // 1MHz / 5 == 200KHz
-
-    hal::GPT::new(gpt)
-}
-
-/// Use a GPT to delay `ms` milliseconds
-pub async fn gpt_delay_ms(gpt: &mut hal::GPT, ms: u32) {
-    gpt.delay(ms * 1_000 / 5).await
 }
 
-/// Use a GPT to delay `us` microseconds
-pub async fn gpt_delay_us(gpt: &mut hal::GPT, us: u32) {
-    gpt.delay(us / 5).await
-}
+#[cfg(feature = "teensy4")]
+pub use hal::teensy4::{gpt_delay_ms, gpt_delay_us, new_gpt};
@@ -2,6 +2,9 @@ INCLUDE device.x /* TODO conditionally emit based on 'rt' flag */
 
 MEMORY
 {
+    /* Skip the start of ITCM, so that no function has a null address */
+    ITCM    (rwx): ORIGIN = 0x00000020, LENGTH = 128K - 32
+    DTCM    (rwx): ORIGIN = 0x20000000, LENGTH = 128K
     RAM     (rwx): ORIGIN = 0x20200000, LENGTH = 512K
     FLASH   (rwx): ORIGIN = 0x60000000, LENGTH = 1984K
 }
@@ -36,7 +39,7 @@ ENTRY(_ivt);
 SECTIONS
 {
     /* If you add more sections to FLASH, you must add this section here */
-    __lflash = SIZEOF(.boot) + SIZEOF(.vector_table) + SIZEOF(.text) + SIZEOF(.rodata) + SIZEOF(.data) + SIZEOF(.gnu.sgstubs);
+    __lflash = SIZEOF(.boot) + SIZEOF(.vector_table) + SIZEOF(.text) + SIZEOF(.rodata) + SIZEOF(.data) + SIZEOF(.itcm) + SIZEOF(.dma_buffer) + SIZEOF(.gnu.sgstubs);
 
     /* The boot section contains all the special things that allow the IMXRT1062 to boot */
     .boot ORIGIN(FLASH) :
@@ -148,6 +151,32 @@ SECTIONS
     /* LMA of .data */
     __sidata = LOADADDR(.data);
 
+    /* ### .itcm */
+    .itcm : ALIGN(4)
+    {
+        __sitcm = .;
+        *(.itcm .itcm.*);
+        . = ALIGN(4);
+        __eitcm = .;
+    } > ITCM AT>FLASH
+
+    /* LMA of .itcm */
+    __siitcm = LOADADDR(.itcm);
+
+    /* ### .dma_buffer */
+    /* DMA buffers live in DTCM, which is never cached, so DMA operations skip cache
+       maintenance for them. pre_init() copies the section from flash. */
+    .dma_buffer : ALIGN(32)
+    {
+        __sdma_buffer = .;
+        *(.dma_buffer .dma_buffer.*);
+        . = ALIGN(32);
+        __edma_buffer = .;
+    } > DTCM AT>FLASH
+
+    /* LMA of .dma_buffer */
+    __sidma_buffer = LOADADDR(.dma_buffer);
+
     /* ### .bss */
     . = ALIGN(4);
     __sbss = .; /* Define outside of section to include INSERT BEFORE/AFTER symbols */
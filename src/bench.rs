@@ -0,0 +1,161 @@
+//! Cycle-count instrumentation for the async plumbing
+//!
+//! With the `"bench"` feature, the crate measures how many CPU cycles it spends in a
+//! few hot paths. Each [`Probe`] collects the number of samples, and the minimum,
+//! maximum, and total cycle counts. Use [`stats`] to read the measurements, and
+//! [`reset`] to clear them.
+//!
+//! The measurements use the DWT cycle counter. Call [`enable`] once before you
+//! start measuring.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::bench::{self, Probe};
+//!
+//! let mut cp = cortex_m::Peripherals::take().unwrap();
+//! bench::enable(&mut cp.DCB, &mut cp.DWT);
+//!
+//! // Run your program...
+//!
+//! let dma_setup = bench::stats(Probe::DmaSetup);
+//! if let Some(mean) = dma_setup.mean() {
+//!     // Report the mean...
+//! }
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use cortex_m::peripheral::{DCB, DWT};
+
+/// A measured code path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(usize)]
+#[cfg_attr(docsrs, doc(cfg(feature = "bench")))]
+pub enum Probe {
+    /// Preparing and starting a DMA transfer, including cache maintenance
+    DmaSetup,
+    /// DMA interrupt handler entry until all channels are woken
+    DmaInterrupt,
+    /// GPIO interrupt handler entry until all pins are woken
+    GpioInterrupt,
+    /// Filling the I2C transmit FIFO, or draining the I2C receive FIFO
+    I2cFifo,
+    /// Sleeping in [`block_on`](crate::blocking::block_on), until the processor wakes
+    ///
+    /// Each sample is one WFI, so `count` is the number of wakeups. The sample
+    /// ends before the interrupt handler that woke the processor runs.
+    Sleep,
+}
+
+const PROBE_COUNT: usize = 5;
+
+/// Measurements for a [`Probe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "bench")))]
+pub struct Stats {
+    /// Number of samples
+    pub count: u32,
+    /// Sum of all samples, in cycles
+    ///
+    /// The sum wraps once it exceeds `u32::MAX`.
+    pub total: u32,
+    /// Smallest sample, in cycles
+    pub min: u32,
+    /// Largest sample, in cycles
+    pub max: u32,
+}
+
+impl Stats {
+    /// Returns the mean sample, in cycles, or `None` if there are no samples
+    pub fn mean(&self) -> Option<u32> {
+        self.total.checked_div(self.count)
+    }
+}
+
+struct Samples {
+    count: AtomicU32,
+    total: AtomicU32,
+    min: AtomicU32,
+    max: AtomicU32,
+}
+
+impl Samples {
+    const fn new() -> Self {
+        Samples {
+            count: AtomicU32::new(0),
+            total: AtomicU32::new(0),
+            min: AtomicU32::new(u32::MAX),
+            max: AtomicU32::new(0),
+        }
+    }
+}
+
+static SAMPLES: [Samples; PROBE_COUNT] = [
+    Samples::new(),
+    Samples::new(),
+    Samples::new(),
+    Samples::new(),
+    Samples::new(),
+];
+
+/// Enable the DWT cycle counter
+pub fn enable(dcb: &mut DCB, dwt: &mut DWT) {
+    dcb.enable_trace();
+    dwt.enable_cycle_counter();
+}
+
+/// Returns the measurements for `probe`
+pub fn stats(probe: Probe) -> Stats {
+    let samples = &SAMPLES[probe as usize];
+    let count = samples.count.load(Ordering::Relaxed);
+    Stats {
+        count,
+        total: samples.total.load(Ordering::Relaxed),
+        min: if count > 0 {
+            samples.min.load(Ordering::Relaxed)
+        } else {
+            0
+        },
+        max: samples.max.load(Ordering::Relaxed),
+    }
+}
+
+/// Clear all measurements
+pub fn reset() {
+    for samples in &SAMPLES {
+        samples.count.store(0, Ordering::Relaxed);
+        samples.total.store(0, Ordering::Relaxed);
+        samples.min.store(u32::MAX, Ordering::Relaxed);
+        samples.max.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Measures a code path until it's dropped
+pub(crate) struct Measurement {
+    probe: Probe,
+    start: u32,
+}
+
+impl Measurement {
+    /// Start measuring `probe`
+    #[inline(always)]
+    pub(crate) fn start(probe: Probe) -> Self {
+        Measurement {
+            probe,
+            start: DWT::get_cycle_count(),
+        }
+    }
+}
+
+impl Drop for Measurement {
+    #[inline(always)]
+    fn drop(&mut self) {
+        let cycles = DWT::get_cycle_count().wrapping_sub(self.start);
+        let samples = &SAMPLES[self.probe as usize];
+        samples.count.fetch_add(1, Ordering::Relaxed);
+        samples.total.fetch_add(cycles, Ordering::Relaxed);
+        samples.min.fetch_min(cycles, Ordering::Relaxed);
+        samples.max.fetch_max(cycles, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,330 @@
+//! Blocking adapters
+//!
+//! Use the blocking adapters when you can't run an executor, like in early boot code,
+//! or in a panic handler. Each driver's `blocking()` method returns a [`Blocking`]
+//! adapter. The adapter has the same operations as the driver, but each operation
+//! waits for completion before it returns.
+//!
+//! The adapters drive the driver's futures with [`block_on`]. `block_on` sleeps with
+//! WFI until an interrupt arrives, so the driver's interrupts must be enabled in the
+//! NVIC. Pending interrupts wake the processor even if interrupts are disabled, like in
+//! a panic handler. In that case, the driver's interrupt handlers can't run, so
+//! `block_on` polls the future after every wakeup. Since the interrupt stays pending,
+//! `block_on` then polls without sleeping until the future completes.
+//!
+//! `block_on` counts how long it sleeps, how often it wakes, and which interrupt woke it
+//! last. Use [`sleep_stats`] to check that your program lets the processor sleep. With
+//! the `"bench"` feature, [`Probe::Sleep`](crate::bench::Probe::Sleep) also measures each
+//! sleep.
+//!
+//! `block_on` only sleeps with WFI, in whichever low-power mode the processor is
+//! configured for. It doesn't select deeper low-power modes, so the statistics don't
+//! report them.
+//!
+//! # Example
+//!
+//! Write a message from a panic handler, using a UART and a DMA channel that you
+//! set aside.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//!
+//! fn report_panic<TX, RX>(uart: &mut hal::UART<TX, RX>, channel: &mut hal::dma::Channel) {
+//!     let _ = uart.blocking().write(channel, b"panic!\r\n");
+//! }
+//! ```
+
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+use cortex_m::peripheral::{DWT, SCB};
+
+/// Set when any `block_on` waker is woken
+///
+/// Wakers may outlive their `block_on` call, so they don't reference the stack.
+static WOKEN: AtomicBool = AtomicBool::new(false);
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake, drop);
+
+fn clone(_: *const ()) -> RawWaker {
+    RawWaker::new(core::ptr::null(), &VTABLE)
+}
+
+fn wake(_: *const ()) {
+    WOKEN.store(true, Ordering::Release);
+}
+
+fn drop(_: *const ()) {}
+
+/// Cycles spent in WFI
+static SLEEP_CYCLES: AtomicU32 = AtomicU32::new(0);
+/// Number of WFI wakeups
+static WAKES: AtomicU32 = AtomicU32::new(0);
+/// Exception number that ended the last WFI, or zero if unknown
+static LAST_WAKE: AtomicU32 = AtomicU32::new(0);
+
+/// ICSR[VECTPENDING]
+const VECTPENDING_OFFSET: u32 = 12;
+const VECTPENDING_MASK: u32 = 0x1FF << VECTPENDING_OFFSET;
+
+/// The exception that woke [`block_on`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WakeSource {
+    /// A processor exception, like SysTick, by exception number
+    Exception(u8),
+    /// A device interrupt, by IRQ number
+    Interrupt(u16),
+}
+
+/// Sleep statistics from [`block_on`]
+///
+/// See [`sleep_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SleepStats {
+    /// CPU cycles spent in WFI
+    ///
+    /// The count wraps once it exceeds `u32::MAX`. It's only valid if the DWT cycle
+    /// counter is enabled; otherwise, it's zero.
+    pub sleep_cycles: u32,
+    /// Number of times the processor woke from WFI
+    pub wakes: u32,
+    /// The exception that ended the last WFI, or `None` if there hasn't been a wakeup
+    pub last_wake: Option<WakeSource>,
+}
+
+/// Returns the sleep statistics from every [`block_on`] call
+pub fn sleep_stats() -> SleepStats {
+    let last_wake = match LAST_WAKE.load(Ordering::Relaxed) {
+        0 => None,
+        number if number < 16 => Some(WakeSource::Exception(number as u8)),
+        number => Some(WakeSource::Interrupt((number - 16) as u16)),
+    };
+    SleepStats {
+        sleep_cycles: SLEEP_CYCLES.load(Ordering::Relaxed),
+        wakes: WAKES.load(Ordering::Relaxed),
+        last_wake,
+    }
+}
+
+/// Clear the sleep statistics
+pub fn reset_sleep_stats() {
+    cortex_m::interrupt::free(|_| {
+        SLEEP_CYCLES.store(0, Ordering::Relaxed);
+        WAKES.store(0, Ordering::Relaxed);
+        LAST_WAKE.store(0, Ordering::Relaxed);
+    });
+}
+
+/// Sleep with WFI until an interrupt is pending, unless a waker was already woken
+///
+/// Interrupts are masked around the WFI, so that a wake just before the WFI isn't
+/// missed, and so that the pending interrupt identifies the wake source. If interrupts
+/// were enabled, the handler runs once `sleep` returns.
+fn sleep() {
+    let enabled = cortex_m::register::primask::read().is_active();
+    cortex_m::interrupt::disable();
+    if !WOKEN.load(Ordering::Acquire) {
+        let start = DWT::get_cycle_count();
+        {
+            #[cfg(feature = "bench")]
+            let _measurement = crate::bench::Measurement::start(crate::bench::Probe::Sleep);
+            cortex_m::asm::wfi();
+        }
+        let cycles = DWT::get_cycle_count().wrapping_sub(start);
+        // Safety: reading ICSR has no side effects. VECTPENDING is the highest priority
+        // pending exception, which woke the processor.
+        let icsr = unsafe { (*SCB::ptr()).icsr.read() };
+        let number = (icsr & VECTPENDING_MASK) >> VECTPENDING_OFFSET;
+        SLEEP_CYCLES.store(
+            SLEEP_CYCLES.load(Ordering::Relaxed).wrapping_add(cycles),
+            Ordering::Relaxed,
+        );
+        WAKES.store(
+            WAKES.load(Ordering::Relaxed).wrapping_add(1),
+            Ordering::Relaxed,
+        );
+        if number != 0 {
+            LAST_WAKE.store(number, Ordering::Relaxed);
+        }
+    }
+    if enabled {
+        // Safety: interrupts were enabled when sleep() was called.
+        unsafe { cortex_m::interrupt::enable() };
+    }
+}
+
+/// Run `future` to completion, sleeping between polls
+///
+/// If interrupts are disabled when you call `block_on`, it polls `future` after every
+/// wakeup. See the [module-level documentation](crate::blocking) for more information.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = future;
+    // Safety: future is shadowed, so it's never moved again.
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    // Safety: the vtable functions do nothing with the data pointer.
+    let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) };
+    let mut cx = Context::from_waker(&waker);
+    // With interrupts disabled, no interrupt handler can wake the future. Poll it after
+    // every wakeup, and let it observe the hardware state.
+    let masked = !cortex_m::register::primask::read().is_active();
+    loop {
+        WOKEN.store(false, Ordering::Relaxed);
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        while !WOKEN.load(Ordering::Acquire) {
+            sleep();
+            if masked {
+                break;
+            }
+        }
+    }
+}
+
+/// A driver adapter whose operations block until completion
+///
+/// Use a driver's `blocking()` method to create a `Blocking` adapter. See the
+/// [module-level documentation](crate::blocking) for more information.
+pub struct Blocking<'a, D> {
+    driver: &'a mut D,
+}
+
+impl<'a, D> Blocking<'a, D> {
+    pub(crate) fn new(driver: &'a mut D) -> Self {
+        Blocking { driver }
+    }
+}
+
+#[cfg(feature = "gpt")]
+impl crate::GPT {
+    /// Returns an adapter that blocks until each delay elapses
+    pub fn blocking(&mut self) -> Blocking<'_, Self> {
+        Blocking::new(self)
+    }
+}
+
+#[cfg(feature = "gpt")]
+impl Blocking<'_, crate::GPT> {
+    /// Wait for `ticks` clock counts to elapse
+    pub fn delay(&mut self, ticks: u32) {
+        block_on(self.driver.delay(ticks))
+    }
+}
+
+#[cfg(feature = "pit")]
+impl crate::PIT {
+    /// Returns an adapter that blocks until each delay elapses
+    pub fn blocking(&mut self) -> Blocking<'_, Self> {
+        Blocking::new(self)
+    }
+}
+
+#[cfg(feature = "pit")]
+impl Blocking<'_, crate::PIT> {
+    /// Wait for the counts to elapse
+    pub fn delay(&mut self, count: u32) {
+        block_on(self.driver.delay(count))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<SCL, SDA> crate::I2C<SCL, SDA> {
+    /// Returns an adapter that blocks until each transaction completes
+    pub fn blocking(&mut self) -> Blocking<'_, Self> {
+        Blocking::new(self)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<SCL, SDA> Blocking<'_, crate::I2C<SCL, SDA>> {
+    /// Perform an I2C write
+    pub fn write(&mut self, address: u8, buffer: &[u8]) -> Result<(), crate::I2CError> {
+        block_on(self.driver.write(address, buffer))
+    }
+
+    /// Perform an I2C read
+    pub fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), crate::I2CError> {
+        block_on(self.driver.read(address, buffer))
+    }
+
+    /// Perform an I2C write-read
+    pub fn write_read(
+        &mut self,
+        address: u8,
+        output: &[u8],
+        input: &mut [u8],
+    ) -> Result<(), crate::I2CError> {
+        block_on(self.driver.write_read(address, output, input))
+    }
+}
+
+#[cfg(feature = "uart")]
+impl<TX, RX> crate::UART<TX, RX> {
+    /// Returns an adapter that blocks until each transfer completes
+    pub fn blocking(&mut self) -> Blocking<'_, Self> {
+        Blocking::new(self)
+    }
+}
+
+#[cfg(feature = "uart")]
+impl<TX, RX> Blocking<'_, crate::UART<TX, RX>> {
+    /// Use a DMA channel to write `buffer` to the UART peripheral
+    pub fn write(
+        &mut self,
+        channel: &mut crate::dma::Channel,
+        buffer: &[u8],
+    ) -> Result<(), crate::dma::Error> {
+        block_on(self.driver.dma_write(channel, buffer))
+    }
+
+    /// Use a DMA channel to fill `buffer` with data from the UART peripheral
+    pub fn read(
+        &mut self,
+        channel: &mut crate::dma::Channel,
+        buffer: &mut [u8],
+    ) -> Result<(), crate::dma::Error> {
+        block_on(self.driver.dma_read(channel, buffer))
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<Pins> crate::SPI<Pins> {
+    /// Returns an adapter that blocks until each transfer completes
+    pub fn blocking(&mut self) -> Blocking<'_, Self> {
+        Blocking::new(self)
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<Pins> Blocking<'_, crate::SPI<Pins>> {
+    /// Use a DMA channel to write `buffer` to the SPI peripheral
+    pub fn write<E: crate::dma::Element>(
+        &mut self,
+        channel: &mut crate::dma::Channel,
+        buffer: &[E],
+    ) -> Result<(), crate::dma::Error> {
+        block_on(self.driver.dma_write(channel, buffer))
+    }
+
+    /// Use a DMA channel to read data from the SPI peripheral into `buffer`
+    pub fn read<E: crate::dma::Element>(
+        &mut self,
+        channel: &mut crate::dma::Channel,
+        buffer: &mut [E],
+    ) -> Result<(), crate::dma::Error> {
+        block_on(self.driver.dma_read(channel, buffer))
+    }
+
+    /// Use two DMA channels to perform a full-duplex transfer
+    pub fn full_duplex<E: crate::dma::Element>(
+        &mut self,
+        rx_channel: &mut crate::dma::Channel,
+        tx_channel: &mut crate::dma::Channel,
+        buffer: &mut [E],
+    ) -> Result<(), crate::dma::Error> {
+        block_on(self.driver.dma_full_duplex(rx_channel, tx_channel, buffer))
+    }
+}
@@ -0,0 +1,72 @@
+//! Data cache maintenance by address
+//!
+//! These functions write the Cortex-M7 cache maintenance registers directly. Unlike
+//! `cortex_m::Peripherals::steal()`, they don't mark the core peripherals as taken, so
+//! they don't interfere with a user's `cortex_m::Peripherals::take()`.
+//!
+//! Each function acts on every cache line that overlaps the memory, even if the memory
+//! doesn't start or end on a cache line boundary.
+
+use cortex_m::peripheral::CBP;
+
+/// Size of a data cache line, in bytes
+const LINE_SIZE: usize = 32;
+
+/// Returns the cache lines that overlap `len` bytes at `address`, as a range of line
+/// addresses
+fn lines(address: usize, len: usize) -> impl Iterator<Item = usize> {
+    let start = address & !(LINE_SIZE - 1);
+    let end = if len == 0 {
+        start
+    } else {
+        (address + len + LINE_SIZE - 1) & !(LINE_SIZE - 1)
+    };
+    (start..end).step_by(LINE_SIZE)
+}
+
+/// Write any dirty cache lines that hold `len` bytes at `address` to memory
+pub fn clean(address: usize, len: usize) {
+    cortex_m::asm::dsb();
+    for line in lines(address, len) {
+        // Safety: cleaning the cache does not change memory contents as observed
+        // by the CPU. The write-only register is safe to write from any context.
+        unsafe { (*CBP::ptr()).dccmvac.write(line as u32) };
+    }
+    cortex_m::asm::dsb();
+    cortex_m::asm::isb();
+}
+
+/// Write any dirty cache lines that hold `len` bytes at `address` to memory, then
+/// invalidate those lines
+pub fn clean_invalidate(address: usize, len: usize) {
+    cortex_m::asm::dsb();
+    for line in lines(address, len) {
+        // Safety: see clean()
+        unsafe { (*CBP::ptr()).dccimvac.write(line as u32) };
+    }
+    cortex_m::asm::dsb();
+    cortex_m::asm::isb();
+}
+
+/// Invalidate the cache lines that hold `len` bytes at `address`
+///
+/// Cache lines that only partly hold the memory are cleaned and invalidated, so that
+/// CPU writes to the neighbouring memory aren't lost.
+///
+/// # Safety
+///
+/// Any CPU writes to the memory that are still in the cache are lost.
+pub unsafe fn invalidate(address: usize, len: usize) {
+    let end = address + len;
+    let partial = |line: usize| line < address || line + LINE_SIZE > end;
+    cortex_m::asm::dsb();
+    for line in lines(address, len) {
+        if partial(line) {
+            (*CBP::ptr()).dccimvac.write(line as u32);
+        } else {
+            (*CBP::ptr()).dcimvac.write(line as u32);
+        }
+    }
+    cortex_m::asm::dsb();
+    cortex_m::asm::isb();
+}
@@ -0,0 +1,192 @@
+//! Clock setup
+//!
+//! Each driver expects you to configure its clock root, and to turn on its clock gate.
+//! [`setup_defaults`] configures the clock roots used by this crate's drivers, and it
+//! turns on the DMA clock gate. It returns the resulting clock frequencies, which you
+//! pass to APIs like [`set_baud`](crate::UART::set_baud()) and
+//! [`set_clock_speed`](crate::SPI::set_clock_speed()).
+//!
+//! You're still responsible for turning on each peripheral's clock gate. In debug builds,
+//! driver constructors check that their peripheral's clock gate is on. If it's off,
+//! the constructor panics with a message that names the gate. Release builds skip the
+//! check. The clock roots have no enable, so they're not checked.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::ral::{self, ccm::CCM};
+//!
+//! let ccm = CCM::take().unwrap();
+//! let clocks = hal::ccm::setup_defaults(&ccm);
+//!
+//! // LPUART2 clock gate on
+//! ral::modify_reg!(ral::ccm, ccm, CCGR0, CG14: 0b11);
+//!
+//! let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let uart2 = ral::lpuart::LPUART2::take()
+//!     .and_then(hal::instance::uart)
+//!     .unwrap();
+//! let mut uart = hal::UART::new(uart2, pads.ad_b1.p02, pads.ad_b1.p03);
+//! uart.set_baud(115_200, clocks.uart_hz).unwrap();
+//! ```
+
+use crate::ral;
+
+/// Crystal oscillator frequency
+const OSCILLATOR_HZ: u32 = 24_000_000;
+/// PLL2 (system PLL) frequency
+const PLL2_HZ: u32 = 528_000_000;
+
+const PERCLK_DIVIDER: u32 = 24;
+const UART_DIVIDER: u32 = 1;
+const SPI_DIVIDER: u32 = 5;
+const I2C_DIVIDER: u32 = 3;
+
+/// Clock root frequencies
+///
+/// Returned from [`setup_defaults`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clocks {
+    /// The periodic clock, used by the PIT and GPT timers
+    pub perclk_hz: u32,
+    /// The LPUART clock root
+    pub uart_hz: u32,
+    /// The LPSPI clock root
+    pub spi_hz: u32,
+    /// The LPI2C clock root
+    pub i2c_hz: u32,
+}
+
+/// Configure the clock roots, and turn on the DMA clock gate
+///
+/// | Clock root | Source             | Divider | Frequency |
+/// | ---------- | ------------------ | ------- | --------- |
+/// | PERCLK     | Crystal oscillator | 24      | 1MHz      |
+/// | LPUART     | Crystal oscillator | 1       | 24MHz     |
+/// | LPSPI      | PLL2               | 5       | 105.6MHz  |
+/// | LPI2C      | Crystal oscillator | 3       | 8MHz      |
+///
+/// The LPSPI root expects that PLL2 is running at 528MHz, which is the boot ROM's
+/// configuration.
+pub fn setup_defaults(ccm: &ral::ccm::Instance) -> Clocks {
+    ral::modify_reg!(ral::ccm, ccm, CSCMR1, PERCLK_PODF: PERCLK_DIVIDER - 1, PERCLK_CLK_SEL: 1 /* Oscillator */);
+    ral::modify_reg!(ral::ccm, ccm, CSCDR1, UART_CLK_SEL: 1 /* Oscillator */, UART_CLK_PODF: UART_DIVIDER - 1);
+    ral::modify_reg!(ral::ccm, ccm, CBCMR, LPSPI_CLK_SEL: LPSPI_CLK_SEL_2 /* PLL2 */, LPSPI_PODF: SPI_DIVIDER - 1);
+    ral::modify_reg!(ral::ccm, ccm, CSCDR2, LPI2C_CLK_SEL: 1 /* Oscillator */, LPI2C_CLK_PODF: I2C_DIVIDER - 1);
+    // DMA clock gate on
+    ral::modify_reg!(ral::ccm, ccm, CCGR5, CG3: 0b11);
+
+    Clocks {
+        perclk_hz: OSCILLATOR_HZ / PERCLK_DIVIDER,
+        uart_hz: OSCILLATOR_HZ / UART_DIVIDER,
+        spi_hz: PLL2_HZ / SPI_DIVIDER,
+        i2c_hz: OSCILLATOR_HZ / I2C_DIVIDER,
+    }
+}
+
+/// A clock gate, as a CCGR register number and a CG field number
+type Gate = (u8, u8);
+
+/// Peripherals whose clock gates are checked in debug builds
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Peripheral {
+    #[cfg(feature = "uart")]
+    UART(usize),
+    #[cfg(feature = "spi")]
+    SPI(usize),
+    #[cfg(feature = "i2c")]
+    I2C(usize),
+    #[cfg(feature = "gpio")]
+    GPIO(usize),
+    #[cfg(feature = "gpt")]
+    GPT(usize),
+    #[cfg(feature = "pit")]
+    PIT,
+    DMA,
+}
+
+impl Peripheral {
+    /// Returns the peripheral's name, and its clock gates
+    ///
+    /// The imxrt1010 uses the same gates as the imxrt1060, for the instances that it has.
+    fn gates(self) -> (&'static str, Option<usize>, &'static [Gate]) {
+        match self {
+            #[cfg(feature = "uart")]
+            Peripheral::UART(inst) => {
+                const GATES: [[Gate; 1]; 8] = [
+                    [(5, 12)],
+                    [(0, 14)],
+                    [(0, 6)],
+                    [(1, 12)],
+                    [(3, 1)],
+                    [(3, 3)],
+                    [(5, 13)],
+                    [(6, 7)],
+                ];
+                ("LPUART", Some(inst), &GATES[inst - 1])
+            }
+            #[cfg(feature = "spi")]
+            Peripheral::SPI(inst) => {
+                const GATES: [[Gate; 1]; 4] = [[(1, 0)], [(1, 1)], [(1, 2)], [(1, 3)]];
+                ("LPSPI", Some(inst), &GATES[inst - 1])
+            }
+            #[cfg(feature = "i2c")]
+            Peripheral::I2C(inst) => {
+                const GATES: [[Gate; 1]; 4] = [[(2, 3)], [(2, 4)], [(2, 5)], [(6, 12)]];
+                ("LPI2C", Some(inst), &GATES[inst - 1])
+            }
+            #[cfg(feature = "gpio")]
+            Peripheral::GPIO(inst) => {
+                const GATES: [[Gate; 1]; 5] =
+                    [[(1, 13)], [(0, 15)], [(2, 13)], [(3, 6)], [(1, 15)]];
+                ("GPIO", Some(inst), &GATES[inst - 1])
+            }
+            #[cfg(feature = "gpt")]
+            Peripheral::GPT(inst) => {
+                // The bus clock gate, then the serial clock gate
+                const GATES: [[Gate; 2]; 2] = [[(1, 10), (1, 11)], [(0, 12), (0, 13)]];
+                ("GPT", Some(inst), &GATES[inst - 1])
+            }
+            #[cfg(feature = "pit")]
+            Peripheral::PIT => ("PIT", None, &[(1, 6)]),
+            Peripheral::DMA => ("DMA", None, &[(5, 3)]),
+        }
+    }
+}
+
+/// Panics if any of the peripheral's clock gates are off
+///
+/// Only checks in debug builds.
+pub(crate) fn assert_gates_on(peripheral: Peripheral) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let (name, inst, gates) = peripheral.gates();
+    for &(ccgr, cg) in gates {
+        // Safety: atomic read of a CCM register. It doesn't affect any other CCM user.
+        let value = unsafe {
+            match ccgr {
+                0 => ral::read_reg!(ral::ccm, ral::ccm::CCM, CCGR0),
+                1 => ral::read_reg!(ral::ccm, ral::ccm::CCM, CCGR1),
+                2 => ral::read_reg!(ral::ccm, ral::ccm::CCM, CCGR2),
+                3 => ral::read_reg!(ral::ccm, ral::ccm::CCM, CCGR3),
+                5 => ral::read_reg!(ral::ccm, ral::ccm::CCM, CCGR5),
+                6 => ral::read_reg!(ral::ccm, ral::ccm::CCM, CCGR6),
+                _ => unreachable!("No checked gates in CCGR{}", ccgr),
+            }
+        };
+        if (value >> (2 * cg)) & 0b11 == 0 {
+            match inst {
+                Some(inst) => panic!(
+                    "{}{} clock gate is off; turn on CCM CCGR{} CG{} before creating the driver",
+                    name, inst, ccgr, cg
+                ),
+                None => panic!(
+                    "{} clock gate is off; turn on CCM CCGR{} CG{} before creating the driver",
+                    name, ccgr, cg
+                ),
+            }
+        }
+    }
+}
@@ -5,18 +5,301 @@
 //! Then, use the `Channel`s in APIs that require them. The implementation handles
 //! DMA receive and transfer operations, and ensures that the lifetime of your buffers
 //! is correct.
+//!
+//! # Caches
+//!
+//! The DMA controller does not see the Cortex-M7 data cache. If a buffer is in cacheable
+//! memory, like OCRAM or SDRAM, the DMA operations maintain the cache for you:
+//!
+//! - [`transfer`](transfer()) cleans the buffer from the cache before the DMA controller
+//!   reads it.
+//! - [`receive`](receive()) and [`full_duplex`](full_duplex()) clean and invalidate the
+//!   buffer before the DMA controller writes it, then invalidate the buffer once you
+//!   drop the future, whether or not the operation completed.
+//!
+//! Buffers in TCM are never cached, and they do not need cache maintenance.
+//!
+//! Cache maintenance acts on 32 byte cache lines. If a receive buffer in cacheable memory
+//! shares a cache line with other data, the DMA operations clean that line rather than
+//! discard it, but CPU writes to that other data during the DMA operation may overwrite
+//! the received data. Align receive buffers to 32 bytes, and size them in multiples of
+//! 32 bytes. A [`DmaBuffer`] meets these requirements.
+//!
+//! # Low-level futures
+//!
+//! The futures that wait for a DMA channel's interrupt are implemented by the `imxrt-dma`
+//! crate, and this module doesn't expose a lower-level constructor than
+//! [`transfer`](transfer()), [`receive`](receive()) and [`full_duplex`](full_duplex()).
+//! Those functions are the building blocks for DMA transfers with your own peripherals;
+//! implement `imxrt-dma`'s peripheral traits for your peripheral to use them.
 
 #![allow(non_snake_case)] // Compatibility with RAL
 
 pub(crate) use imxrt_dma::peripheral::{Bidirectional, Destination, Source};
-pub use imxrt_dma::{
-    peripheral::{full_duplex, receive, transfer, FullDuplex, Rx, Tx},
-    Element,
-};
+pub use imxrt_dma::{peripheral::Tx, Element};
 
 use crate::ral;
+use core::{
+    future::Future,
+    mem::ManuallyDrop,
+    pin::Pin,
+    task::{Context, Poll},
+};
 pub use imxrt_dma::{BandwidthControl, Channel, Error};
 
+/// Returns `true` if the memory at `address` may be in the data cache
+///
+/// ITCM starts at 0x0000_0000, and DTCM starts at 0x2000_0000. Each is at most 512KiB.
+fn is_cacheable(address: usize) -> bool {
+    const TCM_SIZE: usize = 512 * 1024;
+    const ITCM: usize = 0x0000_0000;
+    const DTCM: usize = 0x2000_0000;
+    !(ITCM..ITCM + TCM_SIZE).contains(&address) && !(DTCM..DTCM + TCM_SIZE).contains(&address)
+}
+
+/// Write any dirty cache lines that hold `buffer` to memory
+fn clean<E>(buffer: &[E]) {
+    let address = buffer.as_ptr() as usize;
+    if is_cacheable(address) {
+        crate::cache::clean(address, core::mem::size_of_val(buffer));
+    }
+}
+
+/// Write any dirty cache lines that hold `buffer` to memory, then invalidate those lines
+fn clean_invalidate<E>(buffer: &[E]) {
+    let address = buffer.as_ptr() as usize;
+    if is_cacheable(address) {
+        crate::cache::clean_invalidate(address, core::mem::size_of_val(buffer));
+    }
+}
+
+/// Invalidate the cache lines that hold `len` bytes at `address`
+///
+/// # Safety
+///
+/// Any CPU writes to the memory that are still in the cache are lost.
+unsafe fn invalidate(address: usize, len: usize) {
+    if is_cacheable(address) {
+        crate::cache::invalidate(address, len);
+    }
+}
+
+/// A buffer of `N` `T`s for DMA operations
+///
+/// A `DmaBuffer` is aligned to, and sized in multiples of, a 32 byte cache line. It never
+/// shares a cache line with other data, so cache maintenance in the DMA operations cannot
+/// affect other variables. A `DmaBuffer` dereferences to a slice, so you may use it anywhere
+/// that a DMA operation expects a buffer.
+///
+/// To skip cache maintenance, place your `DmaBuffer`s in the `.dma_buffer` link section,
+/// and have your linker script place that section in DTCM. DMA operations detect buffers
+/// in DTCM, and skip their cache maintenance. The Teensy 4 examples' linker script places
+/// `.dma_buffer` in DTCM, and their `pre_init` function copies the section's initial
+/// values from flash. If you instead place the section in OCRAM, it's cacheable unless
+/// you cover it with a non-cacheable [`mpu`](crate::mpu) region, and the DMA operations
+/// still perform cache maintenance.
+///
+/// # Example
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::dma::DmaBuffer;
+///
+/// #[link_section = ".dma_buffer"]
+/// static mut BUFFER: DmaBuffer<u8, 64> = DmaBuffer::new([0; 64]);
+///
+/// let buffer: &mut [u8] = unsafe { &mut BUFFER };
+/// assert_eq!(buffer.len(), 64);
+/// ```
+#[repr(C, align(32))]
+pub struct DmaBuffer<T, const N: usize> {
+    buffer: [T; N],
+}
+
+impl<T, const N: usize> DmaBuffer<T, N> {
+    /// Create a `DmaBuffer` that holds `buffer`
+    pub const fn new(buffer: [T; N]) -> Self {
+        DmaBuffer { buffer }
+    }
+
+    /// Return the inner array
+    pub fn into_inner(self) -> [T; N] {
+        self.buffer
+    }
+}
+
+impl<T, const N: usize> core::ops::Deref for DmaBuffer<T, N> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.buffer
+    }
+}
+
+impl<T, const N: usize> core::ops::DerefMut for DmaBuffer<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.buffer
+    }
+}
+
+/// Use a DMA channel to transfer `buffer` to `destination`
+///
+/// `transfer` cleans `buffer` from the data cache before the transfer starts.
+pub fn transfer<'a, D, E>(
+    channel: &'a mut Channel,
+    buffer: &'a [E],
+    destination: &'a mut D,
+) -> Tx<'a, D, E>
+where
+    D: Destination<E>,
+    E: Element,
+{
+    #[cfg(feature = "bench")]
+    let _measurement = crate::bench::Measurement::start(crate::bench::Probe::DmaSetup);
+    clean(buffer);
+    imxrt_dma::peripheral::transfer(channel, buffer, destination)
+}
+
+/// Use a DMA channel to receive data from `source` into `buffer`
+///
+/// `receive` maintains the data cache for `buffer`. See the [module-level
+/// documentation](crate::dma) for more information.
+pub fn receive<'a, S, E>(
+    channel: &'a mut Channel,
+    source: &'a mut S,
+    buffer: &'a mut [E],
+) -> Rx<'a, S, E>
+where
+    S: Source<E>,
+    E: Element,
+{
+    #[cfg(feature = "bench")]
+    let _measurement = crate::bench::Measurement::start(crate::bench::Probe::DmaSetup);
+    clean_invalidate(buffer);
+    let address = buffer.as_ptr() as usize;
+    let len = core::mem::size_of_val(buffer);
+    Rx {
+        rx: ManuallyDrop::new(imxrt_dma::peripheral::receive(channel, source, buffer)),
+        address,
+        len,
+    }
+}
+
+/// Use two DMA channels to send `buffer` to, and receive data from, `peripheral`
+///
+/// The received data replaces the contents of `buffer`. `full_duplex` maintains the data
+/// cache for `buffer`. See the [module-level documentation](crate::dma) for more information.
+pub fn full_duplex<'a, P, E>(
+    rx_channel: &'a mut Channel,
+    tx_channel: &'a mut Channel,
+    peripheral: &'a mut P,
+    buffer: &'a mut [E],
+) -> FullDuplex<'a, P, E>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    #[cfg(feature = "bench")]
+    let _measurement = crate::bench::Measurement::start(crate::bench::Probe::DmaSetup);
+    clean_invalidate(buffer);
+    let address = buffer.as_ptr() as usize;
+    let len = core::mem::size_of_val(buffer);
+    FullDuplex {
+        full_duplex: ManuallyDrop::new(imxrt_dma::peripheral::full_duplex(
+            rx_channel, tx_channel, peripheral, buffer,
+        )),
+        address,
+        len,
+    }
+}
+
+/// A future that receives data into a buffer
+///
+/// Use [`receive`](receive()) to create this future.
+pub struct Rx<'a, S, E>
+where
+    S: Source<E>,
+    E: Element,
+{
+    rx: ManuallyDrop<imxrt_dma::peripheral::Rx<'a, S, E>>,
+    address: usize,
+    len: usize,
+}
+
+impl<'a, S, E> Future for Rx<'a, S, E>
+where
+    S: Source<E>,
+    E: Element,
+    imxrt_dma::peripheral::Rx<'a, S, E>: Future,
+{
+    type Output = <imxrt_dma::peripheral::Rx<'a, S, E> as Future>::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the inner future is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        unsafe { Pin::new_unchecked(&mut *this.rx) }.poll(cx)
+    }
+}
+
+impl<'a, S, E> Drop for Rx<'a, S, E>
+where
+    S: Source<E>,
+    E: Element,
+{
+    fn drop(&mut self) {
+        // Safety: the inner future is dropped in place, and only once. Dropping it
+        // cancels any incomplete transfer, so the DMA controller no longer writes the
+        // buffer. The buffer was invalidated before the DMA controller wrote it, and
+        // the caller could not write it while it was borrowed.
+        unsafe {
+            ManuallyDrop::drop(&mut self.rx);
+            invalidate(self.address, self.len);
+        }
+    }
+}
+
+/// A future that sends and receives data using a single buffer
+///
+/// Use [`full_duplex`](full_duplex()) to create this future.
+pub struct FullDuplex<'a, P, E>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    full_duplex: ManuallyDrop<imxrt_dma::peripheral::FullDuplex<'a, P, E>>,
+    address: usize,
+    len: usize,
+}
+
+impl<'a, P, E> Future for FullDuplex<'a, P, E>
+where
+    P: Bidirectional<E>,
+    E: Element,
+    imxrt_dma::peripheral::FullDuplex<'a, P, E>: Future,
+{
+    type Output = <imxrt_dma::peripheral::FullDuplex<'a, P, E> as Future>::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the inner future is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        unsafe { Pin::new_unchecked(&mut *this.full_duplex) }.poll(cx)
+    }
+}
+
+impl<'a, P, E> Drop for FullDuplex<'a, P, E>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    fn drop(&mut self) {
+        // Safety: the inner future is dropped in place, and only once. Dropping it
+        // cancels any incomplete transfer, so the DMA controller no longer writes the
+        // buffer. The buffer was invalidated before the DMA controller wrote it, and
+        // the caller could not write it while it was borrowed.
+        unsafe {
+            ManuallyDrop::drop(&mut self.full_duplex);
+            invalidate(self.address, self.len);
+        }
+    }
+}
+
 #[cfg(not(feature = "imxrt1010"))]
 pub const CHANNEL_COUNT: usize = 32;
 #[cfg(feature = "imxrt1010")]
@@ -56,6 +339,7 @@ pub const CHANNEL_COUNT: usize = 16;
 pub fn channels(dma: ral::dma0::Instance, mux: ral::dmamux::Instance) -> [Option<Channel>; 32] {
     drop(dma);
     drop(mux);
+    crate::ccm::assert_gates_on(crate::ccm::Peripheral::DMA);
 
     let mut channels = [
         None, None, None, None, None, None, None, None, None, None, None, None, None, None, None,
@@ -114,152 +398,327 @@ pub fn channels(dma: ral::dma0::Instance, mux: ral::dmamux::Instance) -> [Option
     channels
 }
 
+/// Channel selection for an [`Allocator`]
+///
+/// When two channels request service at the same time, the DMA controller serves the
+/// higher-numbered channel first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Prefer the lowest-numbered free channel
+    Low,
+    /// Prefer the highest-numbered free channel
+    High,
+}
+
+/// Hands out DMA channels on request
+///
+/// An `Allocator` owns the channels returned from [`channels`](channels()). Instead of
+/// picking channel numbers yourself, [`take`](Allocator::take()) the next free channel.
+///
+/// On chips with 32 channels, channels `n` and `n + 16` share an interrupt. When it can,
+/// `take` returns a channel whose partner is also free, so that the channel doesn't share
+/// its interrupt with another transfer.
+///
+/// # Example
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::dma::{self, Priority};
+/// use hal::ral::{dma0::DMA0, dmamux::DMAMUX};
+///
+/// let mut dma = dma::Allocator::new(dma::channels(
+///     DMA0::take().unwrap(),
+///     DMAMUX::take().unwrap(),
+/// ));
+///
+/// let uart_tx = dma.take(Priority::Low).unwrap();
+/// let spi_rx = dma.take(Priority::High).unwrap();
+/// let fixed = dma.take_channel(7).unwrap();
+///
+/// dma.release(fixed);
+/// ```
+pub struct Allocator {
+    channels: [Option<Channel>; 32],
+}
+
+impl Allocator {
+    /// Create an allocator that owns `channels`
+    pub fn new(channels: [Option<Channel>; 32]) -> Self {
+        Allocator { channels }
+    }
+
+    /// Take a free channel
+    ///
+    /// Returns `None` if there are no free channels.
+    pub fn take(&mut self, priority: Priority) -> Option<Channel> {
+        let mut candidates = (0..CHANNEL_COUNT).map(|idx| match priority {
+            Priority::Low => idx,
+            Priority::High => CHANNEL_COUNT - 1 - idx,
+        });
+        let unshared = candidates.clone().find(|&idx| {
+            let partner = idx ^ 16;
+            self.channels[idx].is_some()
+                && (partner >= CHANNEL_COUNT || self.channels[partner].is_some())
+        });
+        let idx = unshared.or_else(|| candidates.find(|&idx| self.channels[idx].is_some()))?;
+        self.channels[idx].take()
+    }
+
+    /// Take channel number `channel`
+    ///
+    /// Returns `None` if the channel is already taken, or if it doesn't exist.
+    pub fn take_channel(&mut self, channel: usize) -> Option<Channel> {
+        self.channels.get_mut(channel)?.take()
+    }
+
+    /// Return a channel to the allocator, so that it may be taken again
+    pub fn release(&mut self, channel: Channel) {
+        let idx = channel.channel();
+        self.channels[idx] = Some(channel);
+    }
+
+    /// Returns the number of free channels
+    pub fn available(&self) -> usize {
+        self.channels
+            .iter()
+            .filter(|channel| channel.is_some())
+            .count()
+    }
+}
+
+/// A snapshot of a DMA channel's transfer control descriptor (TCD) and status
+///
+/// Use [`channel_state`](channel_state()) to inspect a transfer that isn't completing.
+#[cfg(feature = "diagnostics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelState {
+    /// The channel number
+    pub channel: usize,
+    /// The next source address
+    pub source: u32,
+    /// The next destination address
+    pub destination: u32,
+    /// Bytes transferred for each service request
+    pub minor_loop_bytes: u32,
+    /// Service requests remaining in the transfer
+    pub current_iterations: u16,
+    /// Service requests in the whole transfer
+    pub beginning_iterations: u16,
+    /// The channel is executing
+    pub active: bool,
+    /// The channel finished its transfer
+    pub done: bool,
+    /// The channel accepts hardware service requests
+    pub request_enabled: bool,
+    /// The channel's interrupt is pending
+    pub interrupt: bool,
+    /// The channel has an error
+    pub error: bool,
+    /// The DMA error status register (ES)
+    ///
+    /// ES describes the most recent error, on any channel.
+    pub error_status: u32,
+}
+
+/// Read the state of DMA channel number `channel`
+///
+/// `channel_state` doesn't modify the channel, so you may call it while a transfer
+/// is in progress.
+///
+/// # Panics
+///
+/// Panics if `channel` is not less than [`CHANNEL_COUNT`].
+///
+/// # Example
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+///
+/// let state = hal::dma::channel_state(7);
+/// if state.request_enabled && state.current_iterations == state.beginning_iterations {
+///     // The peripheral never requested data...
+/// }
+/// ```
+#[cfg(feature = "diagnostics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+pub fn channel_state(channel: usize) -> ChannelState {
+    assert!(channel < CHANNEL_COUNT, "DMA channel is out of range");
+    // Safety: reads have no side effects on the DMA controller, or on the channel.
+    let dma = unsafe { ral::dma0::DMA0::steal() };
+    let mask = 1 << channel;
+    // Each TCD is 32 bytes, starting 0x1000 bytes from the DMA base address
+    let tcd = (ral::dma0::DMA0 as usize + 0x1000 + 32 * channel) as *const u8;
+    // Safety: TCD addresses are valid, and aligned for their register sizes.
+    let read_u32 =
+        |offset: usize| unsafe { core::ptr::read_volatile(tcd.add(offset) as *const u32) };
+    let read_u16 =
+        |offset: usize| unsafe { core::ptr::read_volatile(tcd.add(offset) as *const u16) };
+    let csr = read_u16(28);
+    ChannelState {
+        channel,
+        source: read_u32(0),
+        destination: read_u32(16),
+        minor_loop_bytes: read_u32(8),
+        // Channel linking isn't used, so iteration counts are 15 bits
+        current_iterations: read_u16(22) & 0x7FFF,
+        beginning_iterations: read_u16(30) & 0x7FFF,
+        active: csr & (1 << 6) != 0,
+        done: csr & (1 << 7) != 0,
+        request_enabled: ral::read_reg!(ral::dma0, dma, ERQ) & mask != 0,
+        interrupt: ral::read_reg!(ral::dma0, dma, INT) & mask != 0,
+        error: ral::read_reg!(ral::dma0, dma, ERR) & mask != 0,
+        error_status: ral::read_reg!(ral::dma0, dma, ES),
+    }
+}
+
+/// Wake the `channels` that have a pending interrupt or error
+///
+/// Channels that share an interrupt, but have nothing pending, are not touched.
+#[inline(always)]
+#[cfg_attr(not(target_arch = "arm"), allow(unused))]
+unsafe fn on_interrupt(channels: &[usize]) {
+    #[cfg(feature = "bench")]
+    let _measurement = crate::bench::Measurement::start(crate::bench::Probe::DmaInterrupt);
+    let dma = ral::dma0::DMA0::steal();
+    let pending = ral::read_reg!(ral::dma0, dma, INT) | ral::read_reg!(ral::dma0, dma, ERR);
+    channels
+        .iter()
+        .copied()
+        .filter(|&channel| pending & (1 << channel) != 0)
+        .for_each(|channel| imxrt_dma::on_interrupt(channel));
+}
+
 #[cfg(not(feature = "imxrt1010"))]
 interrupts! {
     handler!{unsafe fn DMA0_DMA16() {
-        imxrt_dma::on_interrupt(0);
-        imxrt_dma::on_interrupt(16);
+        on_interrupt(&[0, 16]);
     }}
 
     handler!{unsafe fn DMA1_DMA17() {
-        imxrt_dma::on_interrupt(1);
-        imxrt_dma::on_interrupt(17);
+        on_interrupt(&[1, 17]);
     }}
 
     handler!{unsafe fn DMA2_DMA18() {
-        imxrt_dma::on_interrupt(2);
-        imxrt_dma::on_interrupt(18);
+        on_interrupt(&[2, 18]);
     }}
 
     handler!{unsafe fn DMA3_DMA19() {
-        imxrt_dma::on_interrupt(3);
-        imxrt_dma::on_interrupt(19);
+        on_interrupt(&[3, 19]);
     }}
 
     handler!{unsafe fn DMA4_DMA20() {
-        imxrt_dma::on_interrupt(4);
-        imxrt_dma::on_interrupt(20);
+        on_interrupt(&[4, 20]);
     }}
 
     handler!{unsafe fn DMA5_DMA21() {
-        imxrt_dma::on_interrupt(5);
-        imxrt_dma::on_interrupt(21);
+        on_interrupt(&[5, 21]);
     }}
 
     handler!{unsafe fn DMA6_DMA22() {
-        imxrt_dma::on_interrupt(6);
-        imxrt_dma::on_interrupt(22);
+        on_interrupt(&[6, 22]);
     }}
 
     handler!{unsafe fn DMA7_DMA23() {
-        imxrt_dma::on_interrupt(7);
-        imxrt_dma::on_interrupt(23);
+        on_interrupt(&[7, 23]);
     }}
 
     handler!{unsafe fn DMA8_DMA24() {
-        imxrt_dma::on_interrupt(8);
-        imxrt_dma::on_interrupt(24);
+        on_interrupt(&[8, 24]);
     }}
 
     handler!{unsafe fn DMA9_DMA25() {
-        imxrt_dma::on_interrupt(9);
-        imxrt_dma::on_interrupt(25);
+        on_interrupt(&[9, 25]);
     }}
 
     handler!{unsafe fn DMA10_DMA26() {
-        imxrt_dma::on_interrupt(10);
-        imxrt_dma::on_interrupt(26);
+        on_interrupt(&[10, 26]);
     }}
 
     handler!{unsafe fn DMA11_DMA27() {
-        imxrt_dma::on_interrupt(11);
-        imxrt_dma::on_interrupt(27);
+        on_interrupt(&[11, 27]);
     }}
 
     handler!{unsafe fn DMA12_DMA28() {
-        imxrt_dma::on_interrupt(12);
-        imxrt_dma::on_interrupt(28);
+        on_interrupt(&[12, 28]);
     }}
 
     handler!{unsafe fn DMA13_DMA29() {
-        imxrt_dma::on_interrupt(13);
-        imxrt_dma::on_interrupt(29);
+        on_interrupt(&[13, 29]);
     }}
 
     handler!{unsafe fn DMA14_DMA30() {
-        imxrt_dma::on_interrupt(14);
-        imxrt_dma::on_interrupt(30);
+        on_interrupt(&[14, 30]);
     }}
 
     handler!{unsafe fn DMA15_DMA31() {
-        imxrt_dma::on_interrupt(15);
-        imxrt_dma::on_interrupt(31);
+        on_interrupt(&[15, 31]);
     }}
 }
 
 #[cfg(feature = "imxrt1010")]
 interrupts! {
     handler!{unsafe fn DMA0() {
-        imxrt_dma::on_interrupt(0);
+        on_interrupt(&[0]);
     }}
 
     handler!{unsafe fn DMA1() {
-        imxrt_dma::on_interrupt(1);
+        on_interrupt(&[1]);
     }}
 
     handler!{unsafe fn DMA2() {
-        imxrt_dma::on_interrupt(2);
+        on_interrupt(&[2]);
     }}
 
     handler!{unsafe fn DMA3() {
-        imxrt_dma::on_interrupt(3);
+        on_interrupt(&[3]);
     }}
 
     handler!{unsafe fn DMA4() {
-        imxrt_dma::on_interrupt(4);
+        on_interrupt(&[4]);
     }}
 
     handler!{unsafe fn DMA5() {
-        imxrt_dma::on_interrupt(5);
+        on_interrupt(&[5]);
     }}
 
     handler!{unsafe fn DMA6() {
-        imxrt_dma::on_interrupt(6);
+        on_interrupt(&[6]);
     }}
 
     handler!{unsafe fn DMA7() {
-        imxrt_dma::on_interrupt(7);
+        on_interrupt(&[7]);
     }}
 
     handler!{unsafe fn DMA8() {
-        imxrt_dma::on_interrupt(8);
+        on_interrupt(&[8]);
     }}
 
     handler!{unsafe fn DMA9() {
-        imxrt_dma::on_interrupt(9);
+        on_interrupt(&[9]);
     }}
 
     handler!{unsafe fn DMA10() {
-        imxrt_dma::on_interrupt(10);
+        on_interrupt(&[10]);
     }}
 
     handler!{unsafe fn DMA11() {
-        imxrt_dma::on_interrupt(11);
+        on_interrupt(&[11]);
     }}
 
     handler!{unsafe fn DMA12() {
-        imxrt_dma::on_interrupt(12);
+        on_interrupt(&[12]);
     }}
 
     handler!{unsafe fn DMA13() {
-        imxrt_dma::on_interrupt(13);
+        on_interrupt(&[13]);
     }}
 
     handler!{unsafe fn DMA14() {
-        imxrt_dma::on_interrupt(14);
+        on_interrupt(&[14]);
     }}
 
     handler!{unsafe fn DMA15() {
-        imxrt_dma::on_interrupt(15);
+        on_interrupt(&[15]);
     }}
 }
@@ -0,0 +1,185 @@
+//! DSHOT electronic speed controller (ESC) output
+//!
+//! The [`DSHOT`] driver generates DSHOT frames with a [`SPI`](crate::SPI) peripheral,
+//! like the [`WS2812`](crate::WS2812) driver. It stretches each DSHOT bit across eight SPI
+//! bits, and a DMA channel feeds the encoded frame to the SPI peripheral.
+//!
+//! Connect the ESC's signal input to the SPI's SDO pin. The other SPI pins are unused
+//! by the ESC, but the `SPI` driver still requires them. Each `DSHOT` driver controls
+//! one ESC.
+//!
+//! # Timing
+//!
+//! Set the SPI clock to the [`Speed`]'s [`spi_clock_hz`](Speed::spi_clock_hz()) using
+//! [`set_clock_speed`](crate::SPI::set_clock_speed()). SPI frame delays only stretch the
+//! low part of a DSHOT bit.
+//!
+//! ESCs expect a steady stream of frames. Send a frame at least every few milliseconds,
+//! or the ESC disarms.
+//!
+//! # Example
+//!
+//! Arm an ESC, then spin the motor at a quarter throttle using DSHOT600 on SPI4.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{dma, instance, iomuxc, SPI, SPIPins, dshot::{DSHOT, Speed}};
+//! use hal::ral::{dma0::DMA0, dmamux::DMAMUX, iomuxc::IOMUXC, lpspi::LPSPI4};
+//!
+//! // Effective LPSPI source clock (PLL2 / 5)
+//! const SOURCE_CLOCK_HZ: u32 = 528_000_000 / 5;
+//!
+//! let pads = IOMUXC::take().map(iomuxc::new).unwrap();
+//! let mut channels = dma::channels(
+//!     DMA0::take().unwrap(),
+//!     DMAMUX::take().unwrap(),
+//! );
+//!
+//! let spi_pins = SPIPins {
+//!     sdo: pads.b0.p02,
+//!     sdi: pads.b0.p01,
+//!     sck: pads.b0.p03,
+//!     pcs0: pads.b0.p00,
+//! };
+//! let spi4 = LPSPI4::take().and_then(instance::spi).unwrap();
+//! let mut spi = SPI::new(spi_pins, spi4);
+//! spi.set_clock_speed(Speed::DSHOT600.spi_clock_hz(), SOURCE_CLOCK_HZ).unwrap();
+//!
+//! let mut esc = DSHOT::new(spi, channels[8].take().unwrap());
+//!
+//! # async fn delay() {}
+//! # async {
+//! // Zero throttle arms the ESC
+//! for _ in 0..1_000 {
+//!     esc.send_throttle(0).await.unwrap();
+//!     delay().await;
+//! }
+//! loop {
+//!     esc.send_throttle(500).await.unwrap();
+//!     delay().await;
+//! }
+//! # };
+//! ```
+
+use crate::{dma, SPI};
+
+/// DSHOT bit rates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub enum Speed {
+    /// 150 kbit/s
+    DSHOT150,
+    /// 300 kbit/s
+    DSHOT300,
+    /// 600 kbit/s
+    DSHOT600,
+}
+
+impl Speed {
+    /// Returns the SPI serial clock speed that produces this bit rate
+    pub const fn spi_clock_hz(self) -> u32 {
+        8 * match self {
+            Speed::DSHOT150 => 150_000,
+            Speed::DSHOT300 => 300_000,
+            Speed::DSHOT600 => 600_000,
+        }
+    }
+}
+
+/// The largest throttle value
+pub const MAX_THROTTLE: u16 = 2_000;
+/// The largest command value
+pub const MAX_COMMAND: u8 = 47;
+
+/// A DSHOT ESC driven by a SPI peripheral
+///
+/// See the [module-level documentation](crate::dshot) for more information.
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub struct DSHOT<Pins> {
+    spi: SPI<Pins>,
+    channel: dma::Channel,
+    /// One encoded frame; each `u32` holds four DSHOT bits
+    buffer: [u32; 4],
+}
+
+impl<Pins> DSHOT<Pins> {
+    /// Create a `DSHOT` driver from a SPI peripheral and a DMA channel
+    ///
+    /// Make sure that the SPI clock speed matches your DSHOT [`Speed`].
+    pub fn new(spi: SPI<Pins>, mut channel: dma::Channel) -> Self {
+        channel.set_interrupt_on_completion(true);
+        DSHOT {
+            spi,
+            channel,
+            buffer: [0; 4],
+        }
+    }
+
+    /// Return the SPI driver and the DMA channel
+    pub fn release(self) -> (SPI<Pins>, dma::Channel) {
+        (self.spi, self.channel)
+    }
+
+    /// Send a throttle value, from zero through [`MAX_THROTTLE`]
+    ///
+    /// Zero stops the motor. Completes once the frame is queued in the SPI peripheral.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `throttle` is greater than [`MAX_THROTTLE`].
+    pub fn send_throttle(&mut self, throttle: u16) -> dma::Tx<'_, SPI<Pins>, u32> {
+        assert!(throttle <= MAX_THROTTLE, "DSHOT throttle is out of range");
+        // Values 1 through 47 are commands
+        let value = if throttle == 0 { 0 } else { throttle + 47 };
+        self.send(value)
+    }
+
+    /// Send a special command, from zero through [`MAX_COMMAND`]
+    ///
+    /// Consult your ESC's documentation for the supported commands. Most commands
+    /// must be repeated, and they're only accepted while the motor is stopped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `command` is greater than [`MAX_COMMAND`].
+    pub fn send_command(&mut self, command: u8) -> dma::Tx<'_, SPI<Pins>, u32> {
+        assert!(command <= MAX_COMMAND, "DSHOT command is out of range");
+        self.send(command.into())
+    }
+
+    fn send(&mut self, value: u16) -> dma::Tx<'_, SPI<Pins>, u32> {
+        let frame = frame(value);
+        for (idx, word) in self.buffer.iter_mut().enumerate() {
+            *word = encode((frame >> (12 - 4 * idx)) as u8 & 0xF);
+        }
+        self.spi.dma_write(&mut self.channel, &self.buffer)
+    }
+}
+
+/// Returns the 16-bit frame for an 11-bit `value`, without telemetry
+///
+/// The frame is the value, the telemetry request bit, then a four-bit checksum.
+const fn frame(value: u16) -> u16 {
+    let packet = value << 1;
+    let crc = (packet ^ (packet >> 4) ^ (packet >> 8)) & 0xF;
+    (packet << 4) | crc
+}
+
+/// Stretch each bit of `nibble` into eight SPI bits
+///
+/// A `1` becomes `0b11111100`, and a `0` becomes `0b11100000`. The most-significant
+/// bit is sent first.
+const fn encode(nibble: u8) -> u32 {
+    let mut word = 0;
+    let mut bit = 0;
+    while bit < 4 {
+        let byte = if nibble & (0x8 >> bit) != 0 {
+            0b1111_1100
+        } else {
+            0b1110_0000
+        };
+        word = (word << 8) | byte;
+        bit += 1;
+    }
+    word
+}
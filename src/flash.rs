@@ -0,0 +1,340 @@
+//! In-application programming for the FlexSPI boot flash
+//!
+//! [`Flash`] erases and programs the serial NOR flash that holds your program. It
+//! uses the FlexSPI NOR driver that's built into the i.MX RT boot ROM. Use `Flash` to
+//! store configuration data, or to stage a firmware update.
+//!
+//! The driver masks interrupts while the ROM erases or programs flash, since an interrupt
+//! handler that executes from flash would fault during the operation. To keep interrupt
+//! latency bounded, the [`erase`](Flash::erase()) and [`program`](Flash::program())
+//! futures handle one sector or one page per poll, then yield to the executor.
+//!
+//! All offsets are relative to the start of flash. **Be careful not to erase the region
+//! of flash that holds your program.**
+//!
+//! # Example
+//!
+//! Erase the last sector of a 2MiB flash, and write four bytes to the start of that sector.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{flash::Flash, ral::flexspi::FLEXSPI};
+//!
+//! let mut flash = Flash::new(FLEXSPI::take().unwrap()).unwrap();
+//! let offset = 2 * 1024 * 1024 - flash.sector_size();
+//!
+//! # async {
+//! flash.erase(offset, flash.sector_size()).await.unwrap();
+//! flash.program(offset, &[0xDE, 0xAD, 0xBE, 0xEF]).await.unwrap();
+//!
+//! let mut buffer = [0; 4];
+//! flash.read(offset, &mut buffer).unwrap();
+//! assert_eq!(buffer, [0xDE, 0xAD, 0xBE, 0xEF]);
+//! # };
+//! ```
+
+pub mod storage;
+
+use crate::ral;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Address of the FlexSPI NOR configuration block, which is also the start of flash
+const FLASH_ADDRESS: usize = 0x6000_0000;
+/// The FlexSPI instance used by the boot ROM
+const INSTANCE: u32 = 0;
+/// Largest supported page size, in bytes
+const MAX_PAGE_SIZE: usize = 512;
+
+/// Pointer to the boot ROM's API tree
+const ROM_API_TREE: *const *const BootloaderTree = 0x0020_001C as *const _;
+
+// The ROM API layouts are specific to the i.MX RT 1060. Unused
+// members are included so that the offsets are correct.
+#[repr(C)]
+#[allow(dead_code)]
+struct BootloaderTree {
+    run_bootloader: unsafe extern "C" fn(*mut u32),
+    version: u32,
+    copyright: *const u8,
+    reserved0: u32,
+    flexspi_nor: *const NorDriver,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct NorDriver {
+    version: u32,
+    init: unsafe extern "C" fn(u32, *mut NorConfig) -> u32,
+    program: unsafe extern "C" fn(u32, *mut NorConfig, u32, *const u32) -> u32,
+    erase_all: unsafe extern "C" fn(u32, *mut NorConfig) -> u32,
+    erase: unsafe extern "C" fn(u32, *mut NorConfig, u32, u32) -> u32,
+    read: unsafe extern "C" fn(u32, *mut NorConfig, *mut u32, u32, u32) -> u32,
+    clear_cache: unsafe extern "C" fn(u32),
+}
+
+/// A page of program data, aligned for the ROM
+#[repr(C, align(4))]
+struct Page([u8; MAX_PAGE_SIZE]);
+
+/// The ROM's `flexspi_nor_config_t`, copied from the boot configuration block
+#[repr(C, align(4))]
+#[derive(Clone, Copy)]
+struct NorConfig([u32; 128]);
+
+impl NorConfig {
+    /// Serial flash size, in bytes (`memConfig.sflashA1Size`)
+    fn flash_size(&self) -> usize {
+        self.0[0x50 / 4] as usize
+    }
+    /// Page size, in bytes (`pageSize`)
+    fn page_size(&self) -> usize {
+        self.0[0x1C0 / 4] as usize
+    }
+    /// Sector size, in bytes (`sectorSize`)
+    fn sector_size(&self) -> usize {
+        self.0[0x1C4 / 4] as usize
+    }
+}
+
+/// Errors propagated from [`Flash`]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "flash")))]
+pub enum Error {
+    /// The boot ROM returned a non-zero status code
+    Status(u32),
+    /// The offset or length is not a multiple of the sector size (erase),
+    /// or the offset is not a multiple of the page size (program)
+    Alignment,
+    /// The operation extends beyond the end of flash
+    OutOfBounds,
+    /// The flash page size is zero, or larger than this driver supports
+    PageSize,
+    /// The flash sector size is zero
+    SectorSize,
+}
+
+/// Runs a boot ROM flash operation with interrupts masked
+///
+/// The flash is unavailable while the ROM acts on it. Masking interrupts prevents
+/// handlers that are in flash from executing.
+fn rom<F: FnOnce(&NorDriver) -> u32>(f: F) -> Result<(), Error> {
+    let status = cortex_m::interrupt::free(|_| unsafe { f(&*(**ROM_API_TREE).flexspi_nor) });
+    if 0 == status {
+        Ok(())
+    } else {
+        Err(Error::Status(status))
+    }
+}
+
+/// The boot flash
+///
+/// See the [module-level documentation](crate::flash) for more information.
+#[cfg_attr(docsrs, doc(cfg(feature = "flash")))]
+pub struct Flash {
+    config: NorConfig,
+}
+
+impl Flash {
+    /// Prepare the ROM's flash driver using the boot configuration block
+    ///
+    /// `flexspi` should be the FlexSPI instance that your system booted from. Returns an
+    /// error if the configuration block reports a page or sector size that the driver
+    /// can't use, which may happen if the block is blank or corrupt.
+    pub fn new(flexspi: ral::flexspi::Instance) -> Result<Self, Error> {
+        drop(flexspi);
+        // Safety: the configuration block is at the start of flash, and it is
+        // the same size as a `NorConfig`.
+        let mut config = unsafe { core::ptr::read_volatile(FLASH_ADDRESS as *const NorConfig) };
+        rom(|driver| unsafe { (driver.init)(INSTANCE, &mut config) })?;
+        if config.page_size() == 0 || config.page_size() > MAX_PAGE_SIZE {
+            return Err(Error::PageSize);
+        }
+        if config.sector_size() == 0 {
+            return Err(Error::SectorSize);
+        }
+        Ok(Flash { config })
+    }
+
+    /// Returns the flash size, in bytes
+    pub fn size(&self) -> usize {
+        self.config.flash_size()
+    }
+
+    /// Returns the page size, in bytes
+    ///
+    /// The page size is the unit of [`program`](Flash::program()).
+    pub fn page_size(&self) -> usize {
+        self.config.page_size()
+    }
+
+    /// Returns the sector size, in bytes
+    ///
+    /// The sector size is the unit of [`erase`](Flash::erase()).
+    pub fn sector_size(&self) -> usize {
+        self.config.sector_size()
+    }
+
+    fn check_bounds(&self, offset: usize, len: usize) -> Result<(), Error> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.size() => Ok(()),
+            _ => Err(Error::OutOfBounds),
+        }
+    }
+
+    /// Erase `len` bytes of flash, starting at `offset`
+    ///
+    /// Both `offset` and `len` must be multiples of the [`sector_size`](Flash::sector_size()).
+    /// Erased flash reads as `0xFF`.
+    pub fn erase(&mut self, offset: usize, len: usize) -> Erase<'_> {
+        Erase {
+            flash: self,
+            offset,
+            len,
+        }
+    }
+
+    /// Program `data` into flash, starting at `offset`
+    ///
+    /// `offset` must be a multiple of the [`page_size`](Flash::page_size()). The flash must
+    /// be erased before it's programmed. If `data` does not fill the last page, the rest of
+    /// the page is left erased.
+    pub fn program<'a>(&'a mut self, offset: usize, data: &'a [u8]) -> Program<'a> {
+        Program {
+            flash: self,
+            offset,
+            data,
+        }
+    }
+
+    /// Read flash contents at `offset` into `buffer`
+    pub fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<(), Error> {
+        self.check_bounds(offset, buffer.len())?;
+        // Safety: bounds checked above. Erase and program invalidate
+        // the caches, so the read observes the latest flash contents.
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                (FLASH_ADDRESS + offset) as *const u8,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+            );
+        }
+        Ok(())
+    }
+
+    fn erase_sector(&mut self, offset: usize) -> Result<(), Error> {
+        let sector_size = self.sector_size();
+        let config = &mut self.config;
+        rom(|driver| unsafe {
+            (driver.erase)(INSTANCE, config, offset as u32, sector_size as u32)
+        })?;
+        invalidate(offset, sector_size);
+        Ok(())
+    }
+
+    fn program_page(&mut self, offset: usize, page: &Page) -> Result<(), Error> {
+        let page_size = self.page_size();
+        let config = &mut self.config;
+        rom(|driver| unsafe {
+            (driver.program)(
+                INSTANCE,
+                config,
+                offset as u32,
+                page.0.as_ptr() as *const u32,
+            )
+        })?;
+        invalidate(offset, page_size);
+        Ok(())
+    }
+}
+
+/// Invalidate all caches that might hold stale flash contents
+fn invalidate(offset: usize, len: usize) {
+    cortex_m::interrupt::free(|_| unsafe {
+        let driver = &*(**ROM_API_TREE).flexspi_nor;
+        (driver.clear_cache)(INSTANCE);
+        // The CPU never writes flash through the cache, so no writes are lost.
+        crate::cache::invalidate(FLASH_ADDRESS + offset, len);
+    });
+}
+
+/// A future that erases flash
+///
+/// Use [`erase`](Flash::erase()) to create this future.
+pub struct Erase<'a> {
+    flash: &'a mut Flash,
+    offset: usize,
+    len: usize,
+}
+
+impl Future for Erase<'_> {
+    type Output = Result<(), Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let sector_size = this.flash.sector_size();
+        if this.offset % sector_size != 0 || this.len % sector_size != 0 {
+            return Poll::Ready(Err(Error::Alignment));
+        }
+        this.flash.check_bounds(this.offset, this.len)?;
+        if 0 == this.len {
+            return Poll::Ready(Ok(()));
+        }
+
+        this.flash.erase_sector(this.offset)?;
+        this.offset += sector_size;
+        this.len -= sector_size;
+
+        if 0 == this.len {
+            Poll::Ready(Ok(()))
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// A future that programs flash
+///
+/// Use [`program`](Flash::program()) to create this future.
+pub struct Program<'a> {
+    flash: &'a mut Flash,
+    offset: usize,
+    data: &'a [u8],
+}
+
+impl Future for Program<'_> {
+    type Output = Result<(), Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let page_size = this.flash.page_size();
+        if this.offset % page_size != 0 {
+            return Poll::Ready(Err(Error::Alignment));
+        }
+        this.flash.check_bounds(this.offset, this.data.len())?;
+        if this.data.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+
+        // Copy the data into RAM, since the caller's data could be in flash.
+        // Unused page bytes stay erased.
+        let mut page = Page([0xFF; MAX_PAGE_SIZE]);
+        let len = this.data.len().min(page_size);
+        let (chunk, rest) = this.data.split_at(len);
+        page.0[..len].copy_from_slice(chunk);
+
+        this.flash.program_page(this.offset, &page)?;
+        this.offset += page_size;
+        this.data = rest;
+
+        if this.data.is_empty() {
+            Poll::Ready(Ok(()))
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
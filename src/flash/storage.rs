@@ -0,0 +1,572 @@
+//! Key-value storage in flash
+//!
+//! [`Storage`] persists small records, like calibration values and settings, in two
+//! flash sectors. Each record is identified by a `u16` key. Writes append a new version
+//! of the record to the active sector; reads find the most recent version. Once the
+//! active sector is full, `Storage` copies the latest version of each record into the
+//! other sector, then switches sectors. Alternating between sectors spreads the erase
+//! cycles across both sectors.
+//!
+//! Records are checksummed. If power fails while writing a record, reads return the
+//! previous version of the record. If power fails while switching sectors, `Storage`
+//! continues to use the previous sector.
+//!
+//! Implement [`Record`] to store your own types.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{flash::{Flash, storage::{Record, Storage}}, ral::flexspi::FLEXSPI};
+//!
+//! struct Calibration {
+//!     offset: i32,
+//! }
+//!
+//! impl Record for Calibration {
+//!     const KEY: u16 = 1;
+//!     fn encode(&self, buffer: &mut [u8]) -> usize {
+//!         buffer[..4].copy_from_slice(&self.offset.to_le_bytes());
+//!         4
+//!     }
+//!     fn decode(buffer: &[u8]) -> Option<Self> {
+//!         use core::convert::TryInto;
+//!         let offset = i32::from_le_bytes(buffer.try_into().ok()?);
+//!         Some(Calibration { offset })
+//!     }
+//! }
+//!
+//! let mut flash = Flash::new(FLEXSPI::take().unwrap()).unwrap();
+//! // Use the last two sectors of a 2MiB flash
+//! let offset = 2 * 1024 * 1024 - 2 * flash.sector_size();
+//! let mut storage = Storage::new(&mut flash, offset).unwrap();
+//!
+//! # async {
+//! let calibration = storage.load::<Calibration>().unwrap().unwrap_or(Calibration { offset: 0 });
+//! storage.store(&Calibration { offset: calibration.offset + 1 }).await.unwrap();
+//! # };
+//! ```
+
+use super::{Flash, Page, MAX_PAGE_SIZE};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Marks a sector that holds records
+const MAGIC: u32 = 0x5354_4F52;
+/// Size of both the sector header, and a record header
+const HEADER_SIZE: usize = 8;
+/// The key of erased flash
+const ERASED: u16 = 0xFFFF;
+
+/// Errors propagated from [`Storage`]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "flash")))]
+pub enum Error {
+    /// An error from the flash driver
+    Flash(super::Error),
+    /// The record is larger than [`max_len`](Storage::max_len())
+    TooLarge,
+    /// The key is reserved
+    ///
+    /// `0xFFFF` is the only reserved key.
+    InvalidKey,
+    /// The latest version of every record does not fit in one sector
+    Full,
+}
+
+impl From<super::Error> for Error {
+    fn from(error: super::Error) -> Self {
+        Error::Flash(error)
+    }
+}
+
+/// A type that can be saved in [`Storage`]
+pub trait Record: Sized {
+    /// The record's unique key
+    ///
+    /// Must not be `0xFFFF`.
+    const KEY: u16;
+    /// Encode the record into `buffer`, returning the number of bytes used
+    ///
+    /// `buffer` is [`max_len`](Storage::max_len()) bytes long.
+    fn encode(&self, buffer: &mut [u8]) -> usize;
+    /// Decode the record from `buffer`, returning `None` if `buffer` is invalid
+    fn decode(buffer: &[u8]) -> Option<Self>;
+}
+
+#[derive(Clone, Copy)]
+struct Header {
+    key: u16,
+    len: u16,
+    checksum: u32,
+}
+
+impl Header {
+    fn to_bytes(self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+        bytes[..2].copy_from_slice(&self.key.to_le_bytes());
+        bytes[2..4].copy_from_slice(&self.len.to_le_bytes());
+        bytes[4..].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes
+    }
+    fn from_bytes(bytes: [u8; HEADER_SIZE]) -> Self {
+        Header {
+            key: u16::from_le_bytes([bytes[0], bytes[1]]),
+            len: u16::from_le_bytes([bytes[2], bytes[3]]),
+            checksum: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        }
+    }
+}
+
+/// FNV-1a hash of the record key, length, and data
+fn checksum(key: u16, data: &[u8]) -> u32 {
+    key.to_le_bytes()
+        .iter()
+        .chain(&(data.len() as u16).to_le_bytes())
+        .chain(data)
+        .fold(0x811C_9DC5, |hash, byte| {
+            (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+        })
+}
+
+/// The flash size of a record with `len` data bytes
+const fn record_size(len: usize) -> usize {
+    HEADER_SIZE + ((len + 3) & !3)
+}
+
+/// The result of looking for the next record in a sector
+enum Cursor {
+    /// There's a record at this sector position
+    Record(usize, Header),
+    /// There are no more records. New records may be placed at this sector position
+    End(usize),
+}
+
+/// Persistent key-value storage
+///
+/// See the [module-level documentation](crate::flash::storage) for more information.
+#[cfg_attr(docsrs, doc(cfg(feature = "flash")))]
+pub struct Storage<'f> {
+    flash: &'f mut Flash,
+    /// Flash offsets of the two sectors
+    sectors: [usize; 2],
+    /// Index of the active sector, and its sequence number
+    active: Option<(usize, u32)>,
+    /// Position of the next record in the active sector
+    end: usize,
+}
+
+impl<'f> Storage<'f> {
+    /// Use the two flash sectors starting at `offset` for storage
+    ///
+    /// `offset` must be a multiple of the flash sector size. If neither sector has
+    /// records, `Storage` prepares the sectors on the first write.
+    pub fn new(flash: &'f mut Flash, offset: usize) -> Result<Self, Error> {
+        let sector_size = flash.sector_size();
+        if offset % sector_size != 0 {
+            return Err(Error::Flash(super::Error::Alignment));
+        }
+        let mut storage = Storage {
+            flash,
+            sectors: [offset, offset + sector_size],
+            active: None,
+            end: 0,
+        };
+
+        let sectors = storage.sectors;
+        for (sector, &offset) in sectors.iter().enumerate() {
+            let mut bytes = [0; HEADER_SIZE];
+            storage.flash.read(offset, &mut bytes)?;
+            let magic = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let sequence = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            if MAGIC != magic {
+                continue;
+            }
+            storage.active = match storage.active {
+                // Newer if the (wrapping) difference is positive
+                Some((_, active)) if (sequence.wrapping_sub(active) as i32) <= 0 => storage.active,
+                _ => Some((sector, sequence)),
+            };
+        }
+
+        if let Some((sector, _)) = storage.active {
+            let mut pos = HEADER_SIZE;
+            storage.end = loop {
+                match storage.next(sector, pos)? {
+                    Cursor::Record(at, header) => pos = at + record_size(usize::from(header.len)),
+                    Cursor::End(end) => break end,
+                }
+            };
+        }
+        Ok(storage)
+    }
+
+    /// Returns the largest record, in bytes
+    pub fn max_len(&self) -> usize {
+        self.flash.page_size() - HEADER_SIZE
+    }
+
+    /// Read the latest version of the record identified by `key` into `buffer`
+    ///
+    /// Returns the size of the record, or `None` if there is no record. If `buffer`
+    /// is smaller than the record, `read` only fills `buffer`.
+    pub fn read(&self, key: u16, buffer: &mut [u8]) -> Result<Option<usize>, Error> {
+        let sector = match self.active {
+            Some((sector, _)) => sector,
+            None => return Ok(None),
+        };
+        let mut pos = HEADER_SIZE;
+        let mut latest = None;
+        while let Cursor::Record(at, header) = self.next(sector, pos)? {
+            if header.key == key && self.is_valid(sector, at, header)? {
+                latest = Some((at, header));
+            }
+            pos = at + record_size(usize::from(header.len));
+        }
+
+        match latest {
+            Some((at, header)) => {
+                let len = usize::from(header.len).min(buffer.len());
+                self.flash
+                    .read(self.sectors[sector] + at + HEADER_SIZE, &mut buffer[..len])?;
+                Ok(Some(usize::from(header.len)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Write `data` as the latest version of the record identified by `key`
+    ///
+    /// `data` must be no larger than [`max_len`](Storage::max_len()).
+    pub fn write(&mut self, key: u16, data: &[u8]) -> Write<'_, 'f> {
+        let mut buffer = [0; MAX_PAGE_SIZE];
+        let len = data.len();
+        let step = if ERASED == key {
+            Step::Error(Error::InvalidKey)
+        } else if len > self.max_len() {
+            Step::Error(Error::TooLarge)
+        } else {
+            buffer[..len].copy_from_slice(data);
+            Step::Start
+        };
+        Write {
+            storage: self,
+            key,
+            data: buffer,
+            len,
+            step,
+        }
+    }
+
+    /// Load the latest version of the record `R`
+    ///
+    /// Returns `None` if there is no record, or if the record does not decode.
+    pub fn load<R: Record>(&self) -> Result<Option<R>, Error> {
+        let mut buffer = [0; MAX_PAGE_SIZE];
+        let buffer = &mut buffer[..self.max_len()];
+        Ok(self
+            .read(R::KEY, buffer)?
+            .and_then(|len| R::decode(&buffer[..len.min(buffer.len())])))
+    }
+
+    /// Store `record` as the latest version of the record `R`
+    pub fn store<R: Record>(&mut self, record: &R) -> Write<'_, 'f> {
+        let mut buffer = [0; MAX_PAGE_SIZE];
+        let max_len = self.max_len();
+        let len = record.encode(&mut buffer[..max_len]);
+        self.write(R::KEY, &buffer[..len.min(max_len)])
+    }
+
+    /// Find the next record in `sector` at or after `pos`
+    fn next(&self, sector: usize, mut pos: usize) -> Result<Cursor, Error> {
+        let page_size = self.flash.page_size();
+        let sector_size = self.flash.sector_size();
+        let next_page = |pos: usize| (pos / page_size + 1) * page_size;
+        loop {
+            if pos % page_size + HEADER_SIZE > page_size {
+                pos = next_page(pos);
+            }
+            if pos + HEADER_SIZE > sector_size {
+                return Ok(Cursor::End(sector_size));
+            }
+
+            let header = self.header(sector, pos)?;
+            if ERASED == header.key {
+                // Records that don't fit in a page start on the next page.
+                // If there's a record there, keep looking.
+                let next = next_page(pos);
+                if pos % page_size == 0
+                    || next + HEADER_SIZE > sector_size
+                    || ERASED == self.header(sector, next)?.key
+                {
+                    return Ok(Cursor::End(pos));
+                }
+                pos = next;
+            } else if usize::from(header.len) > self.max_len() {
+                // Corrupt header. Nothing may be placed after this record.
+                return Ok(Cursor::End(sector_size));
+            } else {
+                return Ok(Cursor::Record(pos, header));
+            }
+        }
+    }
+
+    fn header(&self, sector: usize, pos: usize) -> Result<Header, Error> {
+        let mut bytes = [0; HEADER_SIZE];
+        self.flash.read(self.sectors[sector] + pos, &mut bytes)?;
+        Ok(Header::from_bytes(bytes))
+    }
+
+    /// Returns `true` if the record's data matches its checksum
+    fn is_valid(&self, sector: usize, pos: usize, header: Header) -> Result<bool, Error> {
+        let mut data = [0; MAX_PAGE_SIZE];
+        let data = &mut data[..usize::from(header.len)];
+        self.flash
+            .read(self.sectors[sector] + pos + HEADER_SIZE, data)?;
+        Ok(checksum(header.key, data) == header.checksum)
+    }
+
+    /// Returns the position for a record of `len` bytes, at or after `pos`
+    ///
+    /// Records never cross a page boundary. Returns `None` if the record does
+    /// not fit in the sector.
+    fn place(&self, pos: usize, len: usize) -> Option<usize> {
+        let page_size = self.flash.page_size();
+        let size = record_size(len);
+        let pos = if pos % page_size + size > page_size {
+            (pos / page_size + 1) * page_size
+        } else {
+            pos
+        };
+        if pos + size <= self.flash.sector_size() {
+            Some(pos)
+        } else {
+            None
+        }
+    }
+
+    /// Program the bytes `data` at position `pos` of `sector`
+    ///
+    /// The bytes must be within a single page. The rest of the page is unchanged.
+    fn program(&mut self, sector: usize, pos: usize, data: &[u8]) -> Result<(), Error> {
+        let page_size = self.flash.page_size();
+        let page = pos / page_size * page_size;
+        // Programming 0xFF leaves the flash unchanged.
+        let mut buffer = Page([0xFF; MAX_PAGE_SIZE]);
+        buffer.0[pos - page..][..data.len()].copy_from_slice(data);
+        let offset = self.sectors[sector] + page;
+        self.flash.check_bounds(offset, page_size)?;
+        self.flash.program_page(offset, &buffer)?;
+        Ok(())
+    }
+
+    fn append(&mut self, sector: usize, pos: usize, key: u16, data: &[u8]) -> Result<(), Error> {
+        let header = Header {
+            key,
+            len: data.len() as u16,
+            checksum: checksum(key, data),
+        };
+        let mut record = [0xFF; MAX_PAGE_SIZE];
+        record[..HEADER_SIZE].copy_from_slice(&header.to_bytes());
+        record[HEADER_SIZE..][..data.len()].copy_from_slice(data);
+        self.program(sector, pos, &record[..HEADER_SIZE + data.len()])
+    }
+
+    /// Prepare to copy the latest version of each record, except `key`, into the other
+    /// sector, then erase that sector
+    ///
+    /// Returns [`Error::Full`] before erasing anything if the records, and `len` bytes
+    /// for `key`, don't fit.
+    fn start_switch(&mut self, key: u16, len: usize) -> Result<Step, Error> {
+        let (target, sequence) = match self.active {
+            Some((sector, sequence)) => (sector ^ 1, sequence.wrapping_add(1)),
+            None => (0, 0),
+        };
+        let source = self.active.map(|(sector, _)| sector);
+
+        // Lay out the records before erasing the target, so that a full
+        // storage keeps its previous sector.
+        let mut end = HEADER_SIZE;
+        if let Some(source) = source {
+            let mut pos = HEADER_SIZE;
+            while let Some((_, header, next)) = self.next_latest(source, pos, key)? {
+                pos = next;
+                let placed = self
+                    .place(end, usize::from(header.len))
+                    .ok_or(Error::Full)?;
+                end = placed + record_size(usize::from(header.len));
+            }
+        }
+        self.place(end, len).ok_or(Error::Full)?;
+
+        let sector_size = self.flash.sector_size();
+        let offset = self.sectors[target];
+        self.flash.check_bounds(offset, sector_size)?;
+        self.flash.erase_sector(offset)?;
+
+        Ok(Step::Copy {
+            target,
+            sequence,
+            source,
+            pos: HEADER_SIZE,
+            end: HEADER_SIZE,
+        })
+    }
+
+    /// Find the next record in `sector`, at or after `pos`, that holds the latest valid
+    /// version of a key other than `skip`
+    ///
+    /// Returns the record's position and header, and the position after the record.
+    fn next_latest(
+        &self,
+        sector: usize,
+        mut pos: usize,
+        skip: u16,
+    ) -> Result<Option<(usize, Header, usize)>, Error> {
+        while let Cursor::Record(at, header) = self.next(sector, pos)? {
+            pos = at + record_size(usize::from(header.len));
+            if header.key != skip
+                && self.is_valid(sector, at, header)?
+                && !self.has_newer(sector, pos, header.key)?
+            {
+                return Ok(Some((at, header, pos)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns `true` if there's a valid record for `key` at or after `pos`
+    fn has_newer(&self, sector: usize, mut pos: usize, key: u16) -> Result<bool, Error> {
+        while let Cursor::Record(at, header) = self.next(sector, pos)? {
+            if header.key == key && self.is_valid(sector, at, header)? {
+                return Ok(true);
+            }
+            pos = at + record_size(usize::from(header.len));
+        }
+        Ok(false)
+    }
+}
+
+/// The step of a [`Write`] future
+enum Step {
+    /// Append the record to the active sector, or start switching sectors
+    Start,
+    /// The target sector is erased. Copy the next record from `source` at or after
+    /// `pos`, or append the new record and activate the target.
+    Copy {
+        target: usize,
+        sequence: u32,
+        source: Option<usize>,
+        pos: usize,
+        /// Position of the next record in the target
+        end: usize,
+    },
+    /// Fail with this error
+    Error(Error),
+    /// The write completed
+    Done,
+}
+
+/// A future that writes a record
+///
+/// Use [`write`](Storage::write()) or [`store`](Storage::store()) to create this future.
+/// While it switches sectors, the future yields after the erase, and after each record
+/// that it copies, so that other tasks can run.
+#[cfg_attr(docsrs, doc(cfg(feature = "flash")))]
+pub struct Write<'s, 'f> {
+    storage: &'s mut Storage<'f>,
+    key: u16,
+    data: [u8; MAX_PAGE_SIZE],
+    len: usize,
+    step: Step,
+}
+
+impl Write<'_, '_> {
+    /// Take the next step, returning `true` once the write completed
+    fn step(&mut self) -> Result<bool, Error> {
+        let storage = &mut *self.storage;
+        let (key, data) = (self.key, &self.data[..self.len]);
+        match self.step {
+            Step::Start => {
+                let placement = storage.active.and_then(|(sector, _)| {
+                    Some((sector, storage.place(storage.end, data.len())?))
+                });
+                if let Some((sector, pos)) = placement {
+                    storage.append(sector, pos, key, data)?;
+                    storage.end = pos + record_size(data.len());
+                    return Ok(true);
+                }
+                self.step = storage.start_switch(key, data.len())?;
+                Ok(false)
+            }
+            Step::Copy {
+                target,
+                sequence,
+                source,
+                pos,
+                end,
+            } => {
+                let latest = match source {
+                    Some(source) => storage
+                        .next_latest(source, pos, key)?
+                        .map(|record| (source, record)),
+                    None => None,
+                };
+                if let Some((source, (at, header, next))) = latest {
+                    let mut record = [0; MAX_PAGE_SIZE];
+                    let record = &mut record[..usize::from(header.len)];
+                    storage
+                        .flash
+                        .read(storage.sectors[source] + at + HEADER_SIZE, record)?;
+                    let placed = storage.place(end, record.len()).ok_or(Error::Full)?;
+                    storage.append(target, placed, header.key, record)?;
+                    self.step = Step::Copy {
+                        target,
+                        sequence,
+                        source: Some(source),
+                        pos: next,
+                        end: placed + record_size(record.len()),
+                    };
+                    return Ok(false);
+                }
+
+                let placed = storage.place(end, data.len()).ok_or(Error::Full)?;
+                storage.append(target, placed, key, data)?;
+
+                // Write the sector header last. Until then, the previous
+                // sector, and the previous version of `key`, remain active.
+                let mut sector_header = [0; HEADER_SIZE];
+                sector_header[..4].copy_from_slice(&MAGIC.to_le_bytes());
+                sector_header[4..].copy_from_slice(&sequence.to_le_bytes());
+                storage.program(target, 0, &sector_header)?;
+
+                storage.active = Some((target, sequence));
+                storage.end = placed + record_size(data.len());
+                Ok(true)
+            }
+            Step::Error(error) => Err(error),
+            Step::Done => panic!("Write polled after completion"),
+        }
+    }
+}
+
+impl Future for Write<'_, '_> {
+    type Output = Result<(), Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.step() {
+            Ok(false) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            result => {
+                this.step = Step::Done;
+                Poll::Ready(result.map(|_| ()))
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Frequency and period measurement
+//!
+//! A [`FreqMeter`] counts the rising edges on a GPT's `GPT_CLK` input during a window
+//! that's timed by a second GPT. The count and the window length give the input's
+//! average frequency and period. Longer windows measure low frequencies, like a fan's
+//! tachometer, with more resolution.
+//!
+//! The counting GPT counts the edges in hardware, so the processor isn't interrupted
+//! for each edge. Create the counting GPT with [`ClockSource::External`], and route the
+//! input pad to its `GPT_CLK` signal. The gate GPT must know its counter frequency;
+//! create it with [`with_clock`](crate::GPT::with_clock()).
+//!
+//! Enable the `"gpt"` feature to use this module.
+//!
+//! # Example
+//!
+//! Measure a tachometer on `GPT2_CLK` over a 100ms window, using GPT1 as the gate.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{freqmeter::FreqMeter, gpt::ClockSource, ral, GPT};
+//!
+//! let gpt1 = ral::gpt::GPT1::take().unwrap();
+//! let (mut gate, _, _) = GPT::with_clock(gpt1, ClockSource::Crystal { divider_24m: 8 }, 3);
+//! let gpt2 = ral::gpt::GPT2::take().unwrap();
+//! // Route the tachometer pad to GPT2_CLK...
+//! let (counter, _, _) = GPT::with_clock(gpt2, ClockSource::External { hz: None }, 1);
+//! let mut meter = FreqMeter::new(counter);
+//!
+//! # async {
+//! let measurement = meter.measure(&mut gate, 100_000).await;
+//! let rpm = measurement.frequency_hz() * 60 / 2; // Two pulses per revolution
+//! # };
+//! ```
+//!
+//! [`ClockSource::External`]: crate::gpt::ClockSource::External
+
+use crate::{
+    gpt::{ticks_from_micros, Delay},
+    GPT,
+};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The result of a [`FreqMeter`] measurement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    /// The number of rising edges in the window
+    pub edges: u32,
+    /// The window length, in gate GPT clock counts
+    pub window_ticks: u32,
+    /// The gate GPT's counter frequency
+    clock_hz: u32,
+}
+
+impl Measurement {
+    /// Returns the gate GPT's counter frequency, in Hertz
+    pub fn clock_hz(&self) -> u32 {
+        self.clock_hz
+    }
+
+    /// Returns the average input frequency, in Hertz
+    ///
+    /// Returns zero if the window was empty.
+    pub fn frequency_hz(&self) -> u32 {
+        (self.frequency_millihertz() / 1_000) as u32
+    }
+
+    /// Returns the average input frequency, in millihertz
+    ///
+    /// Returns zero if the window was empty.
+    pub fn frequency_millihertz(&self) -> u64 {
+        if self.window_ticks == 0 {
+            return 0;
+        }
+        u64::from(self.edges) * u64::from(self.clock_hz) * 1_000 / u64::from(self.window_ticks)
+    }
+
+    /// Returns the average input period, in microseconds
+    ///
+    /// Returns `None` if there were no edges in the window.
+    pub fn period_us(&self) -> Option<u32> {
+        if self.edges == 0 {
+            return None;
+        }
+        let period = u64::from(self.window_ticks) * 1_000_000
+            / (u64::from(self.edges) * u64::from(self.clock_hz));
+        Some(period as u32)
+    }
+}
+
+/// Measures the frequency and period of a GPT's `GPT_CLK` input
+///
+/// See the [module-level documentation](crate::freqmeter) for more information.
+pub struct FreqMeter {
+    counter: GPT,
+}
+
+impl FreqMeter {
+    /// Measure the frequency of the input that `counter` counts
+    ///
+    /// `counter` may be any of the three timers from the counting GPT. The meter only
+    /// reads the counter, so the other two timers remain available.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `counter` doesn't count its `GPT_CLK` input.
+    pub fn new(counter: GPT) -> Self {
+        assert!(
+            counter.counts_external_clock(),
+            "FreqMeter counter must use ClockSource::External"
+        );
+        FreqMeter { counter }
+    }
+
+    /// Count rising edges for `window_us` microseconds, timed by `gate`
+    ///
+    /// The window starts when you first poll the future. Neither GPT's counter may be
+    /// reset while the measurement is in progress.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the gate's counter frequency is unknown, or zero.
+    pub fn measure<'a>(&'a mut self, gate: &'a mut GPT, window_us: u32) -> Measure<'a> {
+        let clock_hz = gate
+            .clock_hz()
+            .filter(|&hz| hz != 0)
+            .expect("GPT counter frequency is unknown; see GPT::with_clock");
+        Measure {
+            counter: &self.counter,
+            delay: gate.delay(ticks_from_micros(window_us, clock_hz)),
+            clock_hz,
+            start: None,
+        }
+    }
+
+    /// Release the counting GPT timer
+    pub fn release(self) -> GPT {
+        self.counter
+    }
+}
+
+/// A future that measures the input frequency
+///
+/// Use [`measure`](FreqMeter::measure()) to create this future.
+pub struct Measure<'a> {
+    counter: &'a GPT,
+    delay: Delay<'a>,
+    clock_hz: u32,
+    /// The gate and edge counts when the window started
+    start: Option<(u32, u32)>,
+}
+
+impl Measure<'_> {
+    /// Read the gate and edge counters together
+    fn counts(&self) -> (u32, u32) {
+        cortex_m::interrupt::free(|_| (self.delay.counter(), self.counter.counter()))
+    }
+}
+
+impl Future for Measure<'_> {
+    type Output = Measurement;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Measurement> {
+        // Safety: the delay is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.start.is_none() {
+            this.start = Some(this.counts());
+        }
+        if unsafe { Pin::new_unchecked(&mut this.delay) }
+            .poll(cx)
+            .is_pending()
+        {
+            return Poll::Pending;
+        }
+        let (start_ticks, start_edges) = this.start.unwrap();
+        let (end_ticks, end_edges) = this.counts();
+        Poll::Ready(Measurement {
+            edges: end_edges.wrapping_sub(start_edges),
+            window_ticks: end_ticks.wrapping_sub(start_ticks),
+            clock_hz: this.clock_hz,
+        })
+    }
+}
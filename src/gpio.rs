@@ -33,6 +33,19 @@
 //! assert!(!output.is_set());
 //! ```
 //!
+//! To handle every edge on an input, even if your task falls behind, use
+//! [`events`](GPIO::events()). To count edges on an input without waking a task for
+//! every edge, use a [`PulseCounter`]. To decode an encoder's quadrature signals on
+//! any two inputs, use [`Quadrature`].
+//!
+//! To store GPIOs with different pads in an array, or in a struct field, use
+//! [`erase`](GPIO::erase()) to create an [`ErasedPin`]. To change a GPIO's direction
+//! without consuming it, use a [`Flex`] GPIO. To wait for the first of several inputs,
+//! use [`wait_any`]. To update several outputs at the same time, use a [`PortWriter`].
+//!
+//! A `GPIO` can configure its pad's pull resistor, open drain output, and drive
+//! strength. See [`set_pull`](GPIO::set_pull()) and [`configure`](GPIO::configure()).
+//!
 //! # Example
 //!
 //! In this example, we toggle the Teensy 4's LED for every falling edge on pin 14:
@@ -55,6 +68,22 @@
 //! block_on(blinking_loop);
 //! ```
 
+mod erased;
+mod events;
+mod flex;
+mod pad;
+mod port;
+mod pulse_counter;
+mod quadrature;
+
+pub use erased::{wait_any, ErasedInterrupt, ErasedPin, WaitAny};
+pub use events::Events;
+pub use flex::Flex;
+pub use pad::Pull;
+pub use port::PortWriter;
+pub use pulse_counter::{PulseCounter, WaitForCount};
+pub use quadrature::{Quadrature, WaitForDelta, MAX_DECODERS};
+
 use crate::iomuxc::{consts::Unsigned, gpio::Pin};
 use crate::ral::{
     self,
@@ -67,6 +96,7 @@ use core::{
     sync::atomic,
     task::{Context, Poll, Waker},
 };
+use futures::task::AtomicWaker;
 
 /// Indicates that a pin is configured as an input
 pub enum Input {}
@@ -101,29 +131,7 @@ where
     P: Pin,
 {
     fn register_block(&self) -> *const RegisterBlock {
-        // The match expressions depend on the imxrt-iomuxc gpio::Pin
-        // associated constants. Study the imxrt-iomuxc APIs, and make sure
-        // that the unreachable!() arms are truly unreachable.
-        #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
-        compile_error!("Ensure that GPIO register access is correct");
-
-        #[cfg(feature = "imxrt1060")]
-        match self.module() {
-            1 => gpio::GPIO1,
-            2 => gpio::GPIO2,
-            3 => gpio::GPIO3,
-            4 => gpio::GPIO4,
-            5 => gpio::GPIO5,
-            _ => unreachable!(),
-        }
-
-        #[cfg(feature = "imxrt1010")]
-        match self.module() {
-            1 => gpio::GPIO1,
-            2 => gpio::GPIO2,
-            5 => gpio::GPIO5,
-            _ => unreachable!(),
-        }
+        register_block(self.module())
     }
 
     #[inline(always)]
@@ -138,9 +146,58 @@ where
         <P as Pin>::Module::USIZE
     }
 
-    /// Returns the ICR field offset for this pin
-    fn icr_offset(&self) -> usize {
-        (<P as Pin>::Offset::USIZE % 16) * 2
+    /// Release the pin, so that you may prepare it for another function
+    ///
+    /// `release` makes the GPIO an input, and masks its interrupt. If the pin was waiting
+    /// for a trigger, the waker is dropped.
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::{gpio::GPIO, ral};
+    ///
+    /// let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+    /// let uart2 = ral::lpuart::LPUART2::take()
+    ///     .and_then(hal::instance::uart)
+    ///     .unwrap();
+    ///
+    /// // Sample the pad as a strap, then use it as the UART TX pin
+    /// let strap = GPIO::new(pads.ad_b1.p02);
+    /// let _is_set = strap.is_set();
+    /// let uart = hal::UART::new(uart2, strap.release(), pads.ad_b1.p03);
+    ///
+    /// // Later, use the TX pin as a GPIO again
+    /// let (tx, _, _) = uart.release();
+    /// let strap = GPIO::new(tx);
+    /// ```
+    pub fn release(self) -> P {
+        let module = self.module() - 1;
+        let offset = <P as Pin>::Offset::USIZE;
+        cortex_m::interrupt::free(|_| unsafe {
+            ral::modify_reg!(ral::gpio, self.register_block(), IMR, |imr| imr
+                & !self.offset());
+            ral::modify_reg!(ral::gpio, self.register_block(), GDIR, |gdir| gdir
+                & !self.offset());
+            REGISTERED[module].fetch_and(!(1 << offset), atomic::Ordering::Relaxed);
+            WAKERS[module][offset].take();
+        });
+        self.pin
+    }
+
+    /// Create a GPIO that uses the pad's current configuration
+    ///
+    /// Unlike [`new`](GPIO::new()), `steal` does not configure the pad, or change
+    /// the GPIO direction. Make sure that `D` matches the pin's current direction.
+    ///
+    /// # Safety
+    ///
+    /// The original GPIO may still exist, and it may be in the middle of an operation.
+    /// Only use `steal` when the original owner can no longer run, like in a panic or
+    /// fault handler.
+    pub unsafe fn steal(pin: P) -> Self {
+        GPIO {
+            pin,
+            dir: PhantomData,
+        }
     }
 }
 
@@ -160,6 +217,7 @@ where
     /// let input_pin = GPIO::new(pads.b0.p03);
     /// ```
     pub fn new(mut pin: P) -> Self {
+        crate::ccm::assert_gates_on(crate::ccm::Peripheral::GPIO(<P as Pin>::Module::USIZE));
         crate::iomuxc::gpio::prepare(&mut pin);
 
         static ONCE: crate::once::Once = crate::once::new();
@@ -210,37 +268,8 @@ where
     }
 
     fn set_trigger(&mut self, trigger: Trigger) {
-        if Trigger::EitherEdge == trigger {
-            unsafe {
-                ral::modify_reg!(ral::gpio, self.register_block(), EDGE_SEL, |edge_sel| {
-                    edge_sel | self.offset()
-                });
-            }
-        } else {
-            unsafe {
-                ral::modify_reg!(ral::gpio, self.register_block(), EDGE_SEL, |edge_sel| {
-                    edge_sel & !self.offset()
-                });
-            }
-            let icr = match trigger {
-                Trigger::Low => 0,
-                Trigger::High => 1,
-                Trigger::RisingEdge => 2,
-                Trigger::FallingEdge => 3,
-                _ => unreachable!("Trigger::EitherEdge handled above"),
-            };
-            let icr_offset = self.icr_offset();
-            let icr_modify = |reg| reg & !(0b11 << icr_offset) | (icr << icr_offset);
-            if <P as Pin>::Offset::USIZE < 16 {
-                unsafe {
-                    ral::modify_reg!(ral::gpio, self.register_block(), ICR1, icr_modify);
-                }
-            } else {
-                unsafe {
-                    ral::modify_reg!(ral::gpio, self.register_block(), ICR2, icr_modify);
-                }
-            }
-        }
+        // Safety: the GPIO owns this pin's trigger configuration
+        unsafe { set_trigger(self.register_block(), <P as Pin>::Offset::USIZE, trigger) }
     }
 
     /// Sets the trigger for the input GPIO, and await for the input event.
@@ -259,6 +288,46 @@ where
     pub fn wait_for(&mut self, trigger: Trigger) -> Interrupt<'_, P> {
         Interrupt::new(self, trigger)
     }
+
+    /// Set the trigger, and wake `waker` once the trigger occurs
+    ///
+    /// This is a low-level building block for your own futures. Most users should
+    /// use [`wait_for`](GPIO::wait_for()).
+    ///
+    /// After `arm`, the pin is armed until the trigger occurs, or until you call
+    /// [`disarm`](GPIO::disarm()). The interrupt handler wakes `waker` at most once.
+    /// Use [`is_armed`](GPIO::is_armed()) to check if the trigger has occurred.
+    pub fn arm(&mut self, trigger: Trigger, waker: &Waker) {
+        let module = self.module() - 1;
+        let offset = <P as Pin>::Offset::USIZE;
+        self.set_trigger(trigger);
+        WAKERS[module][offset].register(waker);
+        REGISTERED[module].fetch_or(1 << offset, atomic::Ordering::Release);
+        cortex_m::interrupt::free(|_| unsafe {
+            ral::modify_reg!(ral::gpio, self.register_block(), IMR, |imr| imr
+                | self.offset())
+        });
+    }
+
+    /// Returns `true` if the pin is waiting for its trigger
+    ///
+    /// Returns `false` once the trigger occurs. See [`arm`](GPIO::arm()) for more information.
+    pub fn is_armed(&self) -> bool {
+        REGISTERED[self.module() - 1].load(atomic::Ordering::Acquire) & self.offset() != 0
+    }
+
+    /// Stop waiting for the trigger, and drop the waker
+    ///
+    /// The interrupt may still be unmasked. If it fires, the interrupt handler masks
+    /// it, and finds nothing to wake.
+    pub fn disarm(&mut self) {
+        let module = self.module() - 1;
+        let offset = <P as Pin>::Offset::USIZE;
+        cortex_m::interrupt::free(|_| {
+            REGISTERED[module].fetch_and(!(1 << offset), atomic::Ordering::Relaxed);
+            WAKERS[module][offset].take();
+        });
+    }
 }
 
 impl<P> GPIO<P, Output>
@@ -306,6 +375,61 @@ where
     }
 }
 
+#[cfg(feature = "embedded-hal")]
+impl<P> embedded_hal::digital::v2::InputPin for GPIO<P, Input>
+where
+    P: Pin,
+{
+    type Error = core::convert::Infallible;
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.is_set())
+    }
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set())
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<P> embedded_hal::digital::v2::OutputPin for GPIO<P, Output>
+where
+    P: Pin,
+{
+    type Error = core::convert::Infallible;
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set();
+        Ok(())
+    }
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.clear();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<P> embedded_hal::digital::v2::StatefulOutputPin for GPIO<P, Output>
+where
+    P: Pin,
+{
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.is_set())
+    }
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set())
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<P> embedded_hal::digital::v2::ToggleableOutputPin for GPIO<P, Output>
+where
+    P: Pin,
+{
+    type Error = core::convert::Infallible;
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        GPIO::toggle(self);
+        Ok(())
+    }
+}
+
 /// Input interrupt triggers
 ///
 /// See [`GPIO::wait_for`](GPIO::wait_for()) for more information.
@@ -328,18 +452,22 @@ pub enum Trigger {
 ///
 /// Use [`wait_for`](crate::gpio::GPIO::wait_for()) to create the future
 /// that awaits the trigger.
-pub struct Interrupt<'t, P> {
+pub struct Interrupt<'t, P>
+where
+    P: Pin,
+{
     gpio: &'t mut GPIO<P, Input>,
-    waker: Option<Waker>,
     is_ready: bool,
     trigger: Trigger,
 }
 
-impl<'t, P> Interrupt<'t, P> {
+impl<'t, P> Interrupt<'t, P>
+where
+    P: Pin,
+{
     fn new(gpio: &'t mut GPIO<P, Input>, trigger: Trigger) -> Self {
         Interrupt {
             gpio,
-            waker: None,
             is_ready: true,
             trigger,
         }
@@ -355,19 +483,9 @@ where
         let this = self.get_mut();
         if this.is_ready {
             this.is_ready = false;
-            this.gpio.set_trigger(this.trigger);
-            this.waker = Some(cx.waker().clone());
-            unsafe {
-                WAKERS[this.gpio.module().saturating_sub(1)][<P as Pin>::Offset::USIZE] =
-                    &mut this.waker;
-            }
-            atomic::compiler_fence(atomic::Ordering::Release);
-            cortex_m::interrupt::free(|_| unsafe {
-                ral::modify_reg!(ral::gpio, this.gpio.register_block(), IMR, |imr| imr
-                    | this.gpio.offset())
-            });
+            this.gpio.arm(this.trigger, cx.waker());
             Poll::Pending
-        } else if this.waker.is_none() {
+        } else if !this.gpio.is_armed() {
             Poll::Ready(())
         } else {
             Poll::Pending
@@ -375,19 +493,121 @@ where
     }
 }
 
-/// Points to memory owned by the InputSensitive future
-static mut WAKERS: [[*mut Option<Waker>; 32]; 5] = [[core::ptr::null_mut(); 32]; 5];
+impl<'t, P> Drop for Interrupt<'t, P>
+where
+    P: Pin,
+{
+    fn drop(&mut self) {
+        self.gpio.disarm();
+    }
+}
+
+/// Returns the register block for the GPIO `module`, starting at 1
+fn register_block(module: usize) -> *const RegisterBlock {
+    // The match expressions depend on the imxrt-iomuxc gpio::Pin
+    // associated constants. Study the imxrt-iomuxc APIs, and make sure
+    // that the unreachable!() arms are truly unreachable.
+    #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
+    compile_error!("Ensure that GPIO register access is correct");
+
+    #[cfg(feature = "imxrt1060")]
+    match module {
+        1 => gpio::GPIO1,
+        2 => gpio::GPIO2,
+        3 => gpio::GPIO3,
+        4 => gpio::GPIO4,
+        5 => gpio::GPIO5,
+        _ => unreachable!(),
+    }
+
+    #[cfg(feature = "imxrt1010")]
+    match module {
+        1 => gpio::GPIO1,
+        2 => gpio::GPIO2,
+        5 => gpio::GPIO5,
+        _ => unreachable!(),
+    }
+}
+
+/// Set the trigger for the pin at `offset`
+///
+/// # Safety
+///
+/// Performs a read-modify-write on the pin's trigger configuration. The caller must own
+/// the pin.
+unsafe fn set_trigger(gpio: *const RegisterBlock, offset: usize, trigger: Trigger) {
+    if Trigger::EitherEdge == trigger {
+        ral::modify_reg!(ral::gpio, gpio, EDGE_SEL, |edge_sel| edge_sel
+            | (1 << offset));
+    } else {
+        ral::modify_reg!(ral::gpio, gpio, EDGE_SEL, |edge_sel| edge_sel
+            & !(1 << offset));
+        let icr = match trigger {
+            Trigger::Low => 0,
+            Trigger::High => 1,
+            Trigger::RisingEdge => 2,
+            Trigger::FallingEdge => 3,
+            _ => unreachable!("Trigger::EitherEdge handled above"),
+        };
+        let icr_offset = (offset % 16) * 2;
+        let icr_modify = |reg| reg & !(0b11 << icr_offset) | (icr << icr_offset);
+        if offset < 16 {
+            ral::modify_reg!(ral::gpio, gpio, ICR1, icr_modify);
+        } else {
+            ral::modify_reg!(ral::gpio, gpio, ICR2, icr_modify);
+        }
+    }
+}
+
+/// Wakers for the pins that are awaiting an interrupt, indexed by module, then pin offset
+///
+/// Each waker is an `AtomicWaker`, so tasks and the interrupt handler may race to
+/// register, take, or wake it.
+static WAKERS: [[AtomicWaker; 32]; 5] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NONE: AtomicWaker = AtomicWaker::new();
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NONES: [AtomicWaker; 32] = [
+        NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE,
+        NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE, NONE,
+        NONE, NONE,
+    ];
+    [NONES, NONES, NONES, NONES, NONES]
+};
+
+/// For each module, a bitmap of the pins that are awaiting an interrupt
+///
+/// A set bit indicates that the pin's waker is valid, and may be woken.
+static REGISTERED: [atomic::AtomicU32; 5] = [
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+];
 
 #[inline(always)]
 unsafe fn on_interrupt(gpio: *const ral::gpio::RegisterBlock, mut module: usize) {
+    #[cfg(feature = "bench")]
+    let _measurement = crate::bench::Measurement::start(crate::bench::Probe::GpioInterrupt);
     module -= 1;
     let isr = ral::read_reg!(ral::gpio, gpio, ISR);
     ral::write_reg!(ral::gpio, gpio, ISR, isr);
-    ral::modify_reg!(ral::gpio, gpio, IMR, |imr| imr & !isr);
-    (0..32usize)
-        .filter(|bit| (isr & (1 << bit) != 0) && !WAKERS[module][*bit].is_null())
-        .filter_map(|bit| (*WAKERS[module][bit]).take())
-        .for_each(|waker| waker.wake());
+    // Counting and decoding pins stay unmasked. Counting pins are only ready once they
+    // reach their target, and decoding pins wake their decoder's task.
+    let counting = isr & pulse_counter::COUNTING[module].load(atomic::Ordering::Relaxed);
+    let decoding = isr & quadrature::DECODING[module].load(atomic::Ordering::Relaxed);
+    ral::modify_reg!(ral::gpio, gpio, IMR, |imr| imr
+        & !(isr & !counting & !decoding));
+    quadrature::decode(module, decoding);
+    let ready = (isr & !counting & !decoding) | pulse_counter::count(module, counting);
+    let registered = REGISTERED[module].fetch_and(!ready, atomic::Ordering::AcqRel);
+    let mut pending = ready & registered;
+    while pending != 0 {
+        let offset = pending.trailing_zeros() as usize;
+        WAKERS[module][offset].wake();
+        pending &= pending - 1;
+    }
 }
 
 #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
@@ -0,0 +1,345 @@
+//! GPIOs without a pad type
+
+use super::{
+    register_block, set_trigger, Input, Output, Pin, RegisterBlock, Trigger, Unsigned, GPIO,
+    REGISTERED, WAKERS,
+};
+use crate::ral;
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin,
+    sync::atomic,
+    task::{Context, Poll, Waker},
+};
+
+/// A GPIO that doesn't carry its pad type
+///
+/// An `ErasedPin` supports the same operations as a [`GPIO`], but it finds its
+/// registers at runtime. Use `ErasedPin`s to store different pads in an array, or in a
+/// struct field. Use [`erase`](GPIO::erase()) to create an `ErasedPin`.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::gpio::{ErasedPin, Output, GPIO};
+///
+/// let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+/// let mut leds: [ErasedPin<Output>; 2] = [
+///     GPIO::new(pads.b0.p03).output().erase(),
+///     GPIO::new(pads.ad_b1.p02).output().erase(),
+/// ];
+/// for led in leds.iter_mut() {
+///     led.toggle();
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
+pub struct ErasedPin<D> {
+    /// GPIO module, starting at 1
+    pub(super) module: u8,
+    pub(super) offset: u8,
+    dir: PhantomData<D>,
+}
+
+impl<P, D> GPIO<P, D>
+where
+    P: Pin,
+{
+    /// Erase the pad type, so that you may store this GPIO with other GPIOs
+    ///
+    /// You can't release the pad from an `ErasedPin`.
+    pub fn erase(self) -> ErasedPin<D> {
+        ErasedPin {
+            module: <P as Pin>::Module::USIZE as u8,
+            offset: <P as Pin>::Offset::USIZE as u8,
+            dir: PhantomData,
+        }
+    }
+}
+
+impl<D> ErasedPin<D> {
+    /// Rebuild a pin that was consumed by another driver
+    pub(super) fn from_parts(module: u8, offset: u8) -> Self {
+        ErasedPin {
+            module,
+            offset,
+            dir: PhantomData,
+        }
+    }
+
+    fn register_block(&self) -> *const RegisterBlock {
+        register_block(self.module())
+    }
+
+    #[inline(always)]
+    fn offset(&self) -> u32 {
+        1u32 << self.offset
+    }
+
+    #[inline(always)]
+    fn module(&self) -> usize {
+        usize::from(self.module)
+    }
+}
+
+impl ErasedPin<Input> {
+    /// Transition the GPIO from an input to an output
+    pub fn output(self) -> ErasedPin<Output> {
+        // Safety: critical section ensures consistency
+        cortex_m::interrupt::free(|_| unsafe {
+            ral::modify_reg!(ral::gpio, self.register_block(), GDIR, |gdir| gdir
+                | self.offset());
+        });
+        ErasedPin {
+            module: self.module,
+            offset: self.offset,
+            dir: PhantomData,
+        }
+    }
+
+    /// Returns `true` if this input pin is high
+    pub fn is_set(&self) -> bool {
+        // Safety: read is atomic
+        unsafe { ral::read_reg!(ral::gpio, self.register_block(), PSR) & self.offset() != 0 }
+    }
+
+    /// Sets the trigger for the input GPIO, and await for the input event
+    ///
+    /// See [`GPIO::wait_for`] for more information.
+    pub fn wait_for(&mut self, trigger: Trigger) -> ErasedInterrupt<'_> {
+        ErasedInterrupt {
+            gpio: self,
+            is_ready: true,
+            trigger,
+        }
+    }
+
+    /// Set the trigger, and wake `waker` once the trigger occurs
+    ///
+    /// See [`GPIO::arm`] for more information.
+    pub fn arm(&mut self, trigger: Trigger, waker: &Waker) {
+        let module = self.module() - 1;
+        let offset = usize::from(self.offset);
+        // Safety: the GPIO owns this pin's trigger configuration
+        unsafe { set_trigger(self.register_block(), offset, trigger) };
+        WAKERS[module][offset].register(waker);
+        REGISTERED[module].fetch_or(1 << offset, atomic::Ordering::Release);
+        cortex_m::interrupt::free(|_| unsafe {
+            ral::modify_reg!(ral::gpio, self.register_block(), IMR, |imr| imr
+                | self.offset())
+        });
+    }
+
+    /// Returns `true` if the pin is waiting for its trigger
+    pub fn is_armed(&self) -> bool {
+        REGISTERED[self.module() - 1].load(atomic::Ordering::Acquire) & self.offset() != 0
+    }
+
+    /// Stop waiting for the trigger, and drop the waker
+    pub fn disarm(&mut self) {
+        let module = self.module() - 1;
+        let offset = usize::from(self.offset);
+        cortex_m::interrupt::free(|_| {
+            REGISTERED[module].fetch_and(!(1 << offset), atomic::Ordering::Relaxed);
+            WAKERS[module][offset].take();
+        });
+    }
+}
+
+impl ErasedPin<Output> {
+    /// Transition the pin from an output to an input
+    pub fn input(self) -> ErasedPin<Input> {
+        // Safety: critical section ensures consistency
+        cortex_m::interrupt::free(|_| unsafe {
+            ral::modify_reg!(ral::gpio, self.register_block(), GDIR, |gdir| gdir
+                & !self.offset());
+        });
+        ErasedPin {
+            module: self.module,
+            offset: self.offset,
+            dir: PhantomData,
+        }
+    }
+
+    /// Drive the GPIO high
+    pub fn set(&mut self) {
+        // Safety: atomic write
+        unsafe { ral::write_reg!(ral::gpio, self.register_block(), DR_SET, self.offset()) };
+    }
+
+    /// Drive the GPIO low
+    pub fn clear(&mut self) {
+        // Safety: atomic write
+        unsafe { ral::write_reg!(ral::gpio, self.register_block(), DR_CLEAR, self.offset()) };
+    }
+
+    /// Returns `true` if the pin is driving high
+    pub fn is_set(&self) -> bool {
+        // Safety: atomic read
+        unsafe { ral::read_reg!(ral::gpio, self.register_block(), DR) & self.offset() != 0u32 }
+    }
+
+    /// Alternate the state of the pin
+    pub fn toggle(&mut self) {
+        // Safety: atomic write
+        unsafe { ral::write_reg!(ral::gpio, self.register_block(), DR_TOGGLE, self.offset()) }
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl embedded_hal::digital::v2::InputPin for ErasedPin<Input> {
+    type Error = core::convert::Infallible;
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.is_set())
+    }
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set())
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl embedded_hal::digital::v2::OutputPin for ErasedPin<Output> {
+    type Error = core::convert::Infallible;
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set();
+        Ok(())
+    }
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.clear();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl embedded_hal::digital::v2::StatefulOutputPin for ErasedPin<Output> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.is_set())
+    }
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set())
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl embedded_hal::digital::v2::ToggleableOutputPin for ErasedPin<Output> {
+    type Error = core::convert::Infallible;
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        ErasedPin::toggle(self);
+        Ok(())
+    }
+}
+
+/// A future that awaits an [`ErasedPin`]'s trigger
+///
+/// Use [`wait_for`](ErasedPin::wait_for()) to create the future.
+pub struct ErasedInterrupt<'t> {
+    gpio: &'t mut ErasedPin<Input>,
+    is_ready: bool,
+    trigger: Trigger,
+}
+
+impl Future for ErasedInterrupt<'_> {
+    type Output = ();
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.is_ready {
+            this.is_ready = false;
+            this.gpio.arm(this.trigger, cx.waker());
+            Poll::Pending
+        } else if !this.gpio.is_armed() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for ErasedInterrupt<'_> {
+    fn drop(&mut self) {
+        self.gpio.disarm();
+    }
+}
+
+/// Wait for the first of several inputs to meet `trigger`
+///
+/// The future completes with the index of the pin that met its trigger. If several pins
+/// met their triggers, it returns the lowest index. All pins are disarmed when the future
+/// completes, or when it drops. The future doesn't need memory for each pin; the pins
+/// share the task's waker.
+///
+/// # Panics
+///
+/// The future panics if `pins` is empty.
+///
+/// # Example
+///
+/// Wait for any of three buttons.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::gpio::{self, ErasedPin, Input, Trigger, GPIO};
+///
+/// let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+/// let mut buttons: [ErasedPin<Input>; 3] = [
+///     GPIO::new(pads.b0.p01).erase(),
+///     GPIO::new(pads.b0.p02).erase(),
+///     GPIO::new(pads.b0.p03).erase(),
+/// ];
+/// # async {
+/// let pressed = gpio::wait_any(&mut buttons, Trigger::FallingEdge).await;
+/// # };
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
+pub fn wait_any(pins: &mut [ErasedPin<Input>], trigger: Trigger) -> WaitAny<'_> {
+    WaitAny {
+        pins,
+        is_ready: true,
+        trigger,
+    }
+}
+
+/// A future that awaits the first of several inputs
+///
+/// Use [`wait_any`] to create the future.
+pub struct WaitAny<'t> {
+    pins: &'t mut [ErasedPin<Input>],
+    is_ready: bool,
+    trigger: Trigger,
+}
+
+impl Future for WaitAny<'_> {
+    type Output = usize;
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        assert!(!this.pins.is_empty(), "wait_any requires at least one pin");
+        if this.is_ready {
+            this.is_ready = false;
+            let trigger = this.trigger;
+            this.pins
+                .iter_mut()
+                .for_each(|pin| pin.arm(trigger, cx.waker()));
+            return Poll::Pending;
+        }
+        match this.pins.iter().position(|pin| !pin.is_armed()) {
+            Some(index) => {
+                this.pins.iter_mut().for_each(ErasedPin::disarm);
+                Poll::Ready(index)
+            }
+            None => {
+                this.pins.iter().for_each(|pin| {
+                    WAKERS[pin.module() - 1][usize::from(pin.offset)].register(cx.waker())
+                });
+                // A pin may have met its trigger before we registered the waker
+                if this.pins.iter().any(|pin| !pin.is_armed()) {
+                    cx.waker().wake_by_ref();
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for WaitAny<'_> {
+    fn drop(&mut self) {
+        self.pins.iter_mut().for_each(ErasedPin::disarm);
+    }
+}
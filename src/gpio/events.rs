@@ -0,0 +1,82 @@
+//! A stream of GPIO edges
+
+use super::{pulse_counter, Input, Pin, Trigger, GPIO};
+use core::{
+    pin,
+    sync::atomic,
+    task::{Context, Poll},
+};
+use futures::stream::Stream;
+
+impl<P> GPIO<P, Input>
+where
+    P: Pin,
+{
+    /// Returns a stream of the `trigger` edges on this input
+    ///
+    /// The interrupt handler counts edges while the stream exists, so no edge is missed
+    /// between polls. Each item is the number of edges since the previous item, and it's
+    /// always at least one. Once the stream drops, the input stops counting edges.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trigger` is [`Trigger::Low`] or [`Trigger::High`].
+    ///
+    /// # Example
+    ///
+    /// Handle every falling edge from a button, even if the task falls behind.
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::gpio::{GPIO, Trigger};
+    /// use futures::stream::StreamExt;
+    ///
+    /// let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+    /// let mut button = GPIO::new(pads.b0.p03);
+    /// let mut presses = 0u32;
+    /// # async {
+    /// let mut edges = button.events(Trigger::FallingEdge);
+    /// while let Some(count) = edges.next().await {
+    ///     presses += count;
+    /// }
+    /// # };
+    /// ```
+    pub fn events(&mut self, trigger: Trigger) -> Events<'_, P> {
+        pulse_counter::start(self, trigger);
+        Events { gpio: self }
+    }
+}
+
+/// A stream of GPIO edges
+///
+/// Use [`events`](GPIO::events()) to create the stream.
+#[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
+pub struct Events<'g, P>
+where
+    P: Pin,
+{
+    gpio: &'g mut GPIO<P, Input>,
+}
+
+impl<'g, P> Stream for Events<'g, P>
+where
+    P: Pin,
+{
+    type Item = u32;
+    fn poll_next(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<u32>> {
+        let this = self.get_mut();
+        pulse_counter::poll_count(this.gpio, 1, cx).map(|_| {
+            let edges = pulse_counter::counter(this.gpio).swap(0, atomic::Ordering::Relaxed);
+            Some(edges)
+        })
+    }
+}
+
+impl<'g, P> Drop for Events<'g, P>
+where
+    P: Pin,
+{
+    fn drop(&mut self) {
+        pulse_counter::stop(self.gpio);
+    }
+}
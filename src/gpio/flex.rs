@@ -0,0 +1,157 @@
+//! GPIOs with a runtime direction
+
+use super::{Input, Interrupt, Output, Pin, Trigger, GPIO};
+use crate::ral;
+use core::marker::PhantomData;
+
+/// A GPIO whose direction changes at runtime
+///
+/// A `Flex` GPIO switches between input and output without consuming itself. Use it for
+/// protocols that turn the bus around within a transaction, like 1-Wire. Create a `Flex`
+/// from any [`GPIO`]; it keeps the GPIO's direction.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::gpio::{Flex, GPIO, Trigger};
+///
+/// let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+/// let mut data = Flex::from(GPIO::new(pads.b0.p03));
+/// # async {
+/// // Pull the line low, then release it and wait for the response
+/// data.clear();
+/// data.set_as_output();
+/// // ...
+/// data.set_as_input();
+/// data.wait_for(Trigger::FallingEdge).await;
+/// # };
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
+pub struct Flex<P>
+where
+    P: Pin,
+{
+    /// The direction marker is unused; `GDIR` holds the direction
+    gpio: GPIO<P, Input>,
+}
+
+impl<P, D> From<GPIO<P, D>> for Flex<P>
+where
+    P: Pin,
+{
+    fn from(gpio: GPIO<P, D>) -> Self {
+        Flex {
+            gpio: GPIO {
+                pin: gpio.pin,
+                dir: PhantomData,
+            },
+        }
+    }
+}
+
+impl<P> Flex<P>
+where
+    P: Pin,
+{
+    /// Make the GPIO an input
+    pub fn set_as_input(&mut self) {
+        // Safety: critical section ensures consistency
+        cortex_m::interrupt::free(|_| unsafe {
+            ral::modify_reg!(ral::gpio, self.gpio.register_block(), GDIR, |gdir| gdir
+                & !self.gpio.offset());
+        });
+    }
+
+    /// Make the GPIO an output
+    ///
+    /// The output drives the last level that you selected with [`set`](Flex::set()) or
+    /// [`clear`](Flex::clear()).
+    pub fn set_as_output(&mut self) {
+        // Safety: critical section ensures consistency
+        cortex_m::interrupt::free(|_| unsafe {
+            ral::modify_reg!(ral::gpio, self.gpio.register_block(), GDIR, |gdir| gdir
+                | self.gpio.offset());
+        });
+    }
+
+    /// Returns `true` if the GPIO is an output
+    pub fn is_output(&self) -> bool {
+        // Safety: atomic read
+        unsafe {
+            ral::read_reg!(ral::gpio, self.gpio.register_block(), GDIR) & self.gpio.offset() != 0
+        }
+    }
+
+    /// Returns `true` if the pad is high
+    ///
+    /// While the GPIO is an output, the pad only reads back if the pad's input path
+    /// is enabled.
+    pub fn is_set(&self) -> bool {
+        self.gpio.is_set()
+    }
+
+    /// Select a high output
+    ///
+    /// If the GPIO is an input, it drives high once it becomes an output.
+    pub fn set(&mut self) {
+        // Safety: atomic write
+        unsafe {
+            ral::write_reg!(
+                ral::gpio,
+                self.gpio.register_block(),
+                DR_SET,
+                self.gpio.offset()
+            )
+        };
+    }
+
+    /// Select a low output
+    ///
+    /// If the GPIO is an input, it drives low once it becomes an output.
+    pub fn clear(&mut self) {
+        // Safety: atomic write
+        unsafe {
+            ral::write_reg!(
+                ral::gpio,
+                self.gpio.register_block(),
+                DR_CLEAR,
+                self.gpio.offset()
+            )
+        };
+    }
+
+    /// Alternate the selected output level
+    pub fn toggle(&mut self) {
+        // Safety: atomic write
+        unsafe {
+            ral::write_reg!(
+                ral::gpio,
+                self.gpio.register_block(),
+                DR_TOGGLE,
+                self.gpio.offset()
+            )
+        };
+    }
+
+    /// Set the trigger, and await the input event
+    ///
+    /// Make the GPIO an input before you wait. See [`GPIO::wait_for`] for more
+    /// information.
+    pub fn wait_for(&mut self, trigger: Trigger) -> Interrupt<'_, P> {
+        self.gpio.wait_for(trigger)
+    }
+
+    /// Make the GPIO an input, and return the typed GPIO
+    pub fn into_input(mut self) -> GPIO<P, Input> {
+        self.set_as_input();
+        self.gpio
+    }
+
+    /// Make the GPIO an output, and return the typed GPIO
+    pub fn into_output(mut self) -> GPIO<P, Output> {
+        self.set_as_output();
+        GPIO {
+            pin: self.gpio.pin,
+            dir: PhantomData,
+        }
+    }
+}
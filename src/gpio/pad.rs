@@ -0,0 +1,82 @@
+//! Pad configuration for GPIOs
+
+use super::{Pin, GPIO};
+use crate::iomuxc::{self, Config, DriveStrength, OpenDrain, PullKeep, PullKeepSelect, PullUpDown};
+
+/// A GPIO pad's pull resistor, or keeper
+///
+/// See [`set_pull`](GPIO::set_pull()) for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
+pub enum Pull {
+    /// No pull resistor, and no keeper
+    None,
+    /// Hold the pad's last driven level
+    Keeper,
+    /// 100KOhm pull down
+    Down100k,
+    /// 22KOhm pull up
+    Up22k,
+    /// 47KOhm pull up
+    Up47k,
+    /// 100KOhm pull up
+    Up100k,
+}
+
+impl<P, D> GPIO<P, D>
+where
+    P: Pin,
+{
+    /// Apply `config` to the GPIO's pad
+    ///
+    /// Use `configure` for pad settings that don't have their own method, like the
+    /// slew rate or hysteresis.
+    pub fn configure(&mut self, config: Config) {
+        iomuxc::configure(&mut self.pin, config);
+    }
+
+    /// Select the pad's pull resistor, or keeper
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::gpio::{GPIO, Pull};
+    ///
+    /// let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+    /// let mut button = GPIO::new(pads.b0.p03);
+    /// button.set_pull(Pull::Up22k);
+    /// ```
+    pub fn set_pull(&mut self, pull: Pull) {
+        let config = Config::modify();
+        let config = match pull {
+            Pull::None => config.set_pull_keep(PullKeep::Disabled),
+            Pull::Keeper => config
+                .set_pull_keep(PullKeep::Enabled)
+                .set_pull_keep_select(PullKeepSelect::Keeper),
+            pull => config
+                .set_pull_keep(PullKeep::Enabled)
+                .set_pull_keep_select(PullKeepSelect::Pull)
+                .set_pullupdown(match pull {
+                    Pull::Down100k => PullUpDown::Pulldown100k,
+                    Pull::Up22k => PullUpDown::Pullup22k,
+                    Pull::Up47k => PullUpDown::Pullup47k,
+                    Pull::Up100k => PullUpDown::Pullup100k,
+                    _ => unreachable!("Pull::None and Pull::Keeper handled above"),
+                }),
+        };
+        self.configure(config);
+    }
+
+    /// Enable or disable the pad's open drain output
+    pub fn set_open_drain(&mut self, open_drain: bool) {
+        self.configure(Config::modify().set_open_drain(if open_drain {
+            OpenDrain::Enabled
+        } else {
+            OpenDrain::Disabled
+        }));
+    }
+
+    /// Select the pad's output drive strength
+    pub fn set_drive_strength(&mut self, drive_strength: DriveStrength) {
+        self.configure(Config::modify().set_drive_strength(drive_strength));
+    }
+}
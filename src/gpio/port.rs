@@ -0,0 +1,98 @@
+//! Simultaneous writes to GPIO outputs
+
+use super::{register_block, ErasedPin, Output};
+use crate::ral;
+
+/// Drives a group of outputs with one register write
+///
+/// A `PortWriter` owns outputs from the same GPIO module. [`write`](PortWriter::write())
+/// drives the high outputs with one store to the module's set register, then drives the
+/// low outputs with one store to its clear register. The stores only touch the owned
+/// outputs, so they don't need a critical section, and no output glitches. Use a
+/// `PortWriter` for a parallel data bus, like an 8-bit LCD interface.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::gpio::{PortWriter, GPIO};
+///
+/// let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+/// let mut bus = PortWriter::new([
+///     GPIO::new(pads.b0.p00).output().erase(),
+///     GPIO::new(pads.b0.p01).output().erase(),
+///     GPIO::new(pads.b0.p02).output().erase(),
+///     GPIO::new(pads.b0.p03).output().erase(),
+/// ]);
+/// bus.write(0b1010);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
+pub struct PortWriter {
+    /// GPIO module, starting at 1
+    module: u8,
+    /// Pin offsets, in bit order
+    offsets: [u8; 32],
+    len: usize,
+    /// Bitmask of all pins
+    mask: u32,
+}
+
+impl PortWriter {
+    /// Create a `PortWriter` that owns `pins`
+    ///
+    /// The first pin is bit 0 of the value that you [`write`](PortWriter::write()),
+    /// the second pin is bit 1, and so on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pins` is empty, or if the pins aren't in the same GPIO module.
+    pub fn new<I>(pins: I) -> Self
+    where
+        I: IntoIterator<Item = ErasedPin<Output>>,
+    {
+        let mut writer = PortWriter {
+            module: 0,
+            offsets: [0; 32],
+            len: 0,
+            mask: 0,
+        };
+        for pin in pins {
+            assert!(
+                writer.len == 0 || writer.module == pin.module,
+                "PortWriter pins must be in the same GPIO module"
+            );
+            writer.module = pin.module;
+            writer.offsets[writer.len] = pin.offset;
+            writer.len += 1;
+            writer.mask |= 1 << pin.offset;
+        }
+        assert!(writer.len > 0, "PortWriter requires at least one pin");
+        writer
+    }
+
+    /// Drive all pins, using one bit of `value` for each pin
+    ///
+    /// Bits beyond the number of pins are ignored.
+    pub fn write(&mut self, value: u32) {
+        let bits = self.offsets[..self.len]
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| value & (1 << bit) != 0)
+            .fold(0u32, |bits, (_, offset)| bits | (1 << offset));
+        let gpio = register_block(usize::from(self.module));
+        // Safety: atomic writes, which only affect the owned pins
+        unsafe {
+            ral::write_reg!(ral::gpio, gpio, DR_SET, bits);
+            ral::write_reg!(ral::gpio, gpio, DR_CLEAR, self.mask & !bits);
+        }
+    }
+
+    /// Release the pins, in bit order
+    pub fn release(self) -> impl Iterator<Item = ErasedPin<Output>> {
+        let PortWriter {
+            module,
+            offsets,
+            len,
+            ..
+        } = self;
+        (0..len).map(move |bit| ErasedPin::from_parts(module, offsets[bit]))
+    }
+}
@@ -0,0 +1,258 @@
+//! Count GPIO edges in the interrupt handler
+
+use super::{Input, Pin, Trigger, Unsigned, GPIO, REGISTERED, WAKERS};
+use crate::ral;
+use core::{
+    future::Future,
+    mem::ManuallyDrop,
+    pin, ptr,
+    sync::atomic,
+    task::{Context, Poll},
+};
+
+/// For each module, a bitmap of the pins that are counting edges
+pub(super) static COUNTING: [atomic::AtomicU32; 5] = [
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+];
+
+/// Edge counts, indexed by module, then pin offset
+static COUNTS: [[atomic::AtomicU32; 32]; 5] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: atomic::AtomicU32 = atomic::AtomicU32::new(0);
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZEROS: [atomic::AtomicU32; 32] = [
+        ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO,
+        ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO, ZERO,
+        ZERO, ZERO,
+    ];
+    [ZEROS, ZEROS, ZEROS, ZEROS, ZEROS]
+};
+
+/// The count that wakes a waiting task, indexed by module, then pin offset
+///
+/// Only written in critical sections.
+static mut TARGETS: [[u32; 32]; 5] = [[0; 32]; 5];
+
+/// Increment the counts for the `counting` pins
+///
+/// Returns the pins that reached their target. Called from the GPIO interrupt handler.
+#[inline(always)]
+pub(super) unsafe fn count(module: usize, mut counting: u32) -> u32 {
+    let mut ready = 0;
+    while counting != 0 {
+        let offset = counting.trailing_zeros() as usize;
+        let count = COUNTS[module][offset]
+            .fetch_add(1, atomic::Ordering::Relaxed)
+            .wrapping_add(1);
+        if count >= TARGETS[module][offset] {
+            ready |= 1 << offset;
+        }
+        counting &= counting - 1;
+    }
+    ready
+}
+
+/// Start counting `trigger` edges on `gpio`, from zero
+///
+/// # Panics
+///
+/// Panics if `trigger` isn't an edge trigger.
+pub(super) fn start<P: Pin>(gpio: &mut GPIO<P, Input>, trigger: Trigger) {
+    assert!(
+        trigger != Trigger::Low && trigger != Trigger::High,
+        "Counting GPIO edges requires an edge trigger"
+    );
+    let module = gpio.module() - 1;
+    let offset = <P as Pin>::Offset::USIZE;
+    gpio.disarm();
+    gpio.set_trigger(trigger);
+    COUNTS[module][offset].store(0, atomic::Ordering::Relaxed);
+    COUNTING[module].fetch_or(1 << offset, atomic::Ordering::Relaxed);
+    cortex_m::interrupt::free(|_| unsafe {
+        ral::write_reg!(ral::gpio, gpio.register_block(), ISR, gpio.offset());
+        ral::modify_reg!(ral::gpio, gpio.register_block(), IMR, |imr| imr
+            | gpio.offset());
+    });
+}
+
+/// Stop counting edges on `gpio`, and drop any waker
+pub(super) fn stop<P: Pin>(gpio: &GPIO<P, Input>) {
+    let module = gpio.module() - 1;
+    let offset = <P as Pin>::Offset::USIZE;
+    cortex_m::interrupt::free(|_| unsafe {
+        ral::modify_reg!(ral::gpio, gpio.register_block(), IMR, |imr| imr
+            & !gpio.offset());
+        COUNTING[module].fetch_and(!(1 << offset), atomic::Ordering::Relaxed);
+        REGISTERED[module].fetch_and(!(1 << offset), atomic::Ordering::Relaxed);
+        WAKERS[module][offset].take();
+    });
+}
+
+/// Returns `gpio`'s edge count
+pub(super) fn counter<P: Pin>(gpio: &GPIO<P, Input>) -> &'static atomic::AtomicU32 {
+    &COUNTS[gpio.module() - 1][<P as Pin>::Offset::USIZE]
+}
+
+/// Ready once `gpio`'s count reaches `target`, otherwise wakes the task when it does
+pub(super) fn poll_count<P: Pin>(
+    gpio: &GPIO<P, Input>,
+    target: u32,
+    cx: &mut Context<'_>,
+) -> Poll<()> {
+    let module = gpio.module() - 1;
+    let offset = <P as Pin>::Offset::USIZE;
+    let counter = counter(gpio);
+    if counter.load(atomic::Ordering::Relaxed) >= target {
+        return Poll::Ready(());
+    }
+    cortex_m::interrupt::free(|_| unsafe {
+        TARGETS[module][offset] = target;
+        WAKERS[module][offset].register(cx.waker());
+        REGISTERED[module].fetch_or(1 << offset, atomic::Ordering::Release);
+        // An edge may have reached the target before we registered the waker
+        if counter.load(atomic::Ordering::Relaxed) >= target {
+            REGISTERED[module].fetch_and(!(1 << offset), atomic::Ordering::Relaxed);
+            WAKERS[module][offset].take();
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+}
+
+/// Stop waking a task for `gpio`, while still counting edges
+pub(super) fn unregister<P: Pin>(gpio: &GPIO<P, Input>) {
+    let module = gpio.module() - 1;
+    let offset = <P as Pin>::Offset::USIZE;
+    cortex_m::interrupt::free(|_| {
+        REGISTERED[module].fetch_and(!(1 << offset), atomic::Ordering::Relaxed);
+        WAKERS[module][offset].take();
+    });
+}
+
+/// Counts GPIO edges
+///
+/// A `PulseCounter` counts edges in the GPIO interrupt handler. It only wakes a task
+/// once the count reaches the task's target, so it's suitable for flow meters,
+/// tachometers, and other inputs with modest edge rates. Every edge still costs an
+/// interrupt; for high edge rates, consider a hardware counter.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::gpio::{GPIO, PulseCounter, Trigger};
+///
+/// let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+/// let mut counter = PulseCounter::new(GPIO::new(pads.b0.p03), Trigger::RisingEdge);
+/// # async {
+/// counter.wait_for_count(100).await;
+/// let pulses = counter.reset();
+/// # };
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
+pub struct PulseCounter<P>
+where
+    P: Pin,
+{
+    gpio: GPIO<P, Input>,
+}
+
+impl<P> PulseCounter<P>
+where
+    P: Pin,
+{
+    /// Count the `trigger` edges on the input `gpio`
+    ///
+    /// The count starts at zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trigger` is [`Trigger::Low`] or [`Trigger::High`]. Only edges can
+    /// be counted.
+    pub fn new(mut gpio: GPIO<P, Input>, trigger: Trigger) -> Self {
+        start(&mut gpio, trigger);
+        PulseCounter { gpio }
+    }
+
+    /// Returns the number of edges counted since creation, or since the last
+    /// [`reset`](PulseCounter::reset())
+    pub fn count(&self) -> u32 {
+        self.counter().load(atomic::Ordering::Relaxed)
+    }
+
+    /// Reset the count to zero, returning the previous count
+    ///
+    /// No edges are lost between the read and the reset.
+    pub fn reset(&mut self) -> u32 {
+        self.counter().swap(0, atomic::Ordering::Relaxed)
+    }
+
+    /// Wait until the count reaches `count`
+    ///
+    /// Completes immediately if the count is already at or above `count`.
+    pub fn wait_for_count(&mut self, count: u32) -> WaitForCount<'_, P> {
+        WaitForCount {
+            counter: self,
+            count,
+        }
+    }
+
+    /// Stop counting, and return the input GPIO
+    pub fn release(self) -> GPIO<P, Input> {
+        let this = ManuallyDrop::new(self);
+        this.stop();
+        // Safety: `this` is never dropped, so the GPIO is only moved once.
+        unsafe { ptr::read(&this.gpio) }
+    }
+
+    fn counter(&self) -> &'static atomic::AtomicU32 {
+        counter(&self.gpio)
+    }
+
+    fn stop(&self) {
+        stop(&self.gpio);
+    }
+}
+
+impl<P> Drop for PulseCounter<P>
+where
+    P: Pin,
+{
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A future that awaits a [`PulseCounter`] count
+///
+/// Use [`wait_for_count`](PulseCounter::wait_for_count()) to create the future.
+pub struct WaitForCount<'c, P>
+where
+    P: Pin,
+{
+    counter: &'c mut PulseCounter<P>,
+    count: u32,
+}
+
+impl<'c, P> Future for WaitForCount<'c, P>
+where
+    P: Pin,
+{
+    type Output = ();
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        poll_count(&this.counter.gpio, this.count, cx)
+    }
+}
+
+impl<'c, P> Drop for WaitForCount<'c, P>
+where
+    P: Pin,
+{
+    fn drop(&mut self) {
+        unregister(&self.counter.gpio);
+    }
+}
@@ -0,0 +1,314 @@
+//! Decode quadrature signals in the GPIO interrupt handler
+
+use super::{Input, Pin, Trigger, Unsigned, GPIO};
+use crate::ral::{self, gpio::RegisterBlock};
+use core::{
+    future::Future,
+    mem::ManuallyDrop,
+    pin, ptr,
+    sync::atomic,
+    task::{Context, Poll},
+};
+use futures::task::AtomicWaker;
+
+/// The maximum number of [`Quadrature`] decoders that may exist at once
+pub const MAX_DECODERS: usize = 4;
+
+/// For each module, a bitmap of the pins that are decoding quadrature signals
+pub(super) static DECODING: [atomic::AtomicU32; 5] = [
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+    atomic::AtomicU32::new(0),
+];
+
+/// The decoder that owns each pin, indexed by module, then pin offset
+///
+/// Only written in critical sections.
+static mut SLOTS: [[u8; 32]; 5] = [[0; 32]; 5];
+
+/// Only written in critical sections
+static mut DECODERS: [Option<Decoder>; MAX_DECODERS] = [None; MAX_DECODERS];
+
+/// Decoder positions, indexed by slot
+static POSITIONS: [atomic::AtomicI32; MAX_DECODERS] = [
+    atomic::AtomicI32::new(0),
+    atomic::AtomicI32::new(0),
+    atomic::AtomicI32::new(0),
+    atomic::AtomicI32::new(0),
+];
+
+/// Wakers for the decoders that are awaiting a delta, indexed by slot
+static DECODER_WAKERS: [AtomicWaker; MAX_DECODERS] = [
+    AtomicWaker::new(),
+    AtomicWaker::new(),
+    AtomicWaker::new(),
+    AtomicWaker::new(),
+];
+
+/// The position change for each transition, indexed by `(previous state << 2) | state`
+///
+/// The state is `(A << 1) | B`. Invalid transitions, which skip a state, don't change
+/// the position.
+const STEPS: [i32; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
+#[derive(Clone, Copy)]
+struct Line {
+    gpio: *const RegisterBlock,
+    mask: u32,
+}
+
+impl Line {
+    unsafe fn is_set(self) -> bool {
+        ral::read_reg!(ral::gpio, self.gpio, PSR) & self.mask != 0
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Decoder {
+    a: Line,
+    b: Line,
+    /// The last `(A << 1) | B` state
+    state: u8,
+    /// The position when the waiting task started waiting
+    start: i32,
+    /// The position change that wakes the waiting task
+    delta: u32,
+}
+
+impl Decoder {
+    unsafe fn read(&self) -> u8 {
+        ((self.a.is_set() as u8) << 1) | self.b.is_set() as u8
+    }
+}
+
+/// Update the positions of the decoders that own the `decoding` pins
+///
+/// Called from the GPIO interrupt handler.
+#[inline(always)]
+pub(super) unsafe fn decode(module: usize, mut decoding: u32) {
+    while decoding != 0 {
+        let offset = decoding.trailing_zeros() as usize;
+        let slot = SLOTS[module][offset] as usize;
+        if let Some(decoder) = &mut DECODERS[slot] {
+            let state = decoder.read();
+            let step = STEPS[((decoder.state << 2) | state) as usize];
+            decoder.state = state;
+            let position = POSITIONS[slot]
+                .fetch_add(step, atomic::Ordering::Relaxed)
+                .wrapping_add(step);
+            if position.wrapping_sub(decoder.start).wrapping_abs() as u32 >= decoder.delta {
+                DECODER_WAKERS[slot].wake();
+            }
+        }
+        decoding &= decoding - 1;
+    }
+}
+
+/// A quadrature decoder that uses two GPIO inputs
+///
+/// `Quadrature` decodes the A and B signals of an incremental encoder in the GPIO
+/// interrupt handler. Use it when the encoder isn't connected to pads that support a
+/// hardware decoder. Each edge on A or B changes the position by one, so the position
+/// changes by four for each encoder cycle. The position increases when A leads B.
+///
+/// Every edge costs an interrupt, so `Quadrature` is only suitable for modest edge rates.
+/// If the decoder misses a state, it doesn't change the position.
+///
+/// At most [`MAX_DECODERS`] decoders may exist at once.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::gpio::{GPIO, Quadrature};
+///
+/// let pads = hal::iomuxc::new(hal::ral::iomuxc::IOMUXC::take().unwrap());
+/// let mut encoder = Quadrature::new(GPIO::new(pads.b0.p02), GPIO::new(pads.b0.p03));
+/// # async {
+/// encoder.wait_for_delta(400).await;
+/// let position = encoder.position();
+/// # };
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
+pub struct Quadrature<A, B>
+where
+    A: Pin,
+    B: Pin,
+{
+    a: GPIO<A, Input>,
+    b: GPIO<B, Input>,
+    slot: usize,
+}
+
+impl<A, B> Quadrature<A, B>
+where
+    A: Pin,
+    B: Pin,
+{
+    /// Decode the quadrature signals on inputs `a` and `b`
+    ///
+    /// The position starts at zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`MAX_DECODERS`] decoders already exist.
+    pub fn new(mut a: GPIO<A, Input>, mut b: GPIO<B, Input>) -> Self {
+        a.disarm();
+        b.disarm();
+        a.set_trigger(Trigger::EitherEdge);
+        b.set_trigger(Trigger::EitherEdge);
+
+        let (a_module, a_offset) = (a.module() - 1, <A as Pin>::Offset::USIZE);
+        let (b_module, b_offset) = (b.module() - 1, <B as Pin>::Offset::USIZE);
+        let slot = cortex_m::interrupt::free(|_| unsafe {
+            let slot = DECODERS
+                .iter()
+                .position(Option::is_none)
+                .expect("All quadrature decoders are in use");
+            let mut decoder = Decoder {
+                a: Line {
+                    gpio: a.register_block(),
+                    mask: a.offset(),
+                },
+                b: Line {
+                    gpio: b.register_block(),
+                    mask: b.offset(),
+                },
+                state: 0,
+                start: 0,
+                delta: u32::max_value(),
+            };
+            decoder.state = decoder.read();
+            DECODERS[slot] = Some(decoder);
+            POSITIONS[slot].store(0, atomic::Ordering::Relaxed);
+            SLOTS[a_module][a_offset] = slot as u8;
+            SLOTS[b_module][b_offset] = slot as u8;
+            DECODING[a_module].fetch_or(1 << a_offset, atomic::Ordering::Relaxed);
+            DECODING[b_module].fetch_or(1 << b_offset, atomic::Ordering::Relaxed);
+
+            ral::write_reg!(ral::gpio, a.register_block(), ISR, a.offset());
+            ral::write_reg!(ral::gpio, b.register_block(), ISR, b.offset());
+            ral::modify_reg!(ral::gpio, a.register_block(), IMR, |imr| imr | a.offset());
+            ral::modify_reg!(ral::gpio, b.register_block(), IMR, |imr| imr | b.offset());
+            slot
+        });
+        Quadrature { a, b, slot }
+    }
+
+    /// Returns the current position
+    pub fn position(&self) -> i32 {
+        POSITIONS[self.slot].load(atomic::Ordering::Relaxed)
+    }
+
+    /// Set the current position, returning the previous position
+    pub fn set_position(&mut self, position: i32) -> i32 {
+        POSITIONS[self.slot].swap(position, atomic::Ordering::Relaxed)
+    }
+
+    /// Wait until the position changes by at least `delta`, in either direction
+    ///
+    /// The change is measured from the position when the future is first polled.
+    pub fn wait_for_delta(&mut self, delta: u32) -> WaitForDelta<'_, A, B> {
+        WaitForDelta {
+            decoder: self,
+            delta,
+            start: None,
+        }
+    }
+
+    /// Stop decoding, and return the A and B inputs
+    pub fn release(self) -> (GPIO<A, Input>, GPIO<B, Input>) {
+        let this = ManuallyDrop::new(self);
+        this.stop();
+        // Safety: `this` is never dropped, so the GPIOs are only moved once.
+        unsafe { (ptr::read(&this.a), ptr::read(&this.b)) }
+    }
+
+    fn stop(&self) {
+        let (a_module, a_offset) = (self.a.module() - 1, <A as Pin>::Offset::USIZE);
+        let (b_module, b_offset) = (self.b.module() - 1, <B as Pin>::Offset::USIZE);
+        cortex_m::interrupt::free(|_| unsafe {
+            ral::modify_reg!(ral::gpio, self.a.register_block(), IMR, |imr| imr
+                & !self.a.offset());
+            ral::modify_reg!(ral::gpio, self.b.register_block(), IMR, |imr| imr
+                & !self.b.offset());
+            DECODING[a_module].fetch_and(!(1 << a_offset), atomic::Ordering::Relaxed);
+            DECODING[b_module].fetch_and(!(1 << b_offset), atomic::Ordering::Relaxed);
+            DECODERS[self.slot] = None;
+            DECODER_WAKERS[self.slot].take();
+        });
+    }
+}
+
+impl<A, B> Drop for Quadrature<A, B>
+where
+    A: Pin,
+    B: Pin,
+{
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A future that awaits a [`Quadrature`] position change
+///
+/// Use [`wait_for_delta`](Quadrature::wait_for_delta()) to create the future.
+pub struct WaitForDelta<'d, A, B>
+where
+    A: Pin,
+    B: Pin,
+{
+    decoder: &'d mut Quadrature<A, B>,
+    delta: u32,
+    start: Option<i32>,
+}
+
+impl<'d, A, B> Future for WaitForDelta<'d, A, B>
+where
+    A: Pin,
+    B: Pin,
+{
+    type Output = ();
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let slot = this.decoder.slot;
+        let delta = this.delta;
+        let position = this.decoder.position();
+        let start = *this.start.get_or_insert(position);
+        let reached = || {
+            POSITIONS[slot]
+                .load(atomic::Ordering::Relaxed)
+                .wrapping_sub(start)
+                .wrapping_abs() as u32
+                >= delta
+        };
+        if reached() {
+            return Poll::Ready(());
+        }
+        cortex_m::interrupt::free(|_| unsafe {
+            if let Some(decoder) = &mut DECODERS[slot] {
+                decoder.start = start;
+                decoder.delta = delta;
+            }
+            DECODER_WAKERS[slot].register(cx.waker());
+            // The position may have changed before we registered the waker
+            if reached() {
+                DECODER_WAKERS[slot].take();
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+    }
+}
+
+impl<'d, A, B> Drop for WaitForDelta<'d, A, B>
+where
+    A: Pin,
+    B: Pin,
+{
+    fn drop(&mut self) {
+        let slot = self.decoder.slot;
+        DECODER_WAKERS[slot].take();
+    }
+}
@@ -0,0 +1,274 @@
+//! IOMUXC general purpose registers (GPR)
+//!
+//! The IOMUXC GPRs hold a collection of chip-level settings that don't belong to
+//! any one peripheral. The [`GPR`] driver exposes the settings as typed methods, so
+//! that you don't need to write raw register values.
+//!
+//! | Setting                   | Methods                                              |
+//! | ------------------------- | ---------------------------------------------------- |
+//! | High-speed GPIO selection | [`set_fast_gpio`](GPR::set_fast_gpio())              |
+//! | MQS audio output          | [`enable_mqs`](GPR::enable_mqs())                    |
+//! | FlexRAM bank allocation   | [`set_flexram_banks`](GPR::set_flexram_banks())      |
+//! | ENET reference clock      | [`set_enet_clock`](GPR::set_enet_clock())            |
+//!
+//! # Example
+//!
+//! Route the MQS audio output, and select the external ENET1 reference clock.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::gpr::{Enet, EnetClock, GPR, MqsOversample};
+//! use hal::ral::iomuxc_gpr::IOMUXC_GPR;
+//!
+//! let mut gpr = GPR::new(IOMUXC_GPR::take().unwrap());
+//! gpr.enable_mqs(8, MqsOversample::R32);
+//! gpr.set_enet_clock(Enet::Enet1, EnetClock::External);
+//! ```
+
+use crate::ral;
+
+/// IOMUXC general purpose registers
+///
+/// See the [module-level documentation](crate::gpr) for more information.
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub struct GPR {
+    gpr: ral::iomuxc_gpr::Instance,
+}
+
+/// A standard GPIO module that may be routed to its high-speed GPIO counterpart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub enum GpioBank {
+    /// GPIO1, or high-speed GPIO6
+    Gpio1,
+    /// GPIO2, or high-speed GPIO7
+    Gpio2,
+    /// GPIO3, or high-speed GPIO8
+    Gpio3,
+    /// GPIO4, or high-speed GPIO9
+    Gpio4,
+}
+
+/// MQS oversampling rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub enum MqsOversample {
+    /// 32x oversampling
+    R32,
+    /// 64x oversampling
+    R64,
+}
+
+/// FlexRAM bank allocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub enum FlexRamBank {
+    /// The bank is not used
+    Unused = 0b00,
+    /// The bank is OCRAM
+    Ocram = 0b01,
+    /// The bank is data TCM
+    Dtcm = 0b10,
+    /// The bank is instruction TCM
+    Itcm = 0b11,
+}
+
+/// The number of FlexRAM banks
+pub const FLEXRAM_BANK_COUNT: usize = 16;
+
+/// An ENET controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub enum Enet {
+    /// ENET1
+    Enet1,
+    /// ENET2
+    Enet2,
+}
+
+/// ENET transmit reference clock source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub enum EnetClock {
+    /// The ENET PLL provides the clock, and the clock is output on the ENET TX clock pad
+    Internal,
+    /// An external oscillator provides the clock on the ENET TX clock pad
+    External,
+}
+
+/// Errors propagated from a [`GPR`]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub enum Error {
+    /// The number of ITCM or DTCM banks is not a power of two
+    TcmSize,
+}
+
+// GPR1
+const ENET1_CLK_SEL: u32 = 1 << 13;
+const ENET2_CLK_SEL: u32 = 1 << 14;
+const ENET1_TX_CLK_DIR: u32 = 1 << 17;
+const ENET2_TX_CLK_DIR: u32 = 1 << 18;
+
+// GPR2
+const MQS_CLK_DIV_OFFSET: u32 = 16;
+const MQS_CLK_DIV_MASK: u32 = 0xFF << MQS_CLK_DIV_OFFSET;
+const MQS_SW_RST: u32 = 1 << 24;
+const MQS_EN: u32 = 1 << 25;
+const MQS_OVERSAMPLE: u32 = 1 << 26;
+
+// GPR14
+const CM7_CFGITCMSZ_OFFSET: u32 = 16;
+const CM7_CFGDTCMSZ_OFFSET: u32 = 20;
+const CM7_CFGTCMSZ_MASK: u32 = 0xFF << CM7_CFGITCMSZ_OFFSET;
+
+// GPR16
+const FLEXRAM_BANK_CFG_SEL: u32 = 1 << 2;
+
+impl GPR {
+    /// Create a `GPR` driver from the RAL's IOMUXC GPR instance
+    pub fn new(gpr: ral::iomuxc_gpr::Instance) -> Self {
+        GPR { gpr }
+    }
+
+    /// Return the RAL instance
+    pub fn release(self) -> ral::iomuxc_gpr::Instance {
+        self.gpr
+    }
+
+    /// Route the GPIO pins identified by `pins` to the high-speed GPIO module, if `fast` is `true`,
+    /// or to the standard GPIO module, if `fast` is `false`
+    ///
+    /// `pins` is a bitmask, where bit `n` is pin `n` of the GPIO module. Pins that are
+    /// not in `pins` are unchanged.
+    ///
+    /// The [`gpio`](crate::gpio) module only controls standard GPIO modules. Once a pin is
+    /// routed to a high-speed GPIO, the `gpio` module cannot control that pin.
+    pub fn set_fast_gpio(&mut self, bank: GpioBank, pins: u32, fast: bool) {
+        let select = |gpr: u32| if fast { gpr | pins } else { gpr & !pins };
+        match bank {
+            GpioBank::Gpio1 => ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR26, select),
+            GpioBank::Gpio2 => ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR27, select),
+            GpioBank::Gpio3 => ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR28, select),
+            GpioBank::Gpio4 => ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR29, select),
+        }
+    }
+
+    /// Returns the bitmask of pins that are routed to the high-speed GPIO module
+    pub fn fast_gpio(&self, bank: GpioBank) -> u32 {
+        match bank {
+            GpioBank::Gpio1 => ral::read_reg!(ral::iomuxc_gpr, self.gpr, GPR26),
+            GpioBank::Gpio2 => ral::read_reg!(ral::iomuxc_gpr, self.gpr, GPR27),
+            GpioBank::Gpio3 => ral::read_reg!(ral::iomuxc_gpr, self.gpr, GPR28),
+            GpioBank::Gpio4 => ral::read_reg!(ral::iomuxc_gpr, self.gpr, GPR29),
+        }
+    }
+
+    /// Enable the medium quality sound (MQS) output
+    ///
+    /// `divider` divides the MQS root clock, and should be between 1 and 256. Values outside
+    /// that range are clamped.
+    pub fn enable_mqs(&mut self, divider: u32, oversample: MqsOversample) {
+        let divider = divider.max(1).min(256) - 1;
+        ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR2, |gpr2| {
+            let gpr2 = (gpr2 & !(MQS_CLK_DIV_MASK | MQS_OVERSAMPLE | MQS_SW_RST))
+                | (divider << MQS_CLK_DIV_OFFSET)
+                | MQS_EN;
+            match oversample {
+                MqsOversample::R32 => gpr2,
+                MqsOversample::R64 => gpr2 | MQS_OVERSAMPLE,
+            }
+        });
+    }
+
+    /// Disable the MQS output
+    pub fn disable_mqs(&mut self) {
+        ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR2, |gpr2| gpr2 & !MQS_EN);
+    }
+
+    /// Reset the MQS, holding it in reset if `reset` is `true`
+    pub fn reset_mqs(&mut self, reset: bool) {
+        ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR2, |gpr2| if reset {
+            gpr2 | MQS_SW_RST
+        } else {
+            gpr2 & !MQS_SW_RST
+        });
+    }
+
+    /// Allocate the FlexRAM banks, and size the ITCM and DTCM to match
+    ///
+    /// `banks[0]` is the first FlexRAM bank. Each bank is 32KiB. The number of ITCM banks,
+    /// and the number of DTCM banks, must each be zero, or a power of two. Otherwise,
+    /// `set_flexram_banks` returns [`Error::TcmSize`] and leaves the FlexRAM unchanged.
+    ///
+    /// # Safety
+    ///
+    /// The new allocation takes effect immediately. Make sure that no code, data, or
+    /// stack is in a bank that changes type.
+    pub unsafe fn set_flexram_banks(
+        &mut self,
+        banks: &[FlexRamBank; FLEXRAM_BANK_COUNT],
+    ) -> Result<(), Error> {
+        let count = |kind: FlexRamBank| banks.iter().filter(|&&bank| bank == kind).count() as u32;
+        let itcm = tcm_size(count(FlexRamBank::Itcm))?;
+        let dtcm = tcm_size(count(FlexRamBank::Dtcm))?;
+
+        let cfg = banks
+            .iter()
+            .enumerate()
+            .fold(0, |cfg, (idx, &bank)| cfg | ((bank as u32) << (2 * idx)));
+        ral::write_reg!(ral::iomuxc_gpr, self.gpr, GPR17, cfg);
+        ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR14, |gpr14| (gpr14
+            & !CM7_CFGTCMSZ_MASK)
+            | (itcm << CM7_CFGITCMSZ_OFFSET)
+            | (dtcm << CM7_CFGDTCMSZ_OFFSET));
+        ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR16, |gpr16| gpr16
+            | FLEXRAM_BANK_CFG_SEL);
+        Ok(())
+    }
+
+    /// Returns the FlexRAM bank allocation from the GPRs
+    ///
+    /// If the allocation has not been set with [`set_flexram_banks`](GPR::set_flexram_banks()),
+    /// the FlexRAM uses the allocation from the fuses, and this return may not reflect
+    /// the actual allocation.
+    pub fn flexram_banks(&self) -> [FlexRamBank; FLEXRAM_BANK_COUNT] {
+        let cfg = ral::read_reg!(ral::iomuxc_gpr, self.gpr, GPR17);
+        let mut banks = [FlexRamBank::Unused; FLEXRAM_BANK_COUNT];
+        for (idx, bank) in banks.iter_mut().enumerate() {
+            *bank = match (cfg >> (2 * idx)) & 0b11 {
+                0b00 => FlexRamBank::Unused,
+                0b01 => FlexRamBank::Ocram,
+                0b10 => FlexRamBank::Dtcm,
+                _ => FlexRamBank::Itcm,
+            };
+        }
+        banks
+    }
+
+    /// Select the transmit reference clock source for an ENET controller
+    pub fn set_enet_clock(&mut self, enet: Enet, clock: EnetClock) {
+        let (clk_sel, tx_clk_dir) = match enet {
+            Enet::Enet1 => (ENET1_CLK_SEL, ENET1_TX_CLK_DIR),
+            Enet::Enet2 => (ENET2_CLK_SEL, ENET2_TX_CLK_DIR),
+        };
+        ral::modify_reg!(ral::iomuxc_gpr, self.gpr, GPR1, |gpr1| match clock {
+            EnetClock::Internal => (gpr1 & !clk_sel) | tx_clk_dir,
+            EnetClock::External => (gpr1 | clk_sel) & !tx_clk_dir,
+        });
+    }
+}
+
+/// Computes the TCM size field for `banks` 32KiB banks
+fn tcm_size(banks: u32) -> Result<u32, Error> {
+    if 0 == banks {
+        Ok(0)
+    } else if banks.is_power_of_two() {
+        // 0b0110 == 32KiB, 0b0111 == 64KiB, ...
+        Ok(banks.trailing_zeros() + 6)
+    } else {
+        Err(Error::TcmSize)
+    }
+}
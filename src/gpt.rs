@@ -1,10 +1,13 @@
 //! General purpose timers (GPT)
 //!
-//! The timer **divides the input clock by 5**. This may affect very precise
-//! timing. For a more precise timer, see [`PIT`](crate::pit::PIT).
+//! The GPT counts at its input clock, divided by its prescalers. To select the clock
+//! and the prescalers, use [`with_clock`](crate::gpt::GPT::with_clock). To learn the
+//! resulting counter frequency, use [`clock_hz`](crate::gpt::GPT::clock_hz).
 //!
 //! Each GPT instance turns into three GPT timers. Use [`new`](crate::gpt::GPT::new)
-//! to acquire the three timers.
+//! to acquire the three timers. To select the GPT clock at the same time, use
+//! [`with_clock`](crate::gpt::GPT::with_clock), instead of writing the GPT's clock
+//! registers yourself.
 //!
 //! # Example
 //!
@@ -13,8 +16,8 @@
 //! ```no_run
 //! use imxrt_async_hal as hal;
 //! use hal::ral as ral;
-//! use ral::{ccm, gpt};
-//! use hal::GPT;
+//! use ral::ccm;
+//! use hal::{gpt::ClockSource, GPT};
 //!
 //! let ccm = ccm::CCM::take().unwrap();
 //! // Select 24MHz crystal oscillator, divide by 24 == 1MHz clock
@@ -23,20 +26,82 @@
 //! ral::modify_reg!(ral::ccm, ccm, CCGR1, CG10: 0b11, CG11: 0b11);
 //!
 //! let gpt = hal::ral::gpt::GPT1::take().unwrap();
-//! ral::write_reg!(
-//!     ral::gpt,
-//!     gpt,
-//!     CR,
-//!     EN_24M: 1, // Enable crystal oscillator
-//!     CLKSRC: 0b101 // Crystal oscillator clock source
-//! );
-//! ral::write_reg!(ral::gpt, gpt, PR, PRESCALER24M: 4); // 1MHz / 5 == 200KHz
-//! let (mut gpt, _, _) = GPT::new(gpt);
+//! // 1MHz peripheral clock / 5 == 200KHz
+//! let (mut gpt, _, _) =
+//!     GPT::with_clock(gpt, ClockSource::Peripheral { hz: 1_000_000 }, 5);
 //!
 //! # async {
-//! gpt.delay(250_000u32 / 5).await;
+//! gpt.delay_ms(250).await;
 //! # };
 //! ```
+//!
+//! # Low-power clock
+//!
+//! The GPT may also count the 32.768KHz low-frequency reference clock. Unlike the
+//! 24MHz crystal oscillator path, this clock keeps running in low-power modes, so the
+//! timer can wake the core from STOP mode. Select [`ClockSource::LowFrequency`] when
+//! you create the timers. The timers know their counter frequency, so duration-based
+//! delays convert to the right number of ticks.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{gpt::ClockSource, GPT};
+//!
+//! let gpt1 = hal::ral::gpt::GPT1::take().unwrap();
+//! let (mut gpt, _, _) = GPT::with_clock(gpt1, ClockSource::LowFrequency, 1);
+//!
+//! # async {
+//! gpt.delay_ms(250).await;
+//! # };
+//! ```
+//!
+//! If you select the clock with [`select_low_frequency_clock`], then [`GPT::new`]
+//! detects the clock, and the timers also know their counter frequency. Use
+//! [`ticks_from_micros`] to compute tick counts at compile time.
+//!
+//! # Counting edges
+//!
+//! The GPT may also count the rising edges on its `GPT_CLK` input. The hardware counts
+//! the edges, so the processor isn't interrupted for each edge. Select
+//! [`ClockSource::External`] when you create the timers, then use
+//! [`delay`](GPT::delay()) to wait for a number of edges, and
+//! [`counter`](GPT::counter()) to read the count. If the input is a clock with a known
+//! frequency, provide the frequency, and the timers can convert durations into ticks.
+//!
+//! You must route the input pad to the timer's `GPT_CLK` signal. See your processor's
+//! reference manual for the pad's mux and daisy settings.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{gpt::ClockSource, GPT};
+//!
+//! let gpt2 = hal::ral::gpt::GPT2::take().unwrap();
+//! // Route a pad to GPT2_CLK...
+//! let (mut flow_meter, _, _) = GPT::with_clock(gpt2, ClockSource::External { hz: None }, 1);
+//!
+//! # async {
+//! // Wait for 450 pulses, about one liter on a common flow sensor
+//! flow_meter.delay(450).await;
+//! # };
+//! ```
+//!
+//! # Input capture
+//!
+//! Each GPT has two input capture channels. A [`Capture`] timestamps the edges on a
+//! capture input with the GPT counter. The hardware latches the counter, so the
+//! timestamps don't depend on interrupt or task latency.
+//!
+//! # Monotonic clock
+//!
+//! The 32-bit counter wraps around. For a clock that doesn't, use a [`Monotonic`]. It
+//! counts the counter's rollovers in the GPT interrupt, and returns 64-bit
+//! [`Instant`]s.
+
+mod capture;
+mod monotonic;
+
+pub use capture::{Capture, CaptureEdge, Captured, Edge, InputCapture, NextCapture};
+pub use monotonic::{Instant, Monotonic};
 
 use crate::ral;
 use core::{
@@ -54,6 +119,41 @@ use core::{
 pub struct GPT {
     gpt: ral::gpt::Instance,
     output_compare: OutputCompare,
+    /// Counter frequency, if known
+    clock_hz: Option<u32>,
+}
+
+#[cfg(feature = "diagnostics")]
+impl core::fmt::Debug for GPT {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let instance = match &*self.gpt as *const _ {
+            ral::gpt::GPT1 => 1,
+            ral::gpt::GPT2 => 2,
+            _ => unreachable!("There are only two GPTs"),
+        };
+        let compare = match self.output_compare {
+            OutputCompare::Channel1 => ral::read_reg!(ral::gpt, self.gpt, OCR1),
+            OutputCompare::Channel2 => ral::read_reg!(ral::gpt, self.gpt, OCR2),
+            OutputCompare::Channel3 => ral::read_reg!(ral::gpt, self.gpt, OCR3),
+        };
+        let mask = 1 << self.output_compare as u32;
+        let enabled = ral::read_reg!(ral::gpt, self.gpt, CR, EN);
+        f.debug_struct("GPT")
+            .field("instance", &instance)
+            .field("output_compare", &(self.output_compare as usize + 1))
+            .field("enabled", &(enabled != 0))
+            .field("counter", &ral::read_reg!(ral::gpt, self.gpt, CNT))
+            .field("compare", &compare)
+            .field(
+                "interrupt_enabled",
+                &(ral::read_reg!(ral::gpt, self.gpt, IR) & mask != 0),
+            )
+            .field(
+                "flag",
+                &(ral::read_reg!(ral::gpt, self.gpt, SR) & mask != 0),
+            )
+            .finish()
+    }
 }
 
 fn steal(gpt: &ral::gpt::Instance) -> ral::gpt::Instance {
@@ -70,12 +170,17 @@ fn steal(gpt: &ral::gpt::Instance) -> ral::gpt::Instance {
 
 impl GPT {
     /// Create a new `GPT` from a RAL GPT instance
+    ///
+    /// If the GPT counts the low-frequency reference clock, the timers know their
+    /// counter frequency. Otherwise, use [`with_clock`](GPT::with_clock()) or
+    /// [`set_clock_hz`](GPT::set_clock_hz()) to provide the frequency.
     pub fn new(gpt: ral::gpt::Instance) -> (Self, Self, Self) {
-        let irq = match &*gpt as *const _ {
-            ral::gpt::GPT1 => ral::interrupt::GPT1,
-            ral::gpt::GPT2 => ral::interrupt::GPT2,
+        let (inst, irq) = match &*gpt as *const _ {
+            ral::gpt::GPT1 => (1, ral::interrupt::GPT1),
+            ral::gpt::GPT2 => (2, ral::interrupt::GPT2),
             _ => unreachable!("There are only two GPTs"),
         };
+        crate::ccm::assert_gates_on(crate::ccm::Peripheral::GPT(inst));
 
         // Clear all statuses
         ral::write_reg!(ral::gpt, gpt, SR, 0b11_1111);
@@ -92,22 +197,153 @@ impl GPT {
         );
 
         unsafe { cortex_m::peripheral::NVIC::unmask(irq) };
+        let clock_hz = fixed_clock_hz(&gpt);
+        (
+            GPT {
+                gpt: steal(&gpt),
+                output_compare: OutputCompare::Channel1,
+                clock_hz,
+            },
+            GPT {
+                gpt: steal(&gpt),
+                output_compare: OutputCompare::Channel2,
+                clock_hz,
+            },
+            GPT {
+                gpt,
+                output_compare: OutputCompare::Channel3,
+                clock_hz,
+            },
+        )
+    }
+
+    /// Select the GPT clock, then create the three `GPT` timers
+    ///
+    /// The counter frequency is the `source` frequency divided by `divider`. The timers
+    /// remember the counter frequency; see [`clock_hz`](GPT::clock_hz()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `divider` isn't between 1 and 4096, or if a [`ClockSource::Crystal`]
+    /// divider isn't between 1 and 16.
+    ///
+    /// # Example
+    ///
+    /// Count at 1MHz, using the crystal oscillator.
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::{gpt::ClockSource, GPT};
+    ///
+    /// let gpt1 = hal::ral::gpt::GPT1::take().unwrap();
+    /// let (mut gpt, _, _) = GPT::with_clock(gpt1, ClockSource::Crystal { divider_24m: 8 }, 3);
+    /// assert_eq!(gpt.clock_hz(), Some(1_000_000));
+    /// ```
+    pub fn with_clock(
+        gpt: ral::gpt::Instance,
+        source: ClockSource,
+        divider: u32,
+    ) -> (Self, Self, Self) {
+        assert!(
+            (1..=4096).contains(&divider),
+            "GPT divider must be between 1 and 4096"
+        );
+        let source_hz = match source {
+            ClockSource::Peripheral { hz } => {
+                disable(&gpt);
+                ral::modify_reg!(ral::gpt, gpt, CR, CLKSRC: 0b001, EN_24M: 0);
+                ral::write_reg!(ral::gpt, gpt, PR, PRESCALER: 0);
+                Some(hz)
+            }
+            ClockSource::Crystal { divider_24m } => {
+                assert!(
+                    (1..=16).contains(&divider_24m),
+                    "GPT crystal divider must be between 1 and 16"
+                );
+                disable(&gpt);
+                ral::modify_reg!(ral::gpt, gpt, CR, CLKSRC: 0b101, EN_24M: 1);
+                ral::write_reg!(ral::gpt, gpt, PR, PRESCALER24M: divider_24m - 1);
+                Some(CRYSTAL_HZ / divider_24m)
+            }
+            ClockSource::LowFrequency => {
+                select_low_frequency_clock(&gpt);
+                Some(LOW_FREQUENCY_CLOCK_HZ)
+            }
+            ClockSource::External { hz } => {
+                select_external_clock(&gpt);
+                hz
+            }
+        };
+        ral::modify_reg!(ral::gpt, gpt, PR, PRESCALER: divider - 1);
+        let clock_hz = source_hz.map(|hz| hz / divider);
+        let (mut gpt1, mut gpt2, mut gpt3) = GPT::new(gpt);
+        gpt1.clock_hz = clock_hz;
+        gpt2.clock_hz = clock_hz;
+        gpt3.clock_hz = clock_hz;
+        (gpt1, gpt2, gpt3)
+    }
+
+    /// Returns the counter frequency, in Hz, if it's known
+    ///
+    /// The frequency is known if you created the timers with
+    /// [`with_clock`](GPT::with_clock()), or if you called
+    /// [`set_clock_hz`](GPT::set_clock_hz()).
+    pub fn clock_hz(&self) -> Option<u32> {
+        self.clock_hz
+    }
+
+    /// Record the counter frequency, if you configured the GPT clock yourself
+    ///
+    /// This doesn't change the GPT clock.
+    pub fn set_clock_hz(&mut self, clock_hz: u32) {
+        self.clock_hz = Some(clock_hz);
+    }
+
+    /// Create three `GPT` timers that use the current GPT configuration
+    ///
+    /// Unlike [`new`](GPT::new()), `steal` does not modify the GPT, or its interrupt. Like
+    /// `new`, the timers only know their counter frequency if the GPT counts the
+    /// low-frequency reference clock.
+    ///
+    /// # Safety
+    ///
+    /// The original driver may still exist, and it may be in the middle of an operation.
+    /// Only use `steal` when the original owner can no longer run, like in a panic or
+    /// fault handler.
+    pub unsafe fn steal(gpt: ral::gpt::Instance) -> (Self, Self, Self) {
+        let clock_hz = fixed_clock_hz(&gpt);
         (
             GPT {
                 gpt: steal(&gpt),
                 output_compare: OutputCompare::Channel1,
+                clock_hz,
             },
             GPT {
                 gpt: steal(&gpt),
                 output_compare: OutputCompare::Channel2,
+                clock_hz,
             },
             GPT {
                 gpt,
                 output_compare: OutputCompare::Channel3,
+                clock_hz,
             },
         )
     }
 
+    /// Returns the GPT counter
+    ///
+    /// All three timers from the same GPT share the counter. Use the difference of two
+    /// counts to time events; the counter wraps around.
+    pub fn counter(&self) -> u32 {
+        ral::read_reg!(ral::gpt, self.gpt, CNT)
+    }
+
+    /// Returns `true` if the timer counts the edges on its `GPT_CLK` input
+    pub(crate) fn counts_external_clock(&self) -> bool {
+        ral::read_reg!(ral::gpt, self.gpt, CR, CLKSRC == 0b011)
+    }
+
     /// Wait for `ticks` clock counts to elapse
     ///
     /// The elapsed time depends on your clock configuration.
@@ -119,15 +355,496 @@ impl GPT {
             _pin: PhantomPinned,
         }
     }
+
+    /// Wait for `duration` to elapse
+    ///
+    /// The duration rounds up to the next clock count, so the delay never completes
+    /// early. Durations longer than `u32::MAX` clock counts saturate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter frequency is unknown. See [`clock_hz`](GPT::clock_hz()).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use core::time::Duration;
+    /// use hal::{gpt::ClockSource, GPT};
+    ///
+    /// let gpt1 = hal::ral::gpt::GPT1::take().unwrap();
+    /// let (mut gpt, _, _) = GPT::with_clock(gpt1, ClockSource::Crystal { divider_24m: 8 }, 3);
+    /// # async {
+    /// gpt.delay_duration(Duration::from_millis(250)).await;
+    /// gpt.delay_ms(250).await;
+    /// # };
+    /// ```
+    pub fn delay_duration(&mut self, duration: core::time::Duration) -> Delay<'_> {
+        let clock_hz = self
+            .clock_hz
+            .expect("GPT counter frequency is unknown; see GPT::with_clock");
+        let ticks = (duration.as_nanos() * u128::from(clock_hz) + 999_999_999) / 1_000_000_000;
+        self.delay(if ticks > u128::from(u32::MAX) {
+            u32::MAX
+        } else {
+            ticks as u32
+        })
+    }
+
+    /// Wait for `ms` milliseconds to elapse
+    ///
+    /// See [`delay_duration`](GPT::delay_duration()) for more information.
+    pub fn delay_ms(&mut self, ms: u32) -> Delay<'_> {
+        self.delay_duration(core::time::Duration::from_millis(ms.into()))
+    }
+
+    /// Wait for `us` microseconds to elapse
+    ///
+    /// See [`delay_duration`](GPT::delay_duration()) for more information.
+    pub fn delay_us(&mut self, us: u32) -> Delay<'_> {
+        self.delay_duration(core::time::Duration::from_micros(us.into()))
+    }
+
+    /// Create a timer that elapses every `period` clock counts
+    ///
+    /// Unlike a loop of [`delay`](GPT::delay())s, the periods do not depend on
+    /// when the task polls the timer. Each period starts when the previous period
+    /// was scheduled to end, so the timer does not drift. The first period starts now.
+    ///
+    /// If a task misses one or more periods, each missed [`tick`](Periodic::tick())
+    /// completes immediately, until the timer catches up.
+    ///
+    /// # Example
+    ///
+    /// Run a control loop at 1KHz, using a 1MHz GPT.
+    ///
+    /// ```no_run
+    /// # async fn f(mut gpt: imxrt_async_hal::GPT) {
+    /// let mut periodic = gpt.periodic(1_000);
+    /// loop {
+    ///     periodic.tick().await;
+    ///     // Sample inputs, update outputs...
+    /// }
+    /// # }
+    /// ```
+    pub fn periodic(&mut self, period: u32) -> Periodic<'_> {
+        // A compare flag left by an earlier delay or compare would end the first period
+        // immediately.
+        disable_interrupt(&self.gpt, self.output_compare);
+        clear_trigger(&self.gpt, self.output_compare);
+        Periodic {
+            gpt: &self.gpt,
+            output_compare: self.output_compare,
+            period,
+            last: ral::read_reg!(ral::gpt, self.gpt, CNT),
+        }
+    }
+
+    /// Select what the timer's compare output pin does when the counter reaches the
+    /// compare value
+    ///
+    /// [`delay`](GPT::delay()), [`periodic`](GPT::periodic()), and
+    /// [`compare`](GPT::compare()) all move the compare value, so they all drive the pin.
+    /// You must route the pad to the timer's `GPT_COMPARE` signal. See your processor's
+    /// reference manual for the pad's mux setting.
+    ///
+    /// # Example
+    ///
+    /// Generate a 500Hz square wave, using a 1MHz GPT. The hardware toggles the pin, so
+    /// the edges don't depend on when the task runs.
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::gpt::CompareOutput;
+    ///
+    /// # async fn f(mut gpt: hal::GPT) {
+    /// // Route a pad to GPT_COMPARE...
+    /// gpt.set_compare_output(CompareOutput::Toggle);
+    /// let mut edge = gpt.counter();
+    /// loop {
+    ///     edge = edge.wrapping_add(1_000);
+    ///     gpt.compare(edge).await;
+    /// }
+    /// # }
+    /// ```
+    pub fn set_compare_output(&mut self, output: CompareOutput) {
+        let mode = output as u32;
+        cortex_m::interrupt::free(|_| match self.output_compare {
+            OutputCompare::Channel1 => ral::modify_reg!(ral::gpt, self.gpt, CR, OM1: mode),
+            OutputCompare::Channel2 => ral::modify_reg!(ral::gpt, self.gpt, CR, OM2: mode),
+            OutputCompare::Channel3 => ral::modify_reg!(ral::gpt, self.gpt, CR, OM3: mode),
+        });
+    }
+
+    /// Act on the compare output pin now, as if the counter reached the compare value
+    ///
+    /// The pin responds according to the [`CompareOutput`] setting. Forcing the output
+    /// doesn't set the compare flag, so it doesn't complete a wait.
+    pub fn force_compare_output(&mut self) {
+        cortex_m::interrupt::free(|_| match self.output_compare {
+            OutputCompare::Channel1 => ral::modify_reg!(ral::gpt, self.gpt, CR, FO1: 1),
+            OutputCompare::Channel2 => ral::modify_reg!(ral::gpt, self.gpt, CR, FO2: 1),
+            OutputCompare::Channel3 => ral::modify_reg!(ral::gpt, self.gpt, CR, FO3: 1),
+        });
+    }
+
+    /// Set the compare value to the counter value `at`, and wait for the counter to
+    /// reach it
+    ///
+    /// Unlike [`delay`](GPT::delay()), `at` is an absolute counter value, so successive
+    /// compares don't accumulate the time it takes to poll the future. If the counter
+    /// already passed `at`, the compare happens after the counter wraps around.
+    pub fn compare(&mut self, at: u32) -> Compare<'_> {
+        // A compare flag left by an earlier delay, compare, or periodic tick would
+        // complete the compare immediately.
+        disable_interrupt(&self.gpt, self.output_compare);
+        clear_trigger(&self.gpt, self.output_compare);
+        Compare {
+            gpt: &self.gpt,
+            output_compare: self.output_compare,
+            at,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+/// A `GPT` timer's alarm
+///
+/// The alarm shares the timer's output compare with [`delay`](GPT::delay()) and
+/// [`periodic`](GPT::periodic()). Clear the alarm before you use the timer for
+/// anything else.
+impl crate::time::TimeDriver for GPT {
+    fn now(&self) -> u32 {
+        self.counter()
+    }
+
+    fn set_alarm(&mut self, at: u32, alarm_waker: &Waker) -> bool {
+        disable_interrupt(&self.gpt, self.output_compare);
+        clear_trigger(&self.gpt, self.output_compare);
+        if !crate::time::is_future(at, self.counter()) {
+            return false;
+        }
+        *waker(&self.gpt, self.output_compare) = Some(alarm_waker.clone());
+        set_ticks(&self.gpt, self.output_compare, at);
+        atomic::compiler_fence(atomic::Ordering::Release);
+        enable_interrupt(&self.gpt, self.output_compare);
+        // The counter may have reached the alarm before the interrupt was enabled; if
+        // so, there's no interrupt coming.
+        if crate::time::is_future(at, self.counter())
+            || is_triggered(&self.gpt, self.output_compare)
+        {
+            true
+        } else {
+            crate::time::TimeDriver::clear_alarm(self);
+            false
+        }
+    }
+
+    fn clear_alarm(&mut self) {
+        disable_interrupt(&self.gpt, self.output_compare);
+        clear_trigger(&self.gpt, self.output_compare);
+        *waker(&self.gpt, self.output_compare) = None;
+    }
+}
+
+/// A timer that elapses at a fixed period
+///
+/// Use [`periodic`](GPT::periodic()) to create a `Periodic` timer.
+///
+/// The GPT's three timers share one counter, so `Periodic` keeps the counter
+/// free-running. It schedules each period by advancing the output compare value,
+/// rather than resetting the counter with the GPT's restart mode.
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub struct Periodic<'a> {
+    gpt: &'a ral::gpt::Instance,
+    output_compare: OutputCompare,
+    period: u32,
+    /// The counter value at the end of the last period
+    last: u32,
+}
+
+impl<'a> Periodic<'a> {
+    /// Wait for the end of the next period
+    ///
+    /// `Periodic` is also a [`Stream`](futures::stream::Stream) that yields at the end
+    /// of each period.
+    pub fn tick(&mut self) -> Tick<'_, 'a> {
+        Tick {
+            periodic: self,
+            _pin: PhantomPinned,
+        }
+    }
+
+    fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let (gpt, output_compare) = (self.gpt, self.output_compare);
+        if is_triggered(gpt, output_compare) {
+            clear_trigger(gpt, output_compare);
+        } else if interrupt_enabled(gpt, output_compare) {
+            // The counter may have passed the compare value before the interrupt was
+            // enabled; if so, there's no interrupt coming.
+            if !self.is_elapsed() {
+                // A stream may be polled by a different task than the one that
+                // registered the waker.
+                cortex_m::interrupt::free(|_| {
+                    *waker(gpt, output_compare) = Some(cx.waker().clone());
+                });
+                return Poll::Pending;
+            }
+            disable_interrupt(gpt, output_compare);
+            clear_trigger(gpt, output_compare);
+        } else if !self.is_elapsed() {
+            *waker(gpt, output_compare) = Some(cx.waker().clone());
+            let next_tick = self.last.wrapping_add(self.period);
+            set_ticks(gpt, output_compare, next_tick);
+            // The flag may be stale, from the previous compare value. If the new compare
+            // value already matched, is_elapsed() catches it below.
+            clear_trigger(gpt, output_compare);
+            atomic::compiler_fence(atomic::Ordering::Release);
+            enable_interrupt(gpt, output_compare);
+            if !self.is_elapsed() {
+                return Poll::Pending;
+            }
+            disable_interrupt(gpt, output_compare);
+            clear_trigger(gpt, output_compare);
+        }
+        self.last = self.last.wrapping_add(self.period);
+        Poll::Ready(())
+    }
+
+    /// Returns `true` if the next period has already ended
+    fn is_elapsed(&self) -> bool {
+        let current_tick = ral::read_reg!(ral::gpt, self.gpt, CNT);
+        current_tick.wrapping_sub(self.last) >= self.period
+    }
+}
+
+impl futures::stream::Stream for Periodic<'_> {
+    type Item = ();
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.get_mut().poll_tick(cx).map(Some)
+    }
+}
+
+impl Drop for Periodic<'_> {
+    fn drop(&mut self) {
+        disable_interrupt(self.gpt, self.output_compare);
+        clear_trigger(self.gpt, self.output_compare);
+    }
+}
+
+/// A future that waits for the end of a [`Periodic`] timer's period
+pub struct Tick<'p, 'a> {
+    periodic: &'p mut Periodic<'a>,
+    _pin: PhantomPinned,
+}
+
+impl Future for Tick<'_, '_> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: no fields are moved out of the pinned future.
+        let periodic = unsafe { &mut *self.get_unchecked_mut().periodic };
+        periodic.poll_tick(cx)
+    }
+}
+
+impl Drop for Tick<'_, '_> {
+    fn drop(&mut self) {
+        disable_interrupt(self.periodic.gpt, self.periodic.output_compare);
+        clear_trigger(self.periodic.gpt, self.periodic.output_compare);
+    }
+}
+
+/// What a compare output pin does when the counter reaches the compare value
+///
+/// See [`set_compare_output`](GPT::set_compare_output()) for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+#[repr(u32)]
+pub enum CompareOutput {
+    /// The timer doesn't drive the pin
+    Disconnected = 0b000,
+    /// Toggle the pin
+    Toggle = 0b001,
+    /// Drive the pin low
+    Clear = 0b010,
+    /// Drive the pin high
+    Set = 0b011,
+    /// Drive the pin low for one input clock cycle
+    Pulse = 0b100,
+}
+
+/// A future that waits for the counter to reach a compare value
+///
+/// Use [`compare`](GPT::compare()) to create the future.
+pub struct Compare<'a> {
+    gpt: &'a ral::gpt::Instance,
+    output_compare: OutputCompare,
+    at: u32,
+    _pin: PhantomPinned,
+}
+
+impl<'a> Future for Compare<'a> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if is_triggered(&self.gpt, self.output_compare) {
+            clear_trigger(&self.gpt, self.output_compare);
+            Poll::Ready(())
+        } else if interrupt_enabled(&self.gpt, self.output_compare) {
+            Poll::Pending
+        } else {
+            *waker(&self.gpt, self.output_compare) = Some(cx.waker().clone());
+            clear_trigger(&self.gpt, self.output_compare);
+            set_ticks(&self.gpt, self.output_compare, self.at);
+            atomic::compiler_fence(atomic::Ordering::Release);
+            enable_interrupt(&self.gpt, self.output_compare);
+            Poll::Pending
+        }
+    }
+}
+
+impl<'a> Drop for Compare<'a> {
+    fn drop(&mut self) {
+        disable_interrupt(&self.gpt, self.output_compare);
+        clear_trigger(&self.gpt, self.output_compare);
+    }
+}
+
+/// A GPT clock source
+///
+/// See [`GPT::with_clock`] for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub enum ClockSource {
+    /// The peripheral clock, running at `hz`
+    ///
+    /// The CCM sets the peripheral clock frequency.
+    Peripheral {
+        /// Peripheral clock frequency
+        hz: u32,
+    },
+    /// The 24MHz crystal oscillator, divided by `divider_24m`
+    ///
+    /// Per the reference manual, the oscillator reaches the GPT without the CCM's
+    /// peripheral clock divider, so the counter frequency is 24MHz divided by
+    /// `divider_24m`, then divided by the `with_clock` divider.
+    Crystal {
+        /// Crystal oscillator divider, 1 to 16
+        divider_24m: u32,
+    },
+    /// The 32.768KHz low-frequency reference clock
+    ///
+    /// See [`select_low_frequency_clock`] for more information.
+    LowFrequency,
+    /// The `GPT_CLK` input, running at `hz`
+    ///
+    /// Use `None` when you're counting edges, rather than time. See
+    /// [`select_external_clock`] for more information.
+    External {
+        /// Input frequency, if known
+        hz: Option<u32>,
+    },
+}
+
+/// Frequency of the crystal oscillator, in Hz
+const CRYSTAL_HZ: u32 = 24_000_000;
+
+/// Stop the timer, and disable its output compare interrupts, before changing its clock
+fn disable(gpt: &ral::gpt::Instance) {
+    ral::modify_reg!(ral::gpt, gpt, CR, EN: 0);
+    ral::modify_reg!(ral::gpt, gpt, IR, OF1IE: 0, OF2IE: 0, OF3IE: 0);
+}
+
+/// Frequency of the low-frequency reference clock, in Hz
+pub const LOW_FREQUENCY_CLOCK_HZ: u32 = 32_768;
+
+/// Returns the counter frequency, if the GPT counts a clock with a fixed frequency
+///
+/// Only the low-frequency reference clock has a fixed frequency. The other clock
+/// frequencies depend on the CCM, or on the board.
+fn fixed_clock_hz(gpt: &ral::gpt::Instance) -> Option<u32> {
+    if ral::read_reg!(ral::gpt, gpt, CR, CLKSRC == 0b100) {
+        let divider = ral::read_reg!(ral::gpt, gpt, PR, PRESCALER) + 1;
+        Some(LOW_FREQUENCY_CLOCK_HZ / divider)
+    } else {
+        None
+    }
+}
+
+/// Select the 32.768KHz low-frequency reference clock as the GPT clock source
+///
+/// The prescaler is set to divide by 1, and the timer is configured to keep
+/// counting in DOZE and STOP modes. `select_low_frequency_clock` disables the timer, so call it
+/// before [`GPT::new`], which enables the timer.
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub fn select_low_frequency_clock(gpt: &ral::gpt::Instance) {
+    disable(gpt);
+    ral::modify_reg!(
+        ral::gpt, gpt, CR,
+        CLKSRC: 0b100, // Low frequency reference clock
+        EN_24M: 0, // Crystal oscillator not needed
+        DOZEEN: 1, // Run in doze mode
+        STOPEN: 1 // Run in stop mode
+    );
+    ral::write_reg!(ral::gpt, gpt, PR, PRESCALER: 0);
+}
+
+/// Select the `GPT_CLK` input as the GPT clock source, so that the timer counts edges
+///
+/// The timer counts each rising edge on the input. The prescaler is set to divide by 1.
+/// The peripheral clock samples the input, so the input frequency must be less than
+/// a quarter of the peripheral clock frequency. `select_external_clock` disables the
+/// timer, so call it before [`GPT::new`], which enables the timer.
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub fn select_external_clock(gpt: &ral::gpt::Instance) {
+    disable(gpt);
+    ral::modify_reg!(
+        ral::gpt, gpt, CR,
+        CLKSRC: 0b011, // External clock
+        EN_24M: 0 // Crystal oscillator not needed
+    );
+    ral::write_reg!(ral::gpt, gpt, PR, PRESCALER: 0);
+}
+
+/// Convert `micros` microseconds into GPT ticks, given the timer's `clock_hz`
+///
+/// The result rounds up, so a delay never completes early. It saturates at
+/// `u32::MAX` ticks.
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub const fn ticks_from_micros(micros: u32, clock_hz: u32) -> u32 {
+    let ticks = (micros as u64 * clock_hz as u64 + 999_999) / 1_000_000;
+    if ticks > u32::MAX as u64 {
+        u32::MAX
+    } else {
+        ticks as u32
+    }
+}
+
+/// Convert `ticks` GPT ticks into microseconds, given the timer's `clock_hz`
+///
+/// The result rounds down. It saturates at `u32::MAX` microseconds.
+///
+/// # Panics
+///
+/// Panics if `clock_hz` is zero.
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub const fn micros_from_ticks(ticks: u32, clock_hz: u32) -> u32 {
+    let micros = ticks as u64 * 1_000_000 / clock_hz as u64;
+    if micros > u32::MAX as u64 {
+        u32::MAX
+    } else {
+        micros as u32
+    }
 }
 
 /// Clear the output compare flag
+///
+/// The status flags are write-1-to-clear, so this writes only the one flag.
 #[inline(always)]
 fn clear_trigger(gpt: &ral::gpt::Instance, output_compare: OutputCompare) {
     match output_compare {
-        OutputCompare::Channel1 => ral::modify_reg!(ral::gpt, gpt, SR, OF1: 1),
-        OutputCompare::Channel2 => ral::modify_reg!(ral::gpt, gpt, SR, OF2: 1),
-        OutputCompare::Channel3 => ral::modify_reg!(ral::gpt, gpt, SR, OF3: 1),
+        OutputCompare::Channel1 => ral::write_reg!(ral::gpt, gpt, SR, OF1: 1),
+        OutputCompare::Channel2 => ral::write_reg!(ral::gpt, gpt, SR, OF2: 1),
+        OutputCompare::Channel3 => ral::write_reg!(ral::gpt, gpt, SR, OF3: 1),
     }
 }
 #[inline(always)]
@@ -189,6 +906,13 @@ pub struct Delay<'a> {
     ticks: u32,
 }
 
+impl Delay<'_> {
+    /// Returns the current counter value of the delay's GPT
+    pub(crate) fn counter(&self) -> u32 {
+        ral::read_reg!(ral::gpt, self.gpt, CNT)
+    }
+}
+
 impl<'a> Future for Delay<'a> {
     type Output = ();
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
@@ -234,6 +958,8 @@ fn on_interrupt(gpt: &ral::gpt::Instance) {
             waker.wake();
         }
     });
+    capture::on_interrupt(gpt);
+    monotonic::on_rollover(gpt);
 }
 
 interrupts! {
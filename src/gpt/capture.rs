@@ -0,0 +1,363 @@
+//! GPT input capture
+
+use super::GPT;
+use crate::ral;
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic,
+    task::{Context, Poll},
+};
+use futures::task::AtomicWaker;
+
+/// Captures queued for each channel
+const QUEUE_LEN: usize = 16;
+
+/// Channel state is indexed by GPT, then by capture channel
+const CHANNELS: usize = 4;
+
+/// For each GPT, a bitmap of the capture channels that are in use
+static TAKEN: [atomic::AtomicU8; 2] = [atomic::AtomicU8::new(0), atomic::AtomicU8::new(0)];
+
+/// The edges that each channel captures; see `MODE_*`
+static MODES: [atomic::AtomicU8; CHANNELS] = [
+    atomic::AtomicU8::new(MODE_NONE),
+    atomic::AtomicU8::new(MODE_NONE),
+    atomic::AtomicU8::new(MODE_NONE),
+    atomic::AtomicU8::new(MODE_NONE),
+];
+const MODE_NONE: u8 = 0;
+const MODE_RISING: u8 = 1;
+const MODE_FALLING: u8 = 2;
+const MODE_BOTH: u8 = 3;
+
+/// Captures, written by the interrupt handler at `HEADS`, and read by the task at `TAILS`
+static mut QUEUES: [[Captured; QUEUE_LEN]; CHANNELS] = [[Captured {
+    edge: Edge::Rising,
+    ticks: 0,
+}; QUEUE_LEN]; CHANNELS];
+static HEADS: [atomic::AtomicUsize; CHANNELS] = [
+    atomic::AtomicUsize::new(0),
+    atomic::AtomicUsize::new(0),
+    atomic::AtomicUsize::new(0),
+    atomic::AtomicUsize::new(0),
+];
+static TAILS: [atomic::AtomicUsize; CHANNELS] = [
+    atomic::AtomicUsize::new(0),
+    atomic::AtomicUsize::new(0),
+    atomic::AtomicUsize::new(0),
+    atomic::AtomicUsize::new(0),
+];
+/// Set when the interrupt handler drops a capture, because the queue is full
+static OVERRUNS: [atomic::AtomicBool; CHANNELS] = [
+    atomic::AtomicBool::new(false),
+    atomic::AtomicBool::new(false),
+    atomic::AtomicBool::new(false),
+    atomic::AtomicBool::new(false),
+];
+static WAKERS: [AtomicWaker; CHANNELS] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NONE: AtomicWaker = AtomicWaker::new();
+    [NONE, NONE, NONE, NONE]
+};
+
+/// Returns the GPT's index
+fn instance(gpt: &ral::gpt::Instance) -> usize {
+    match &**gpt as *const _ {
+        ral::gpt::GPT1 => 0,
+        ral::gpt::GPT2 => 1,
+        _ => unreachable!("There are only two GPTs"),
+    }
+}
+
+/// A GPT input capture channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub enum InputCapture {
+    /// The `GPT_CAPTURE1` input
+    Channel1 = 0,
+    /// The `GPT_CAPTURE2` input
+    Channel2 = 1,
+}
+
+/// The input edges that a [`Capture`] timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub enum CaptureEdge {
+    /// Rising edges
+    Rising,
+    /// Falling edges
+    Falling,
+    /// Rising and falling edges, starting with the next `first` edge
+    Both {
+        /// The first edge to capture
+        first: Edge,
+    },
+}
+
+/// An input edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub enum Edge {
+    /// The input went high
+    Rising,
+    /// The input went low
+    Falling,
+}
+
+/// A timestamped input edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub struct Captured {
+    /// The edge
+    pub edge: Edge,
+    /// The GPT counter value when the edge occurred
+    pub ticks: u32,
+}
+
+/// Timestamps input edges with the GPT counter
+///
+/// The GPT latches its counter into a capture register on each selected input edge,
+/// so the timestamps don't include any interrupt or task latency. The interrupt
+/// handler queues the timestamps, and [`next`](Capture::next()) returns them in order.
+/// The queue holds 16 captures. If the task falls behind, the interrupt handler drops
+/// new captures, and [`overrun`](Capture::overrun()) returns `true`.
+///
+/// To capture [`Both`](CaptureEdge::Both) edges, the interrupt handler switches between
+/// rising and falling edges after each capture, so that it knows each capture's edge.
+/// If the input changes twice within the interrupt latency, the second edge is missed,
+/// and the next capture is the next edge of the expected kind.
+///
+/// You must route the input pad to the timer's `GPT_CAPTUREn` signal. See your
+/// processor's reference manual for the pad's mux and daisy settings. The timestamps
+/// come from the same counter as the GPT's three timers.
+///
+/// # Example
+///
+/// Measure the width of the next high pulse.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::{gpt::{Capture, CaptureEdge, Edge, InputCapture}, GPT};
+///
+/// let (gpt, _, _) = GPT::new(hal::ral::gpt::GPT2::take().unwrap());
+/// // Route a pad to GPT2_CAPTURE1...
+/// let mut capture = Capture::new(&gpt, InputCapture::Channel1).unwrap();
+///
+/// # async {
+/// capture.start(CaptureEdge::Both { first: Edge::Rising });
+/// let rise = capture.next().await;
+/// let fall = capture.next().await;
+/// let width = fall.ticks.wrapping_sub(rise.ticks);
+/// # };
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub struct Capture {
+    gpt: ral::gpt::Instance,
+    channel: InputCapture,
+    clock_hz: Option<u32>,
+}
+
+impl Capture {
+    /// Acquire the `channel` input capture of `gpt`'s GPT
+    ///
+    /// Returns `None` if the channel is already in use. The channel is available again
+    /// once you drop the `Capture`. Captures don't start until you call
+    /// [`start`](Capture::start()).
+    pub fn new(gpt: &GPT, channel: InputCapture) -> Option<Self> {
+        let mask = 1 << channel as u8;
+        let taken = TAKEN[instance(&gpt.gpt)].fetch_or(mask, atomic::Ordering::Acquire);
+        if taken & mask != 0 {
+            return None;
+        }
+        Some(Capture {
+            gpt: super::steal(&gpt.gpt),
+            channel,
+            clock_hz: gpt.clock_hz(),
+        })
+    }
+
+    /// Returns the GPT counter frequency, in Hz, if it's known
+    ///
+    /// The capture uses the frequency of the `GPT` that created it. See
+    /// [`GPT::clock_hz`](crate::GPT::clock_hz()).
+    pub fn clock_hz(&self) -> Option<u32> {
+        self.clock_hz
+    }
+
+    fn index(&self) -> usize {
+        instance(&self.gpt) * 2 + self.channel as usize
+    }
+
+    /// Start timestamping `edge`s
+    ///
+    /// `start` discards any queued captures, and clears the overrun flag.
+    pub fn start(&mut self, edge: CaptureEdge) {
+        let index = self.index();
+        let (mode, first) = match edge {
+            CaptureEdge::Rising => (MODE_RISING, MODE_RISING),
+            CaptureEdge::Falling => (MODE_FALLING, MODE_FALLING),
+            CaptureEdge::Both {
+                first: Edge::Rising,
+            } => (MODE_BOTH, MODE_RISING),
+            CaptureEdge::Both {
+                first: Edge::Falling,
+            } => (MODE_BOTH, MODE_FALLING),
+        };
+        cortex_m::interrupt::free(|_| {
+            self.disable();
+            TAILS[index].store(
+                HEADS[index].load(atomic::Ordering::Relaxed),
+                atomic::Ordering::Relaxed,
+            );
+            OVERRUNS[index].store(false, atomic::Ordering::Relaxed);
+            MODES[index].store(mode, atomic::Ordering::Relaxed);
+            let first = u32::from(first);
+            match self.channel {
+                InputCapture::Channel1 => {
+                    ral::write_reg!(ral::gpt, self.gpt, SR, IF1: 1);
+                    ral::modify_reg!(ral::gpt, self.gpt, CR, IM1: first);
+                    ral::modify_reg!(ral::gpt, self.gpt, IR, IF1IE: 1);
+                }
+                InputCapture::Channel2 => {
+                    ral::write_reg!(ral::gpt, self.gpt, SR, IF2: 1);
+                    ral::modify_reg!(ral::gpt, self.gpt, CR, IM2: first);
+                    ral::modify_reg!(ral::gpt, self.gpt, IR, IF2IE: 1);
+                }
+            }
+        });
+    }
+
+    /// Stop timestamping edges
+    ///
+    /// Captures that are already queued remain available.
+    pub fn stop(&mut self) {
+        cortex_m::interrupt::free(|_| self.disable());
+    }
+
+    fn disable(&mut self) {
+        MODES[self.index()].store(MODE_NONE, atomic::Ordering::Relaxed);
+        match self.channel {
+            InputCapture::Channel1 => {
+                ral::modify_reg!(ral::gpt, self.gpt, IR, IF1IE: 0);
+                ral::modify_reg!(ral::gpt, self.gpt, CR, IM1: 0);
+            }
+            InputCapture::Channel2 => {
+                ral::modify_reg!(ral::gpt, self.gpt, IR, IF2IE: 0);
+                ral::modify_reg!(ral::gpt, self.gpt, CR, IM2: 0);
+            }
+        }
+    }
+
+    /// Returns `true` if the queue overflowed since the last call, or since
+    /// [`start`](Capture::start())
+    ///
+    /// When the queue overflows, the interrupt handler drops new captures.
+    pub fn overrun(&mut self) -> bool {
+        OVERRUNS[self.index()].swap(false, atomic::Ordering::Relaxed)
+    }
+
+    /// Wait for the next capture
+    pub fn next(&mut self) -> NextCapture<'_> {
+        NextCapture { capture: self }
+    }
+
+    /// Poll for the next capture
+    ///
+    /// If there's no capture in the queue, `poll_capture` wakes the task in `cx` once
+    /// the next capture arrives. Use `poll_capture` to build your own futures and
+    /// streams; otherwise, prefer [`next`](Capture::next()).
+    pub fn poll_capture(&mut self, cx: &mut Context<'_>) -> Poll<Captured> {
+        if let Some(captured) = self.try_next() {
+            return Poll::Ready(captured);
+        }
+        WAKERS[self.index()].register(cx.waker());
+        // The interrupt handler may have queued a capture before we registered the waker
+        match self.try_next() {
+            Some(captured) => Poll::Ready(captured),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Returns the next capture, if there's one in the queue
+    pub fn try_next(&mut self) -> Option<Captured> {
+        let index = self.index();
+        let tail = TAILS[index].load(atomic::Ordering::Relaxed);
+        if tail == HEADS[index].load(atomic::Ordering::Acquire) {
+            return None;
+        }
+        // Safety: the interrupt handler doesn't write this slot until we advance the tail
+        let captured = unsafe { QUEUES[index][tail % QUEUE_LEN] };
+        TAILS[index].store(tail.wrapping_add(1), atomic::Ordering::Release);
+        Some(captured)
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        self.stop();
+        WAKERS[self.index()].take();
+        TAKEN[instance(&self.gpt)].fetch_and(!(1 << self.channel as u8), atomic::Ordering::Release);
+    }
+}
+
+/// A future that waits for the next [`Capture`]
+///
+/// Use [`next`](Capture::next()) to create the future.
+pub struct NextCapture<'a> {
+    capture: &'a mut Capture,
+}
+
+impl Future for NextCapture<'_> {
+    type Output = Captured;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Captured> {
+        self.get_mut().capture.poll_capture(cx)
+    }
+}
+
+/// Queue the capture for each channel that captured an edge
+///
+/// Called from the GPT interrupt handler.
+#[inline(always)]
+pub(super) fn on_interrupt(gpt: &ral::gpt::Instance) {
+    let base = instance(gpt) * 2;
+    if ral::read_reg!(ral::gpt, gpt, IR, IF1IE == 1) && ral::read_reg!(ral::gpt, gpt, SR, IF1 == 1)
+    {
+        let ticks = ral::read_reg!(ral::gpt, gpt, ICR1);
+        ral::write_reg!(ral::gpt, gpt, SR, IF1: 1);
+        let mode = ral::read_reg!(ral::gpt, gpt, CR, IM1);
+        if MODES[base].load(atomic::Ordering::Relaxed) == MODE_BOTH {
+            ral::modify_reg!(ral::gpt, gpt, CR, IM1: mode ^ 0b11);
+        }
+        push(base, mode, ticks);
+    }
+    if ral::read_reg!(ral::gpt, gpt, IR, IF2IE == 1) && ral::read_reg!(ral::gpt, gpt, SR, IF2 == 1)
+    {
+        let ticks = ral::read_reg!(ral::gpt, gpt, ICR2);
+        ral::write_reg!(ral::gpt, gpt, SR, IF2: 1);
+        let mode = ral::read_reg!(ral::gpt, gpt, CR, IM2);
+        if MODES[base + 1].load(atomic::Ordering::Relaxed) == MODE_BOTH {
+            ral::modify_reg!(ral::gpt, gpt, CR, IM2: mode ^ 0b11);
+        }
+        push(base + 1, mode, ticks);
+    }
+}
+
+/// Queue a capture, taken while the channel was in capture `mode`
+#[inline(always)]
+fn push(index: usize, mode: u32, ticks: u32) {
+    let edge = if mode == u32::from(MODE_FALLING) {
+        Edge::Falling
+    } else {
+        Edge::Rising
+    };
+    let head = HEADS[index].load(atomic::Ordering::Relaxed);
+    if head.wrapping_sub(TAILS[index].load(atomic::Ordering::Acquire)) >= QUEUE_LEN {
+        OVERRUNS[index].store(true, atomic::Ordering::Relaxed);
+    } else {
+        // Safety: the task doesn't read this slot until we advance the head
+        unsafe { QUEUES[index][head % QUEUE_LEN] = Captured { edge, ticks } };
+        HEADS[index].store(head.wrapping_add(1), atomic::Ordering::Release);
+    }
+    WAKERS[index].wake();
+}
@@ -0,0 +1,224 @@
+//! A 64-bit clock that extends the GPT counter
+
+use super::{steal, GPT};
+use crate::ral;
+use core::{ops, sync::atomic, time::Duration};
+
+/// Counter rollovers, indexed by GPT
+static ROLLOVERS: [atomic::AtomicU32; 2] = [atomic::AtomicU32::new(0), atomic::AtomicU32::new(0)];
+
+#[inline(always)]
+fn rollovers(gpt: &ral::gpt::Instance) -> &'static atomic::AtomicU32 {
+    match &**gpt as *const _ {
+        ral::gpt::GPT1 => &ROLLOVERS[0],
+        ral::gpt::GPT2 => &ROLLOVERS[1],
+        _ => unreachable!("There are only two GPTs"),
+    }
+}
+
+/// Count a rollover, if the rollover interrupt is enabled
+///
+/// Called from the GPT interrupt handler.
+#[inline(always)]
+pub(super) fn on_rollover(gpt: &ral::gpt::Instance) {
+    if ral::read_reg!(ral::gpt, gpt, IR, ROVIE == 1) && ral::read_reg!(ral::gpt, gpt, SR, ROV == 1)
+    {
+        ral::write_reg!(ral::gpt, gpt, SR, ROV: 1);
+        rollovers(gpt).fetch_add(1, atomic::Ordering::Release);
+    }
+}
+
+/// A 64-bit monotonic clock
+///
+/// `Monotonic` extends a GPT's 32-bit counter with a count of counter rollovers, which
+/// the GPT interrupt handler maintains. The clock never wraps around in practice, so
+/// you can compare and subtract [`Instant`]s without worrying about overflow.
+///
+/// The clock counts at the GPT's counter frequency. To convert ticks to and from
+/// [`Duration`]s, the counter frequency must be known; see [`GPT::clock_hz`].
+///
+/// A `Monotonic` doesn't use an output compare, so all three timers from the GPT
+/// remain available. Once you create a `Monotonic`, the rollover interrupt stays
+/// enabled.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::{gpt::{ClockSource, Monotonic}, GPT};
+///
+/// let gpt1 = hal::ral::gpt::GPT1::take().unwrap();
+/// let (mut gpt, _, _) = GPT::with_clock(gpt1, ClockSource::Crystal { divider_24m: 8 }, 3);
+/// let clock = Monotonic::new(&gpt);
+///
+/// # async {
+/// let start = clock.now();
+/// gpt.delay_ms(250).await;
+/// let elapsed = clock.elapsed(start);
+/// # };
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub struct Monotonic {
+    gpt: ral::gpt::Instance,
+    clock_hz: Option<u32>,
+}
+
+impl Clone for Monotonic {
+    fn clone(&self) -> Self {
+        Monotonic {
+            gpt: steal(&self.gpt),
+            clock_hz: self.clock_hz,
+        }
+    }
+}
+
+impl Monotonic {
+    /// Create a clock that extends `gpt`'s counter
+    ///
+    /// The clock uses `gpt`'s counter frequency, if it's known. The clock starts at
+    /// the current counter value.
+    pub fn new(gpt: &GPT) -> Self {
+        cortex_m::interrupt::free(|_| {
+            if ral::read_reg!(ral::gpt, gpt.gpt, IR, ROVIE == 0) {
+                ral::write_reg!(ral::gpt, gpt.gpt, SR, ROV: 1);
+                ral::modify_reg!(ral::gpt, gpt.gpt, IR, ROVIE: 1);
+            }
+        });
+        Monotonic {
+            gpt: steal(&gpt.gpt),
+            clock_hz: gpt.clock_hz,
+        }
+    }
+
+    /// Returns the counter frequency, in Hz, if it's known
+    pub fn clock_hz(&self) -> Option<u32> {
+        self.clock_hz
+    }
+
+    /// Returns the current time
+    ///
+    /// `now` is safe to call from any priority, including from critical sections, as
+    /// long as the rollover interrupt runs at least once per counter period.
+    pub fn now(&self) -> Instant {
+        let rollovers = rollovers(&self.gpt);
+        loop {
+            let high = rollovers.load(atomic::Ordering::Acquire);
+            let low = ral::read_reg!(ral::gpt, self.gpt, CNT);
+            let pending = ral::read_reg!(ral::gpt, self.gpt, SR, ROV == 1);
+            if rollovers.load(atomic::Ordering::Acquire) != high {
+                // The interrupt handler counted a rollover while we were reading
+                continue;
+            }
+            // The counter rolled over, and the interrupt handler hasn't run yet. If the
+            // counter is still high, it rolled over after we read it.
+            let high = if pending && low <= u32::MAX / 2 {
+                high.wrapping_add(1)
+            } else {
+                high
+            };
+            return Instant((u64::from(high) << 32) | u64::from(low));
+        }
+    }
+
+    /// Returns the time elapsed since `earlier`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter frequency is unknown.
+    pub fn elapsed(&self, earlier: Instant) -> Duration {
+        self.duration(self.now().ticks_since(earlier))
+    }
+
+    /// Convert `ticks` into a `Duration`
+    ///
+    /// The result rounds down to the nearest nanosecond.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter frequency is unknown.
+    pub fn duration(&self, ticks: u64) -> Duration {
+        let clock_hz = u64::from(self.expect_clock_hz());
+        let nanos = (ticks % clock_hz) * 1_000_000_000 / clock_hz;
+        Duration::new(ticks / clock_hz, nanos as u32)
+    }
+
+    /// Convert `duration` into ticks
+    ///
+    /// The result rounds up, and saturates at `u64::MAX` ticks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter frequency is unknown.
+    pub fn ticks(&self, duration: Duration) -> u64 {
+        let clock_hz = u128::from(self.expect_clock_hz());
+        let ticks = (duration.as_nanos() * clock_hz + 999_999_999) / 1_000_000_000;
+        if ticks > u128::from(u64::MAX) {
+            u64::MAX
+        } else {
+            ticks as u64
+        }
+    }
+
+    fn expect_clock_hz(&self) -> u32 {
+        self.clock_hz
+            .expect("GPT counter frequency is unknown; see GPT::with_clock")
+    }
+}
+
+/// A point in time, measured by a [`Monotonic`] clock
+///
+/// Only compare `Instant`s from the same clock. Add and subtract ticks to move an
+/// `Instant`; use [`Monotonic::ticks`] to convert a `Duration` into ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub struct Instant(u64);
+
+impl Instant {
+    /// Create an `Instant` at `ticks` since the clock started
+    pub const fn from_ticks(ticks: u64) -> Self {
+        Instant(ticks)
+    }
+
+    /// Returns the number of ticks since the clock started
+    pub const fn ticks(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of ticks from `earlier` to `self`
+    ///
+    /// Returns zero if `earlier` is after `self`.
+    pub fn ticks_since(self, earlier: Instant) -> u64 {
+        self.0.saturating_sub(earlier.0)
+    }
+}
+
+impl ops::Add<u64> for Instant {
+    type Output = Instant;
+    fn add(self, ticks: u64) -> Instant {
+        Instant(self.0 + ticks)
+    }
+}
+
+impl ops::AddAssign<u64> for Instant {
+    fn add_assign(&mut self, ticks: u64) {
+        self.0 += ticks;
+    }
+}
+
+impl ops::Sub<u64> for Instant {
+    type Output = Instant;
+    fn sub(self, ticks: u64) -> Instant {
+        Instant(self.0 - ticks)
+    }
+}
+
+impl ops::SubAssign<u64> for Instant {
+    fn sub_assign(&mut self, ticks: u64) {
+        self.0 -= ticks;
+    }
+}
+
+impl ops::Sub for Instant {
+    type Output = u64;
+    fn sub(self, earlier: Instant) -> u64 {
+        self.0 - earlier.0
+    }
+}
@@ -0,0 +1,242 @@
+//! Ultrasonic ranging with an HC-SR04-style sensor
+//!
+//! An [`HCSR04`] drives the sensor's trigger pin with a GPIO output, and it times
+//! the sensor's echo pulse with a GPT input [`Capture`]. The echo pulse width is
+//! proportional to the distance to the nearest object. The GPT timestamps both echo
+//! edges in hardware, so the measurement doesn't depend on interrupt or task latency.
+//!
+//! Enable the `"gpio"` and `"gpt"` features to use this module.
+//!
+//! # Example
+//!
+//! Measure the distance to an object, using a 1MHz GPT. The echo pin connects to a
+//! pad that's routed to `GPT2_CAPTURE1`. The GPT must know its counter frequency, so
+//! create it with [`with_clock`](crate::GPT::with_clock()).
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{gpio::GPIO, gpt::{Capture, ClockSource, InputCapture}, hcsr04::{self, HCSR04}, ral};
+//!
+//! let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let gpt2 = ral::gpt::GPT2::take().unwrap();
+//! let (mut gpt, _, _) = hal::GPT::with_clock(gpt2, ClockSource::Crystal { divider_24m: 8 }, 3);
+//! let trigger = GPIO::new(pads.b0.p02).output();
+//! // Route the echo pad to GPT2_CAPTURE1...
+//! let echo = Capture::new(&gpt, InputCapture::Channel1).unwrap();
+//! let mut sensor = HCSR04::new(trigger, echo);
+//!
+//! # async {
+//! let micros = sensor.measure(&mut gpt).await.unwrap();
+//! let distance_mm = hcsr04::to_millimeters(micros);
+//! # };
+//! ```
+
+use crate::{
+    gpio::{Output, GPIO},
+    gpt::{micros_from_ticks, ticks_from_micros, Capture, CaptureEdge, Delay, Edge},
+    iomuxc::gpio::Pin,
+    GPT,
+};
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin,
+    task::{Context, Poll},
+};
+
+/// The trigger pulse width, in microseconds
+const TRIGGER_US: u32 = 10;
+/// The longest echo pulse, in microseconds
+///
+/// The sensor holds the echo pin high for about 38ms when it doesn't detect an object.
+const TIMEOUT_US: u32 = 40_000;
+
+/// Errors from an ultrasonic measurement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The echo pulse didn't start, or didn't end, before the timeout
+    ///
+    /// The sensor may not be connected, or it may not have detected an object.
+    Timeout,
+}
+
+/// An HC-SR04-style ultrasonic sensor
+///
+/// See the [module-level documentation](crate::hcsr04) for more information.
+pub struct HCSR04<T>
+where
+    T: Pin,
+{
+    trigger: GPIO<T, Output>,
+    echo: Capture,
+    clock_hz: u32,
+}
+
+impl<T> HCSR04<T>
+where
+    T: Pin,
+{
+    /// Create a sensor that uses the `trigger` output and the `echo` input capture
+    ///
+    /// The sensor converts GPT ticks to microseconds with the capture's
+    /// [`clock_hz`](Capture::clock_hz()). The echo pulse width resolution is one GPT tick,
+    /// so a GPT that counts at 1MHz or faster measures to the microsecond.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capture's GPT counter frequency is unknown, or zero.
+    pub fn new(mut trigger: GPIO<T, Output>, echo: Capture) -> Self {
+        let clock_hz = echo
+            .clock_hz()
+            .filter(|&hz| hz != 0)
+            .expect("GPT counter frequency is unknown; see GPT::with_clock");
+        trigger.clear();
+        HCSR04 {
+            trigger,
+            echo,
+            clock_hz,
+        }
+    }
+
+    /// Trigger a measurement, and return the echo pulse width in microseconds
+    ///
+    /// Use [`to_millimeters`] to convert the pulse width to a distance. `gpt` times the
+    /// trigger pulse and the timeouts; it may be any GPT timer with the same clock as
+    /// the echo capture.
+    pub fn measure<'a>(&'a mut self, gpt: &'a mut GPT) -> Measure<'a, T> {
+        Measure {
+            sensor: self,
+            gpt,
+            state: State::Start,
+            _gpt: PhantomData,
+        }
+    }
+
+    /// Release the trigger GPIO and the echo capture
+    pub fn release(self) -> (GPIO<T, Output>, Capture) {
+        (self.trigger, self.echo)
+    }
+}
+
+/// A future that measures the echo pulse width, in microseconds
+///
+/// Use [`measure`](HCSR04::measure()) to create this future. If you drop the future
+/// before it completes, the sensor stops timestamping echo edges.
+pub struct Measure<'a, T>
+where
+    T: Pin,
+{
+    sensor: &'a mut HCSR04<T>,
+    /// The GPT, borrowed for `'a`; the delay in `state` borrows it
+    gpt: *mut GPT,
+    state: State<'a>,
+    _gpt: PhantomData<&'a mut GPT>,
+}
+
+/// The step of a [`Measure`] future
+enum State<'a> {
+    /// Nothing started
+    Start,
+    /// Holding the trigger pin high
+    Trigger(Delay<'a>),
+    /// Waiting for the echo's rising edge, or a timeout
+    Rise(Delay<'a>),
+    /// Waiting for the echo's falling edge, or a timeout
+    Fall { rise: u32, timeout: Delay<'a> },
+    /// The measurement completed
+    Done,
+}
+
+impl<'a, T> Measure<'a, T>
+where
+    T: Pin,
+{
+    /// Returns a delay of `micros` microseconds
+    fn delay(&mut self, micros: u32) -> Delay<'a> {
+        // Safety: the previous delay, which borrowed the GPT, was dropped or replaced.
+        let gpt = unsafe { &mut *self.gpt };
+        gpt.delay(ticks_from_micros(micros, self.sensor.clock_hz))
+    }
+
+    fn finish(&mut self, result: Result<u32, Error>) -> Poll<Result<u32, Error>> {
+        self.state = State::Done;
+        self.sensor.echo.stop();
+        let clock_hz = self.sensor.clock_hz;
+        Poll::Ready(result.map(|ticks| micros_from_ticks(ticks, clock_hz)))
+    }
+}
+
+impl<T> Future for Measure<'_, T>
+where
+    T: Pin,
+{
+    type Output = Result<u32, Error>;
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the delays are never moved out of the state.
+        let this = unsafe { self.get_unchecked_mut() };
+        loop {
+            match &mut this.state {
+                State::Start => {
+                    this.sensor.echo.start(CaptureEdge::Both {
+                        first: Edge::Rising,
+                    });
+                    this.sensor.trigger.set();
+                    this.state = State::Trigger(this.delay(TRIGGER_US));
+                }
+                State::Trigger(delay) => {
+                    futures::ready!(unsafe { pin::Pin::new_unchecked(delay) }.poll(cx));
+                    this.sensor.trigger.clear();
+                    // Drop the delay before borrowing the GPT again.
+                    this.state = State::Done;
+                    this.state = State::Rise(this.delay(TIMEOUT_US));
+                }
+                State::Rise(timeout) => {
+                    if let Poll::Ready(rise) = this.sensor.echo.poll_capture(cx) {
+                        // Drop the timeout before borrowing the GPT again.
+                        this.state = State::Done;
+                        let timeout = this.delay(TIMEOUT_US);
+                        this.state = State::Fall {
+                            rise: rise.ticks,
+                            timeout,
+                        };
+                    } else {
+                        futures::ready!(unsafe { pin::Pin::new_unchecked(timeout) }.poll(cx));
+                        return this.finish(Err(Error::Timeout));
+                    }
+                }
+                State::Fall { rise, timeout } => {
+                    if let Poll::Ready(fall) = this.sensor.echo.poll_capture(cx) {
+                        let ticks = fall.ticks.wrapping_sub(*rise);
+                        return this.finish(Ok(ticks));
+                    }
+                    futures::ready!(unsafe { pin::Pin::new_unchecked(timeout) }.poll(cx));
+                    return this.finish(Err(Error::Timeout));
+                }
+                State::Done => panic!("Measure polled after completion"),
+            }
+        }
+    }
+}
+
+impl<T> Drop for Measure<'_, T>
+where
+    T: Pin,
+{
+    fn drop(&mut self) {
+        if let State::Start | State::Done = self.state {
+            return;
+        }
+        self.state = State::Done;
+        self.sensor.trigger.clear();
+        self.sensor.echo.stop();
+    }
+}
+
+/// Convert an echo pulse width, in microseconds, to a distance in millimeters
+///
+/// Assumes that sound travels at 343 meters per second.
+pub fn to_millimeters(micros: u32) -> u32 {
+    // The pulse covers the round trip, so halve the distance
+    micros * 343 / 2_000
+}
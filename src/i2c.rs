@@ -1,18 +1,22 @@
 //! I2C driver, types, and futures
 //!
 //! The I2C driver utilizes the internal transmit and receive FIFOs to send and
-//! receive data. When the transmit buffer is nearly full, the driver yields.
-//! When the transmit buffer can support more data, an I2C interrupt
+//! receive data. The driver fills the transmit FIFO with as much data as it can hold,
+//! then yields. When the transmit FIFO is nearly empty, an I2C interrupt
 //! fires and wakes the executor. This cycle continues until all of the
 //! caller's data is transmitted.
 //!
 //! When the receive buffer does not have any data, but the caller is awaiting
-//! data, the drier yields. Once there's at least one byte in the receive buffer,
-//! an I2C interrupt fires and wakers the executor. This cycle continues until
-//! all of the caller's receive buffer is filled.
+//! data, the driver yields. Once the receive FIFO holds enough data for the caller's
+//! buffer, or the FIFO is full, an I2C interrupt fires and wakes the executor.
+//! The driver drains the FIFO, and this cycle continues until all of the caller's
+//! receive buffer is filled.
 //!
 //! The driver also yields when waiting for stop and repeated start conditions.
 //!
+//! Some devices, like EEPROMs, don't acknowledge their address while they're busy. Use
+//! [`with_retry`](I2C::with_retry()) to retry transactions that are not acknowledged.
+//!
 //! The I2C clock speed is unspecified out of construction. Use [`set_clock_speed`](I2C::set_clock_speed())
 //! to select a valid I2C clock speed.
 //!
@@ -79,13 +83,21 @@
 //! ```
 
 mod clock;
-mod commands;
+pub(crate) mod commands;
+pub mod low_level;
 mod read;
+mod register;
+mod retry;
 mod write;
 mod write_read;
 
 pub use clock::ClockSpeed;
 pub use read::Read;
+pub use register::{
+    AddressWidth, Device, Endian, ModifyU8, ReadU16, ReadU8, RegisterMap, RegisterRead,
+    RegisterWrite, MAX_WRITE_LEN,
+};
+pub use retry::{Backoff, Retry, RetryTransaction, Retrying};
 pub use write::Write;
 pub use write_read::WriteRead;
 
@@ -104,6 +116,46 @@ pub struct I2C<SCL, SDA> {
     sda: SDA,
 }
 
+#[cfg(feature = "diagnostics")]
+impl<SCL, SDA> core::fmt::Debug for I2C<SCL, SDA> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use crate::instance::Inst;
+        let men = ral::read_reg!(ral::lpi2c, self.i2c, MCR, MEN);
+        let (tdf, rdf, epf, sdf, ndf, alf, fef, pltf, mbf, bbf) = ral::read_reg!(
+            ral::lpi2c,
+            self.i2c,
+            MSR,
+            TDF,
+            RDF,
+            EPF,
+            SDF,
+            NDF,
+            ALF,
+            FEF,
+            PLTF,
+            MBF,
+            BBF
+        );
+        let (txcount, rxcount) = ral::read_reg!(ral::lpi2c, self.i2c, MFSR, TXCOUNT, RXCOUNT);
+        f.debug_struct("I2C")
+            .field("instance", &self.i2c.inst())
+            .field("enabled", &(men != 0))
+            .field("controller_busy", &(mbf != 0))
+            .field("bus_busy", &(bbf != 0))
+            .field("tx_data", &(tdf != 0))
+            .field("rx_data", &(rdf != 0))
+            .field("end_packet", &(epf != 0))
+            .field("stop_detected", &(sdf != 0))
+            .field("nack", &(ndf != 0))
+            .field("arbitration_lost", &(alf != 0))
+            .field("fifo_error", &(fef != 0))
+            .field("pin_low_timeout", &(pltf != 0))
+            .field("tx_fifo_count", &txcount)
+            .field("rx_fifo_count", &rxcount)
+            .finish()
+    }
+}
+
 impl<SCL, SDA, M> I2C<SCL, SDA>
 where
     M: iomuxc::consts::Unsigned,
@@ -115,6 +167,7 @@ where
     /// The I2C clock speed of the returned `I2C` driver is unspecified and may not be valid.
     /// Use [`set_clock_speed`](I2C::set_clock_speed()) to select a valid I2C clock speed.
     pub fn new(i2c: crate::instance::I2C<M>, mut scl: SCL, mut sda: SDA) -> Self {
+        crate::ccm::assert_gates_on(crate::ccm::Peripheral::I2C(M::USIZE));
         iomuxc::i2c::prepare(&mut scl);
         iomuxc::i2c::prepare(&mut sda);
 
@@ -122,7 +175,10 @@ where
         ral::write_reg!(ral::lpi2c, i2c, MCR, RST: RST_1);
         // Reset is sticky; needs to be explicitly cleared
         ral::write_reg!(ral::lpi2c, i2c, MCR, RST: RST_0);
-        ral::write_reg!(ral::lpi2c, i2c, MFCR, TXWATER: 3, RXWATER: 0);
+        // Transmit requests data once the FIFO is nearly empty, so that the driver
+        // can fill multiple entries per interrupt. The receive watermark is set by
+        // the receive futures.
+        ral::write_reg!(ral::lpi2c, i2c, MFCR, TXWATER: 1, RXWATER: 0);
         ral::modify_reg!(ral::lpi2c, i2c, MCR, MEN: MEN_1);
 
         static ONCE: crate::once::Once = crate::once::new();
@@ -142,6 +198,24 @@ where
 
         I2C { i2c, scl, sda }
     }
+
+    /// Create an `I2C` driver that uses the current peripheral and pin configuration
+    ///
+    /// Unlike [`new`](I2C::new()), `steal` does not reset the peripheral or configure the
+    /// pins, so the clock speed is unchanged.
+    ///
+    /// # Safety
+    ///
+    /// The original driver may still exist, and it may be in the middle of an operation.
+    /// Only use `steal` when the original owner can no longer run, like in a panic or
+    /// fault handler.
+    pub unsafe fn steal(i2c: crate::instance::I2C<M>, scl: SCL, sda: SDA) -> Self {
+        I2C {
+            i2c: i2c.release(),
+            scl,
+            sda,
+        }
+    }
 }
 
 /// Errors propagated from an [`I2C`] device
@@ -169,16 +243,26 @@ pub enum Error {
     ///
     /// The I2C peripheral indicates that it is busy, or that the I2C bus is
     /// busy. Attempting the transaction would block. Consider yielding and
-    /// trying again later.
+    /// trying again later, or use [`wait_while_busy`](crate::i2c::Retrying::wait_while_busy())
+    /// to wait for the bus.
     BusyIsBusy,
 }
 
 impl<SCL, SDA> I2C<SCL, SDA> {
     /// Release the I2C peripheral components
+    ///
+    /// `release` disables the I2C peripheral. You may prepare the returned pins for
+    /// another function.
     pub fn release(self) -> (Instance, SCL, SDA) {
+        ral::modify_reg!(ral::lpi2c, self.i2c, MCR, MEN: MEN_0);
         (self.i2c, self.scl, self.sda)
     }
 
+    /// Borrow the I2C instance for the [`low_level`] API
+    pub fn instance(&mut self) -> &Instance {
+        &self.i2c
+    }
+
     /// Set the I2C clock speed
     ///
     /// If there is an error, error variant is [`crate::i2c::Error::ClockSpeed`].
@@ -57,11 +57,36 @@ pub fn poll_start_read(
     })
 }
 
-/// Send `value` to the I2C device
-pub fn poll_send(i2c: &Instance, cx: &mut Context<'_>, value: u8) -> Poll<Result<(), Error>> {
-    poll_transmit_ready(i2c, cx).map_ok(|_| {
-        ral::write_reg!(ral::lpi2c, i2c, MTDR, CMD: CMD_0, DATA: value as u32);
-    })
+/// Returns the transmit and receive FIFO sizes, in entries
+fn fifo_sizes(i2c: &Instance) -> (usize, usize) {
+    let (tx, rx) = ral::read_reg!(ral::lpi2c, i2c, PARAM, MTXFIFO, MRXFIFO);
+    (1 << tx, 1 << rx)
+}
+
+/// Send as many `bytes` as fit in the transmit FIFO
+///
+/// Resolves to the number of bytes sent, which is non-zero if `bytes` is non-empty.
+pub fn poll_send(i2c: &Instance, cx: &mut Context<'_>, bytes: &[u8]) -> Poll<Result<usize, Error>> {
+    #[cfg(feature = "bench")]
+    let _measurement = crate::bench::Measurement::start(crate::bench::Probe::I2cFifo);
+    if let Err(err) = super::check_errors(&i2c) {
+        return Poll::Ready(Err(err));
+    }
+    let (tx_fifo, _) = fifo_sizes(i2c);
+    let count = ral::read_reg!(ral::lpi2c, i2c, MFSR, TXCOUNT) as usize;
+    let free = tx_fifo.saturating_sub(count);
+    if 0 == free {
+        *waker(&i2c) = Some(cx.waker().clone());
+        atomic::compiler_fence(atomic::Ordering::Release);
+        enable_interrupts(&i2c, InterruptKind::Transfer);
+        Poll::Pending
+    } else {
+        let len = free.min(bytes.len());
+        for &byte in &bytes[..len] {
+            ral::write_reg!(ral::lpi2c, i2c, MTDR, CMD: CMD_0, DATA: byte as u32);
+        }
+        Poll::Ready(Ok(len))
+    }
 }
 
 /// Resolves when we acknowledge and end of packet (repeated start, or stop condition)
@@ -81,6 +106,8 @@ pub fn poll_end_of_packet(i2c: &Instance, cx: &mut Context<'_>) -> Poll<Result<(
 }
 
 /// Prepare to receive `len` bytes from the I2C device
+///
+/// `len` must be between 1 and 256.
 pub fn poll_receive_length(
     i2c: &Instance,
     cx: &mut Context<'_>,
@@ -90,18 +117,36 @@ pub fn poll_receive_length(
         .map_ok(|_| ral::write_reg!(ral::lpi2c, i2c, MTDR, CMD: CMD_1, DATA: (len - 1) as u32))
 }
 
-/// Receive a byte from the I2C device
-pub fn poll_receive(i2c: &Instance, cx: &mut Context<'_>) -> Poll<Result<u8, Error>> {
+/// Drain as many bytes as possible from the receive FIFO into `buffer`
+///
+/// Resolves to the number of bytes received, which is non-zero if `buffer` is non-empty.
+/// While waiting, the receive watermark is raised so that the interrupt fires once the
+/// FIFO holds enough data for `buffer`, or once the FIFO is full.
+pub fn poll_receive(
+    i2c: &Instance,
+    cx: &mut Context<'_>,
+    buffer: &mut [u8],
+) -> Poll<Result<usize, Error>> {
+    #[cfg(feature = "bench")]
+    let _measurement = crate::bench::Measurement::start(crate::bench::Probe::I2cFifo);
     if let Err(err) = super::check_errors(&i2c) {
-        Poll::Ready(Err(err))
-    } else if ral::read_reg!(ral::lpi2c, i2c, MSR, RDF == RDF_1) {
-        let byte = ral::read_reg!(ral::lpi2c, i2c, MRDR, DATA);
-        Poll::Ready(Ok(byte as u8))
-    } else {
+        return Poll::Ready(Err(err));
+    }
+    let count = ral::read_reg!(ral::lpi2c, i2c, MFSR, RXCOUNT) as usize;
+    if 0 == count {
+        let (_, rx_fifo) = fifo_sizes(i2c);
+        let watermark = buffer.len().min(rx_fifo).saturating_sub(1);
+        ral::modify_reg!(ral::lpi2c, i2c, MFCR, RXWATER: watermark as u32);
         *waker(&i2c) = Some(cx.waker().clone());
         atomic::compiler_fence(atomic::Ordering::Release);
         enable_interrupts(&i2c, InterruptKind::Receive);
         Poll::Pending
+    } else {
+        let len = count.min(buffer.len());
+        for byte in &mut buffer[..len] {
+            *byte = ral::read_reg!(ral::lpi2c, i2c, MRDR, DATA) as u8;
+        }
+        Poll::Ready(Ok(len))
     }
 }
 
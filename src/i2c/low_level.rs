@@ -0,0 +1,77 @@
+//! Low-level I2C building blocks
+//!
+//! The functions in this module are the steps of the I2C futures. Use them to build
+//! your own futures or state machines for transactions that the [`I2C`](super::I2C)
+//! driver doesn't support. Most users should use the `I2C` driver.
+//!
+//! Each `poll_*` function takes the I2C instance, and the task context. Use
+//! [`I2C::instance`](super::I2C::instance()) to borrow the instance from a driver.
+//! When a function returns `Poll::Pending`, it has registered the context's waker,
+//! and enabled the interrupt that wakes it. Call it again once woken.
+//!
+//! A transaction has these steps:
+//!
+//! 1. [`prepare`] the peripheral.
+//! 2. [`poll_start_write`] or [`poll_start_read`] to address the device.
+//! 3. To write, [`poll_send`] until all bytes are sent. To read, [`poll_receive_length`],
+//!    then [`poll_receive`] until the buffer is full.
+//! 4. For a repeated start, [`poll_end_of_packet`], then go to step 2.
+//! 5. [`poll_stop_setup`], then [`poll_stop`].
+//! 6. [`finish`], even if a step returned an error.
+//!
+//! # Example
+//!
+//! A future that only addresses a device, and checks for an acknowledgement.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{i2c::low_level, I2CError};
+//! use core::task::Poll;
+//!
+//! async fn probe<SCL, SDA>(i2c: &mut hal::I2C<SCL, SDA>, address: u8) -> Result<(), I2CError> {
+//!     let i2c = i2c.instance();
+//!     low_level::prepare(i2c)?;
+//!     let mut started = false;
+//!     let mut stopping = false;
+//!     let result = futures::future::poll_fn(|cx| loop {
+//!         if !started {
+//!             futures::ready!(low_level::poll_start_write(i2c, cx, address)?);
+//!             started = true;
+//!         } else if !stopping {
+//!             futures::ready!(low_level::poll_stop_setup(i2c, cx)?);
+//!             stopping = true;
+//!         } else {
+//!             return low_level::poll_stop(i2c, cx);
+//!         }
+//!     })
+//!     .await;
+//!     low_level::finish(i2c);
+//!     result
+//! }
+//! ```
+
+use super::Error;
+use crate::ral::lpi2c::Instance;
+
+pub use super::commands::{
+    poll_end_of_packet, poll_receive, poll_receive_length, poll_send, poll_start_read,
+    poll_start_write, poll_stop, poll_stop_setup,
+};
+
+/// Prepare the peripheral for a new transaction
+///
+/// Clears the FIFOs and the status flags. Returns [`Error::BusyIsBusy`] if the
+/// peripheral or the bus is busy.
+pub fn prepare(i2c: &Instance) -> Result<(), Error> {
+    super::check_busy(i2c)?;
+    super::clear_fifo(i2c);
+    super::clear_status(i2c);
+    Ok(())
+}
+
+/// Disable the interrupts that the `poll_*` functions enabled
+///
+/// Call `finish` once the transaction completes, fails, or is abandoned.
+pub fn finish(i2c: &Instance) {
+    super::disable_interrupts(i2c);
+}
@@ -64,9 +64,12 @@ impl Future for Read<'_> {
                     this.state = Some(State::Receive(0));
                 }
                 Some(State::Receive(idx)) => {
-                    let byte = futures::ready!(commands::poll_receive(&this.i2c, cx)?);
-                    this.buffer[idx] = byte;
-                    let next_idx = idx + 1;
+                    let received = futures::ready!(commands::poll_receive(
+                        &this.i2c,
+                        cx,
+                        &mut this.buffer[idx..]
+                    )?);
+                    let next_idx = idx + received;
                     this.state = if next_idx < this.buffer.len() {
                         Some(State::Receive(next_idx))
                     } else {
@@ -0,0 +1,379 @@
+//! Typed register access for I2C devices
+
+use super::{write::Write, write_read::WriteRead, Error, Instance, I2C};
+use core::{
+    future::Future,
+    marker::{PhantomData, PhantomPinned},
+    pin,
+    task::{Context, Poll},
+};
+
+/// The largest number of data bytes in a single [`write`](Device::write()) call
+pub const MAX_WRITE_LEN: usize = 32;
+
+/// The width of a device's register addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub enum AddressWidth {
+    /// One byte register addresses
+    U8,
+    /// Two byte register addresses
+    U16,
+}
+
+/// The byte order of multi-byte register addresses and values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub enum Endian {
+    /// Most significant byte first
+    Big,
+    /// Least significant byte first
+    Little,
+}
+
+/// Describes the register map of an I2C device
+///
+/// Implement `RegisterMap` on a type that represents your device, then use
+/// [`I2C::device`] to access the device's registers.
+///
+/// # Example
+///
+/// A driver for a sensor with one byte registers, and big endian values.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::i2c::{AddressWidth, Endian, RegisterMap};
+///
+/// struct Sensor;
+///
+/// impl RegisterMap for Sensor {
+///     const ADDRESS: u8 = 0x68;
+///     const ADDRESS_WIDTH: AddressWidth = AddressWidth::U8;
+///     const ENDIAN: Endian = Endian::Big;
+/// }
+///
+/// const WHO_AM_I: u16 = 0x75;
+/// const TEMPERATURE: u16 = 0x41;
+///
+/// async fn temperature<SCL, SDA>(i2c: &mut hal::I2C<SCL, SDA>) -> Result<u16, hal::I2CError> {
+///     let mut sensor = i2c.device::<Sensor>();
+///     assert_eq!(sensor.read_u8(WHO_AM_I).await?, 0x68);
+///     sensor.read_u16(TEMPERATURE).await
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub trait RegisterMap {
+    /// The device's 7-bit I2C address
+    const ADDRESS: u8;
+    /// The width of the device's register addresses
+    const ADDRESS_WIDTH: AddressWidth;
+    /// The byte order of the device's register addresses and values
+    const ENDIAN: Endian;
+}
+
+/// An I2C device, described by a [`RegisterMap`]
+///
+/// Use [`I2C::device`] to create a `Device`.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct Device<'a, M, SCL, SDA> {
+    i2c: &'a mut I2C<SCL, SDA>,
+    _map: PhantomData<M>,
+}
+
+impl<SCL, SDA> I2C<SCL, SDA> {
+    /// Access the registers of the device described by `M`
+    ///
+    /// See [`RegisterMap`] for an example.
+    pub fn device<M: RegisterMap>(&mut self) -> Device<'_, M, SCL, SDA> {
+        Device {
+            i2c: self,
+            _map: PhantomData,
+        }
+    }
+}
+
+impl<M: RegisterMap, SCL, SDA> Device<'_, M, SCL, SDA> {
+    /// Read consecutive registers, starting at `register`, into `buffer`
+    pub fn read<'b>(&'b mut self, register: u16, buffer: &'b mut [u8]) -> RegisterRead<'b> {
+        RegisterRead {
+            transaction: Transaction::new::<M>(&self.i2c.i2c, register, Dest::Buffer(Some(buffer))),
+        }
+    }
+
+    /// Write `data` to consecutive registers, starting at `register`
+    ///
+    /// Returns [`Error::RequestTooMuchData`] if `data` is longer than [`MAX_WRITE_LEN`].
+    pub fn write(&mut self, register: u16, data: &[u8]) -> RegisterWrite<'_> {
+        RegisterWrite {
+            transaction: Transaction::write::<M>(&self.i2c.i2c, register, data),
+        }
+    }
+
+    /// Read a one byte register
+    pub fn read_u8(&mut self, register: u16) -> ReadU8<'_> {
+        ReadU8 {
+            transaction: Transaction::new::<M>(&self.i2c.i2c, register, Dest::Value(1)),
+        }
+    }
+
+    /// Write a one byte register
+    pub fn write_u8(&mut self, register: u16, value: u8) -> RegisterWrite<'_> {
+        self.write(register, &[value])
+    }
+
+    /// Read a two byte register, using the device's byte order
+    pub fn read_u16(&mut self, register: u16) -> ReadU16<'_, M> {
+        ReadU16 {
+            transaction: Transaction::new::<M>(&self.i2c.i2c, register, Dest::Value(2)),
+            _map: PhantomData,
+        }
+    }
+
+    /// Write a two byte register, using the device's byte order
+    pub fn write_u16(&mut self, register: u16, value: u16) -> RegisterWrite<'_> {
+        let value = match M::ENDIAN {
+            Endian::Big => value.to_be_bytes(),
+            Endian::Little => value.to_le_bytes(),
+        };
+        self.write(register, &value)
+    }
+
+    /// Read, modify, and write a one byte register
+    ///
+    /// `f` receives the current value, and returns the new value.
+    pub fn modify_u8<F: FnOnce(u8) -> u8>(&mut self, register: u16, f: F) -> ModifyU8<'_, M, F> {
+        ModifyU8 {
+            transaction: Transaction::new::<M>(&self.i2c.i2c, register, Dest::Value(1)),
+            register,
+            f: Some(f),
+            _map: PhantomData,
+        }
+    }
+}
+
+/// Where a [`Transaction`] puts the data that it reads
+enum Dest<'a> {
+    /// Write only
+    None,
+    /// Read into the caller's buffer, taken once the transfer starts
+    Buffer(Option<&'a mut [u8]>),
+    /// Read this many bytes into the transaction's `value`
+    Value(usize),
+}
+
+/// The transfer that a [`Transaction`] runs
+enum Transfer<'a> {
+    Write(Write<'a>),
+    WriteRead(WriteRead<'a>),
+}
+
+/// A register access: the register address and any data to write, followed by any
+/// data to read
+///
+/// The transfer borrows `frame` and `value`, so a transaction only starts its transfer
+/// once it's pinned.
+struct Transaction<'a> {
+    i2c: &'a Instance,
+    address: u8,
+    /// The register address, followed by the data to write
+    frame: [u8; 2 + MAX_WRITE_LEN],
+    frame_len: usize,
+    dest: Dest<'a>,
+    value: [u8; 2],
+    /// An error to return instead of starting the transfer
+    error: Option<Error>,
+    transfer: Option<Transfer<'a>>,
+    _pin: PhantomPinned,
+}
+
+impl<'a> Transaction<'a> {
+    fn new<M: RegisterMap>(i2c: &'a Instance, register: u16, dest: Dest<'a>) -> Self {
+        let (address, len) = register_bytes::<M>(register);
+        let mut frame = [0; 2 + MAX_WRITE_LEN];
+        frame[..len].copy_from_slice(&address[..len]);
+        Transaction {
+            i2c,
+            address: M::ADDRESS,
+            frame,
+            frame_len: len,
+            dest,
+            value: [0; 2],
+            error: None,
+            transfer: None,
+            _pin: PhantomPinned,
+        }
+    }
+
+    fn write<M: RegisterMap>(i2c: &'a Instance, register: u16, data: &[u8]) -> Self {
+        let mut transaction = Self::new::<M>(i2c, register, Dest::None);
+        if data.len() > MAX_WRITE_LEN {
+            transaction.error = Some(Error::RequestTooMuchData);
+        } else {
+            let len = transaction.frame_len;
+            transaction.frame[len..len + data.len()].copy_from_slice(data);
+            transaction.frame_len += data.len();
+        }
+        transaction
+    }
+
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        // Safety: the transaction is pinned, so `frame` and `value` don't move while the
+        // transfer borrows them. The transfer is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Some(error) = this.error.take() {
+            return Poll::Ready(Err(error));
+        }
+        if this.transfer.is_none() {
+            // Safety: see above. Only the transfer uses these borrows.
+            let output = unsafe { &*(&this.frame[..this.frame_len] as *const [u8]) };
+            this.transfer = Some(match &mut this.dest {
+                Dest::None => Transfer::Write(Write::new(this.i2c, this.address, output)),
+                Dest::Buffer(buffer) => Transfer::WriteRead(WriteRead::new(
+                    this.i2c,
+                    this.address,
+                    output,
+                    buffer
+                        .take()
+                        .expect("register read polled after completion"),
+                )),
+                Dest::Value(len) => {
+                    let input = unsafe { &mut *(&mut this.value[..*len] as *mut [u8]) };
+                    Transfer::WriteRead(WriteRead::new(this.i2c, this.address, output, input))
+                }
+            });
+        }
+        // Safety: the transfer is never moved out of `self`.
+        let result = match this.transfer.as_mut().unwrap() {
+            Transfer::Write(write) => unsafe { pin::Pin::new_unchecked(write) }.poll(cx),
+            Transfer::WriteRead(write_read) => {
+                unsafe { pin::Pin::new_unchecked(write_read) }.poll(cx)
+            }
+        };
+        if result.is_ready() {
+            this.transfer = None;
+        }
+        result
+    }
+
+    /// The bytes read into `value`
+    fn value(&self) -> &[u8] {
+        match self.dest {
+            Dest::Value(len) => &self.value[..len],
+            _ => &[],
+        }
+    }
+}
+
+/// Returns the register address bytes, and the number of valid bytes
+fn register_bytes<M: RegisterMap>(register: u16) -> ([u8; 2], usize) {
+    match (M::ADDRESS_WIDTH, M::ENDIAN) {
+        (AddressWidth::U8, _) => ([register as u8, 0], 1),
+        (AddressWidth::U16, Endian::Big) => (register.to_be_bytes(), 2),
+        (AddressWidth::U16, Endian::Little) => (register.to_le_bytes(), 2),
+    }
+}
+
+/// A future that reads consecutive registers
+///
+/// Use [`read`](Device::read()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct RegisterRead<'a> {
+    transaction: Transaction<'a>,
+}
+
+impl Future for RegisterRead<'_> {
+    type Output = Result<(), Error>;
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the transaction is never moved out of `self`.
+        unsafe { self.map_unchecked_mut(|this| &mut this.transaction) }.poll(cx)
+    }
+}
+
+/// A future that writes consecutive registers
+///
+/// Use [`write`](Device::write()), [`write_u8`](Device::write_u8()), or
+/// [`write_u16`](Device::write_u16()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct RegisterWrite<'a> {
+    transaction: Transaction<'a>,
+}
+
+impl Future for RegisterWrite<'_> {
+    type Output = Result<(), Error>;
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the transaction is never moved out of `self`.
+        unsafe { self.map_unchecked_mut(|this| &mut this.transaction) }.poll(cx)
+    }
+}
+
+/// A future that reads a one byte register
+///
+/// Use [`read_u8`](Device::read_u8()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct ReadU8<'a> {
+    transaction: Transaction<'a>,
+}
+
+impl Future for ReadU8<'_> {
+    type Output = Result<u8, Error>;
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the transaction is never moved out of `self`.
+        let mut transaction = unsafe { self.map_unchecked_mut(|this| &mut this.transaction) };
+        futures::ready!(transaction.as_mut().poll(cx))?;
+        Poll::Ready(Ok(transaction.value()[0]))
+    }
+}
+
+/// A future that reads a two byte register
+///
+/// Use [`read_u16`](Device::read_u16()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct ReadU16<'a, M> {
+    transaction: Transaction<'a>,
+    _map: PhantomData<M>,
+}
+
+impl<M: RegisterMap> Future for ReadU16<'_, M> {
+    type Output = Result<u16, Error>;
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the transaction is never moved out of `self`.
+        let mut transaction = unsafe { self.map_unchecked_mut(|this| &mut this.transaction) };
+        futures::ready!(transaction.as_mut().poll(cx))?;
+        let value = [transaction.value()[0], transaction.value()[1]];
+        Poll::Ready(Ok(match M::ENDIAN {
+            Endian::Big => u16::from_be_bytes(value),
+            Endian::Little => u16::from_le_bytes(value),
+        }))
+    }
+}
+
+/// A future that reads, modifies, and writes a one byte register
+///
+/// Use [`modify_u8`](Device::modify_u8()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct ModifyU8<'a, M, F> {
+    /// The read, then the write
+    transaction: Transaction<'a>,
+    register: u16,
+    /// The modification, taken once the read completes
+    f: Option<F>,
+    _map: PhantomData<M>,
+}
+
+impl<M: RegisterMap, F: FnOnce(u8) -> u8> Future for ModifyU8<'_, M, F> {
+    type Output = Result<(), Error>;
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the transaction is never moved out of `self`. Replacing it drops the
+        // read in place.
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut transaction = unsafe { pin::Pin::new_unchecked(&mut this.transaction) };
+        futures::ready!(transaction.as_mut().poll(cx))?;
+        if let Some(f) = this.f.take() {
+            let value = f(transaction.value()[0]);
+            let i2c = transaction.i2c;
+            transaction.set(Transaction::write::<M>(i2c, this.register, &[value]));
+            futures::ready!(transaction.poll(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,350 @@
+//! Retrying I2C transactions
+
+use super::{read::Read, write::Write, write_read::WriteRead, Error, Instance, I2C};
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A timer that waits between I2C retries
+///
+/// `Backoff` is implemented for the crate's timers. Implement `Backoff` if you
+/// want to use another timer.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub trait Backoff<'a> {
+    /// The delay future
+    type Delay: Future<Output = ()> + 'a;
+    /// Wait for `ticks` timer counts to elapse
+    fn delay(&'a mut self, ticks: u32) -> Self::Delay;
+}
+
+#[cfg(feature = "gpt")]
+impl<'a> Backoff<'a> for crate::GPT {
+    type Delay = crate::gpt::Delay<'a>;
+    fn delay(&'a mut self, ticks: u32) -> Self::Delay {
+        crate::GPT::delay(self, ticks)
+    }
+}
+
+#[cfg(feature = "pit")]
+impl<'a> Backoff<'a> for crate::PIT {
+    type Delay = crate::pit::Delay<'a>;
+    fn delay(&'a mut self, ticks: u32) -> Self::Delay {
+        crate::PIT::delay(self, ticks)
+    }
+}
+
+#[cfg(feature = "systick")]
+impl<'a> Backoff<'a> for crate::SysTick {
+    type Delay = crate::systick::Delay<'a>;
+    fn delay(&'a mut self, ticks: u32) -> Self::Delay {
+        crate::SysTick::delay(self, ticks)
+    }
+}
+
+/// How to retry an I2C transaction
+///
+/// A transaction is retried if it fails with [`Error::UnexpectedNACK`] or
+/// [`Error::BusyIsBusy`]. All other errors are returned immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct Retry {
+    /// The number of retries after the first attempt
+    pub retries: u32,
+    /// Timer ticks to wait before each retry
+    pub backoff: u32,
+}
+
+impl Retry {
+    /// Try each transaction once
+    pub const NONE: Retry = Retry {
+        retries: 0,
+        backoff: 0,
+    };
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Retry::NONE
+    }
+}
+
+/// An I2C driver that retries its transactions
+///
+/// Use [`I2C::with_retry`] to create a `Retrying` driver.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct Retrying<'a, B, SCL, SDA> {
+    i2c: &'a mut I2C<SCL, SDA>,
+    backoff: &'a mut B,
+    retry: Retry,
+    wait_while_busy: bool,
+}
+
+impl<SCL, SDA> I2C<SCL, SDA> {
+    /// Retry the following transactions using the `retry` policy
+    ///
+    /// `backoff` waits between retries. Its tick rate determines the duration of
+    /// [`Retry::backoff`].
+    ///
+    /// # Example
+    ///
+    /// An EEPROM does not acknowledge its address while it completes a write. Retry
+    /// the next access for up to 10ms, using a 1MHz GPT.
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::i2c::Retry;
+    ///
+    /// const EEPROM_ADDRESS: u8 = 0x50;
+    ///
+    /// async fn read_after_write<SCL, SDA>(
+    ///     i2c: &mut hal::I2C<SCL, SDA>,
+    ///     gpt: &mut hal::GPT,
+    /// ) -> Result<u8, hal::I2CError> {
+    ///     let retry = Retry { retries: 10, backoff: 1_000 };
+    ///     i2c.write(EEPROM_ADDRESS, &[0x00, 0x10, 0xAB]).await?;
+    ///     let mut data = [0; 1];
+    ///     i2c.with_retry(retry, gpt)
+    ///         .write_read(EEPROM_ADDRESS, &[0x00, 0x10], &mut data)
+    ///         .await?;
+    ///     Ok(data[0])
+    /// }
+    /// ```
+    pub fn with_retry<'a, B>(
+        &'a mut self,
+        retry: Retry,
+        backoff: &'a mut B,
+    ) -> Retrying<'a, B, SCL, SDA>
+    where
+        B: for<'b> Backoff<'b>,
+    {
+        Retrying {
+            i2c: self,
+            backoff,
+            retry,
+            wait_while_busy: false,
+        }
+    }
+}
+
+/// Returns `true` if a transaction that failed with `error` should be retried
+fn is_retryable(error: &Error) -> bool {
+    matches!(error, Error::UnexpectedNACK | Error::BusyIsBusy)
+}
+
+impl<B, SCL, SDA> Retrying<'_, B, SCL, SDA>
+where
+    B: for<'b> Backoff<'b>,
+{
+    /// Wait for the bus to become idle, without limit
+    ///
+    /// When a transaction fails with [`Error::BusyIsBusy`], the driver waits
+    /// [`Retry::backoff`] ticks, then tries again, until the bus is idle. These attempts
+    /// don't count against [`Retry::retries`]. Use `wait_while_busy` when tasks, or
+    /// another bus controller, share the bus.
+    ///
+    /// Since the driver polls the bus, choose a short backoff, like the time to transfer
+    /// a few bytes.
+    ///
+    /// # Example
+    ///
+    /// Wait for another bus controller to finish, polling every 100us with a 1MHz GPT.
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::i2c::Retry;
+    ///
+    /// const SENSOR_ADDRESS: u8 = 0x40;
+    ///
+    /// async fn read_sensor<SCL, SDA>(
+    ///     i2c: &mut hal::I2C<SCL, SDA>,
+    ///     gpt: &mut hal::GPT,
+    /// ) -> Result<[u8; 2], hal::I2CError> {
+    ///     let retry = Retry { retries: 0, backoff: 100 };
+    ///     let mut data = [0; 2];
+    ///     i2c.with_retry(retry, gpt)
+    ///         .wait_while_busy()
+    ///         .write_read(SENSOR_ADDRESS, &[0xE3], &mut data)
+    ///         .await?;
+    ///     Ok(data)
+    /// }
+    /// ```
+    pub fn wait_while_busy(mut self) -> Self {
+        self.wait_while_busy = true;
+        self
+    }
+
+    /// Perform an I2C write, retrying as necessary
+    ///
+    /// See [`I2C::write`] for more information.
+    pub fn write<'b>(&'b mut self, address: u8, buffer: &'b [u8]) -> RetryTransaction<'b, B> {
+        self.transaction(address, Operation::Write(buffer))
+    }
+
+    /// Perform an I2C read, retrying as necessary
+    ///
+    /// See [`I2C::read`] for more information.
+    pub fn read<'b>(&'b mut self, address: u8, buffer: &'b mut [u8]) -> RetryTransaction<'b, B> {
+        self.transaction(address, Operation::Read(buffer))
+    }
+
+    /// Perform an I2C write-read, retrying as necessary
+    ///
+    /// See [`I2C::write_read`] for more information.
+    pub fn write_read<'b>(
+        &'b mut self,
+        address: u8,
+        output: &'b [u8],
+        input: &'b mut [u8],
+    ) -> RetryTransaction<'b, B> {
+        self.transaction(address, Operation::WriteRead(output, input))
+    }
+
+    fn transaction<'b>(&'b mut self, address: u8, operation: Operation) -> RetryTransaction<'b, B> {
+        RetryTransaction {
+            i2c: &self.i2c.i2c,
+            address,
+            operation,
+            backoff: &mut *self.backoff,
+            retry: self.retry,
+            wait_while_busy: self.wait_while_busy,
+            attempt: 0,
+            state: State::Start,
+            _borrows: PhantomData,
+        }
+    }
+}
+
+/// The buffers of a [`RetryTransaction`]
+///
+/// The buffers are borrowed for the transaction's lifetime. Each attempt borrows them
+/// again.
+enum Operation {
+    Write(*const [u8]),
+    Read(*mut [u8]),
+    WriteRead(*const [u8], *mut [u8]),
+}
+
+/// The I2C future of one attempt
+enum Transfer<'a> {
+    Write(Write<'a>),
+    Read(Read<'a>),
+    WriteRead(WriteRead<'a>),
+}
+
+/// The step of a [`RetryTransaction`]
+enum State<'a, B>
+where
+    B: Backoff<'a>,
+{
+    /// Start the next attempt
+    Start,
+    /// An attempt is in progress
+    Transfer(Transfer<'a>),
+    /// Waiting before the next attempt
+    Backoff(B::Delay),
+}
+
+/// A future that performs an I2C transaction, retrying as necessary
+///
+/// Use [`write`](Retrying::write()), [`read`](Retrying::read()), or
+/// [`write_read`](Retrying::write_read()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "i2c")))]
+pub struct RetryTransaction<'a, B>
+where
+    B: Backoff<'a>,
+{
+    i2c: &'a Instance,
+    address: u8,
+    operation: Operation,
+    /// The backoff timer, borrowed for `'a`; the delay in `state` borrows it
+    backoff: *mut B,
+    retry: Retry,
+    wait_while_busy: bool,
+    /// The number of retries so far
+    attempt: u32,
+    state: State<'a, B>,
+    _borrows: PhantomData<(&'a mut B, &'a mut [u8])>,
+}
+
+impl<'a, B> RetryTransaction<'a, B>
+where
+    B: Backoff<'a>,
+{
+    /// Returns the I2C future for the next attempt
+    fn transfer(&mut self) -> Transfer<'a> {
+        // Safety: the buffers are borrowed for 'a, and the previous attempt, which
+        // borrowed them, was dropped.
+        unsafe {
+            match self.operation {
+                Operation::Write(buffer) => {
+                    Transfer::Write(Write::new(self.i2c, self.address, &*buffer))
+                }
+                Operation::Read(buffer) => {
+                    Transfer::Read(Read::new(self.i2c, self.address, &mut *buffer))
+                }
+                Operation::WriteRead(output, input) => Transfer::WriteRead(WriteRead::new(
+                    self.i2c,
+                    self.address,
+                    &*output,
+                    &mut *input,
+                )),
+            }
+        }
+    }
+
+    /// Returns `true` if the transaction should try again after `error`
+    fn should_retry(&mut self, error: &Error) -> bool {
+        let waiting = self.wait_while_busy && matches!(error, Error::BusyIsBusy);
+        if !waiting {
+            if self.attempt >= self.retry.retries || !is_retryable(error) {
+                return false;
+            }
+            self.attempt += 1;
+        }
+        true
+    }
+}
+
+impl<'a, B> Future for RetryTransaction<'a, B>
+where
+    B: Backoff<'a>,
+{
+    type Output = Result<(), Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the attempt and the delay are never moved out of the state.
+        let this = unsafe { self.get_unchecked_mut() };
+        loop {
+            match &mut this.state {
+                State::Start => this.state = State::Transfer(this.transfer()),
+                State::Transfer(transfer) => {
+                    let result = futures::ready!(match transfer {
+                        Transfer::Write(write) => unsafe { Pin::new_unchecked(write) }.poll(cx),
+                        Transfer::Read(read) => unsafe { Pin::new_unchecked(read) }.poll(cx),
+                        Transfer::WriteRead(write_read) => {
+                            unsafe { Pin::new_unchecked(write_read) }.poll(cx)
+                        }
+                    });
+                    this.state = State::Start;
+                    let error = match result {
+                        Ok(()) => return Poll::Ready(Ok(())),
+                        Err(error) => error,
+                    };
+                    if !this.should_retry(&error) {
+                        return Poll::Ready(Err(error));
+                    }
+                    // Safety: the backoff is borrowed for 'a, and no other delay
+                    // borrows it.
+                    let backoff = unsafe { &mut *this.backoff };
+                    this.state = State::Backoff(backoff.delay(this.retry.backoff));
+                }
+                State::Backoff(delay) => {
+                    futures::ready!(unsafe { Pin::new_unchecked(delay) }.poll(cx));
+                    this.state = State::Start;
+                }
+            }
+        }
+    }
+}
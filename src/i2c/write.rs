@@ -54,8 +54,9 @@ impl Future for Write<'_> {
                     this.state = Some(State::Send(0));
                 }
                 Some(State::Send(idx)) => {
-                    futures::ready!(commands::poll_send(&this.i2c, cx, this.buffer[idx])?);
-                    let next_idx = idx + 1;
+                    let sent =
+                        futures::ready!(commands::poll_send(&this.i2c, cx, &this.buffer[idx..])?);
+                    let next_idx = idx + sent;
                     this.state = if next_idx < this.buffer.len() {
                         Some(State::Send(next_idx))
                     } else {
@@ -63,8 +63,9 @@ impl Future for WriteRead<'_> {
                     this.state = Some(State::Send(0));
                 }
                 Some(State::Send(idx)) => {
-                    futures::ready!(commands::poll_send(&this.i2c, cx, this.output[idx])?);
-                    let next_idx = idx + 1;
+                    let sent =
+                        futures::ready!(commands::poll_send(&this.i2c, cx, &this.output[idx..])?);
+                    let next_idx = idx + sent;
                     this.state = if next_idx < this.output.len() {
                         Some(State::Send(next_idx))
                     } else {
@@ -92,9 +93,12 @@ impl Future for WriteRead<'_> {
                     this.state = Some(State::Receive(0));
                 }
                 Some(State::Receive(idx)) => {
-                    let byte = futures::ready!(commands::poll_receive(&this.i2c, cx)?);
-                    this.input[idx] = byte;
-                    let next_idx = idx + 1;
+                    let received = futures::ready!(commands::poll_receive(
+                        &this.i2c,
+                        cx,
+                        &mut this.input[idx..]
+                    )?);
+                    let next_idx = idx + received;
                     this.state = if next_idx < this.input.len() {
                         Some(State::Receive(next_idx))
                     } else {
@@ -0,0 +1,435 @@
+//! Infrared remote control decoders
+//!
+//! The decoders time the edges from an infrared receiver module, like a TSOP38238,
+//! using a GPT input [`Capture`]. [`NEC`] decodes the NEC protocol, and [`RC5`] decodes
+//! the Philips RC-5 protocol. Each decoder is a [`Stream`](futures::stream::Stream) of
+//! frames, and it skips anything that doesn't decode.
+//!
+//! The decoders expect an active-low receiver, which holds its output high when idle.
+//! The GPT timestamps each edge in hardware, and queues the timestamps, so the task
+//! that polls the stream may fall a few milliseconds behind the signal without losing
+//! the frame. If the capture queue overflows, the decoder drops the queued edges, and
+//! the frame.
+//!
+//! Enable the `"gpt"` feature to use this module.
+//!
+//! # Example
+//!
+//! Decode NEC frames, using a 1MHz GPT. The receiver connects to a pad that's routed
+//! to `GPT2_CAPTURE1`. The GPT must know its counter frequency, so create it with
+//! [`with_clock`](crate::GPT::with_clock()).
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{gpt::{Capture, ClockSource, InputCapture}, ir::NEC, ral};
+//! use futures::stream::StreamExt;
+//!
+//! let gpt2 = ral::gpt::GPT2::take().unwrap();
+//! let (gpt, _, _) = hal::GPT::with_clock(gpt2, ClockSource::Crystal { divider_24m: 8 }, 3);
+//! // Route the receiver pad to GPT2_CAPTURE1...
+//! let mut remote = NEC::new(Capture::new(&gpt, InputCapture::Channel1).unwrap());
+//!
+//! # async {
+//! while let Some(frame) = remote.next().await {
+//!     if !frame.repeat {
+//!         // Handle frame.address, frame.command...
+//!     }
+//! }
+//! # };
+//! ```
+
+use crate::gpt::{micros_from_ticks, Capture, CaptureEdge, Captured, Edge};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures::stream::Stream;
+
+/// Returns `true` if `measured` is within 30% of `expected`
+fn near(measured: u32, expected: u32) -> bool {
+    let tolerance = expected * 3 / 10;
+    measured >= expected - tolerance && measured <= expected + tolerance
+}
+
+/// Times the edges on an infrared receiver input
+struct Edges {
+    capture: Capture,
+    clock_hz: u32,
+    last: u32,
+}
+
+impl Edges {
+    fn new(mut capture: Capture) -> Self {
+        let clock_hz = capture
+            .clock_hz()
+            .filter(|&hz| hz != 0)
+            .expect("GPT counter frequency is unknown; see GPT::with_clock");
+        capture.start(CaptureEdge::Both {
+            first: Edge::Falling,
+        });
+        Edges {
+            capture,
+            clock_hz,
+            last: 0,
+        }
+    }
+
+    /// Poll for the next edge
+    ///
+    /// Returns the edge, and the microseconds since the previous edge. Returns `None` if
+    /// the capture queue overflowed. The lost edges followed every queued edge, so the
+    /// queued edges are discarded too.
+    fn poll_edge(&mut self, cx: &mut Context<'_>) -> Poll<Option<(Edge, u32)>> {
+        let Captured { edge, ticks } = match self.capture.poll_capture(cx) {
+            Poll::Ready(captured) => captured,
+            Poll::Pending => return Poll::Pending,
+        };
+        if self.capture.overrun() {
+            while self.capture.try_next().is_some() {}
+            return Poll::Ready(None);
+        }
+        let interval = micros_from_ticks(ticks.wrapping_sub(self.last), self.clock_hz);
+        self.last = ticks;
+        Poll::Ready(Some((edge, interval)))
+    }
+
+    fn release(mut self) -> Capture {
+        self.capture.stop();
+        self.capture
+    }
+}
+
+/// A decoded NEC frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NECFrame {
+    /// The device address
+    ///
+    /// Extended NEC frames use all 16 bits. Otherwise, the address is 8 bits.
+    pub address: u16,
+    /// The command
+    pub command: u8,
+    /// `true` if this is a repeat code, sent while the button is held
+    ///
+    /// A repeat code repeats the previous frame's address and command.
+    pub repeat: bool,
+}
+
+/// Where the NEC decoder is in a frame, and the edge it expects next
+#[derive(Clone, Copy)]
+enum NECState {
+    /// Waiting for the leading mark to start
+    Idle,
+    /// Waiting for the 9ms leading mark to end
+    Leader,
+    /// Waiting for the space after the leading mark to end
+    Space,
+    /// Waiting for the repeat code's final mark to end
+    RepeatMark,
+    /// Waiting for the end of bit `bit`'s mark, or of its space
+    Bit { bits: u32, bit: u32, mark: bool },
+    /// Waiting for the frame's final mark to end
+    FinalMark { bits: u32 },
+}
+
+/// An NEC protocol decoder
+///
+/// `NEC` is a [`Stream`] of frames and repeat codes. A repeat code that doesn't follow
+/// a frame is skipped. The stream never ends.
+///
+/// See the [module-level documentation](crate::ir) for more information.
+pub struct NEC {
+    edges: Edges,
+    state: NECState,
+    previous: Option<NECFrame>,
+}
+
+impl NEC {
+    /// Create an NEC decoder for the receiver on the `input` capture
+    ///
+    /// The decoder converts GPT ticks to microseconds with the capture's
+    /// [`clock_hz`](Capture::clock_hz()). The decoder starts capturing edges now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capture's GPT counter frequency is unknown, or zero.
+    pub fn new(input: Capture) -> Self {
+        NEC {
+            edges: Edges::new(input),
+            state: NECState::Idle,
+            previous: None,
+        }
+    }
+
+    /// Stop capturing edges, and release the receiver input
+    pub fn release(self) -> Capture {
+        self.edges.release()
+    }
+
+    /// Advance the decoder by one edge, returning a frame once it decodes
+    fn on_edge(&mut self, edge: Edge, interval: u32) -> Option<NECFrame> {
+        let (state, frame) = match self.state {
+            NECState::Idle => (NECState::Idle, None),
+            NECState::Leader if near(interval, 9_000) => (NECState::Space, None),
+            NECState::Space if near(interval, 2_250) => (NECState::RepeatMark, None),
+            NECState::Space if near(interval, 4_500) => (
+                NECState::Bit {
+                    bits: 0,
+                    bit: 0,
+                    mark: true,
+                },
+                None,
+            ),
+            NECState::RepeatMark => (
+                NECState::Idle,
+                self.previous.map(|frame| NECFrame {
+                    repeat: true,
+                    ..frame
+                }),
+            ),
+            NECState::Bit {
+                bits,
+                bit,
+                mark: true,
+            } if near(interval, 562) => (
+                NECState::Bit {
+                    bits,
+                    bit,
+                    mark: false,
+                },
+                None,
+            ),
+            NECState::Bit {
+                bits,
+                bit,
+                mark: false,
+            } if near(interval, 562) || near(interval, 1_687) => {
+                let bits = if near(interval, 1_687) {
+                    bits | 1 << bit
+                } else {
+                    bits
+                };
+                if bit == 31 {
+                    (NECState::FinalMark { bits }, None)
+                } else {
+                    (
+                        NECState::Bit {
+                            bits,
+                            bit: bit + 1,
+                            mark: true,
+                        },
+                        None,
+                    )
+                }
+            }
+            NECState::FinalMark { bits } => (NECState::Idle, decode_nec(bits)),
+            _ => (NECState::Idle, None),
+        };
+        // Any falling edge outside of a frame may start the next frame
+        self.state = match state {
+            NECState::Idle if edge == Edge::Falling => NECState::Leader,
+            state => state,
+        };
+        frame
+    }
+}
+
+/// Decode the 32 bits of an NEC frame
+fn decode_nec(bits: u32) -> Option<NECFrame> {
+    let [address, address_inverse, command, command_inverse] = bits.to_le_bytes();
+    if command != !command_inverse {
+        return None;
+    }
+    let address = if address == !address_inverse {
+        u16::from(address)
+    } else {
+        bits as u16
+    };
+    Some(NECFrame {
+        address,
+        command,
+        repeat: false,
+    })
+}
+
+impl Stream for NEC {
+    type Item = NECFrame;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<NECFrame>> {
+        let this = self.get_mut();
+        loop {
+            let (edge, interval) = match this.edges.poll_edge(cx) {
+                Poll::Ready(Some(edge)) => edge,
+                Poll::Ready(None) => {
+                    // Edges were lost, so drop the frame
+                    this.state = NECState::Idle;
+                    continue;
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            if let Some(frame) = this.on_edge(edge, interval) {
+                if !frame.repeat {
+                    this.previous = Some(frame);
+                }
+                return Poll::Ready(Some(frame));
+            }
+        }
+    }
+}
+
+/// A decoded RC-5 frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RC5Frame {
+    /// The 5-bit device address
+    pub address: u8,
+    /// The 7-bit command, including the extended RC-5 field bit
+    pub command: u8,
+    /// `true` if the button was held since the previous frame
+    ///
+    /// The remote toggles a bit for each button press. The frame is a repeat if the
+    /// toggle bit, address, and command match the previous frame.
+    pub repeat: bool,
+}
+
+/// RC-5 half-bit time, in microseconds
+const RC5_HALF_BIT: u32 = 889;
+/// Two halves for each of the 14 bits: two start bits, one toggle bit, five address
+/// bits, and six command bits
+const RC5_HALF_BITS: usize = 28;
+
+/// An RC-5 protocol decoder
+///
+/// `RC5` is a [`Stream`] of frames. The stream never ends.
+///
+/// See the [module-level documentation](crate::ir) for more information.
+pub struct RC5 {
+    edges: Edges,
+    /// The half-bits received so far, `true` for a space. Only the first `len` are valid.
+    halves: [bool; RC5_HALF_BITS],
+    /// The number of half-bits received, or zero if the decoder is waiting for a frame
+    len: usize,
+    /// The previous frame, and its toggle bit
+    previous: Option<(RC5Frame, bool)>,
+}
+
+impl RC5 {
+    /// Create an RC-5 decoder for the receiver on the `input` capture
+    ///
+    /// The decoder converts GPT ticks to microseconds with the capture's
+    /// [`clock_hz`](Capture::clock_hz()). The decoder starts capturing edges now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capture's GPT counter frequency is unknown, or zero.
+    pub fn new(input: Capture) -> Self {
+        RC5 {
+            edges: Edges::new(input),
+            halves: [true; RC5_HALF_BITS],
+            len: 0,
+            previous: None,
+        }
+    }
+
+    /// Stop capturing edges, and release the receiver input
+    pub fn release(self) -> Capture {
+        self.edges.release()
+    }
+
+    /// Advance the decoder by one edge, returning a frame once it decodes
+    fn on_edge(&mut self, edge: Edge, interval: u32) -> Option<RC5Frame> {
+        if self.len > 0 && self.on_half_bits(interval) {
+            if self.is_complete() {
+                self.len = 0;
+                return self.decode();
+            }
+            return None;
+        }
+        // Any falling edge outside of a frame starts the first start bit's mark. That
+        // mark starts in the middle of the bit; the half-bit before the mark is
+        // indistinguishable from idle.
+        self.len = if edge == Edge::Falling { 1 } else { 0 };
+        self.halves[0] = true;
+        None
+    }
+
+    /// Record the half-bits that `interval` spans, returning `false` if it's invalid
+    fn on_half_bits(&mut self, interval: u32) -> bool {
+        let count = if near(interval, RC5_HALF_BIT) {
+            1
+        } else if near(interval, 2 * RC5_HALF_BIT) {
+            2
+        } else {
+            return false;
+        };
+        let len = self.len;
+        if len + count > RC5_HALF_BITS {
+            return false;
+        }
+        let level = !self.halves[len - 1];
+        for half in &mut self.halves[len..len + count] {
+            *half = level;
+        }
+        self.len += count;
+        true
+    }
+
+    /// Returns `true` once all half-bits are known
+    ///
+    /// A final mark is followed by idle, which never ends with an edge, so the last
+    /// half-bit is known once the mark starts.
+    fn is_complete(&mut self) -> bool {
+        if self.len == RC5_HALF_BITS - 1 && !self.halves[self.len - 1] {
+            self.halves[self.len] = true;
+            self.len += 1;
+        }
+        self.len == RC5_HALF_BITS
+    }
+
+    /// Decode a complete frame, returning `None` if the frame is invalid
+    fn decode(&mut self) -> Option<RC5Frame> {
+        // A one is a space, then a mark
+        let mut bits = 0u16;
+        for pair in self.halves.chunks(2) {
+            let bit = match (pair[0], pair[1]) {
+                (true, false) => 1,
+                (false, true) => 0,
+                _ => return None,
+            };
+            bits = (bits << 1) | bit;
+        }
+
+        let field = bits & (1 << 12) != 0;
+        let toggle = bits & (1 << 11) != 0;
+        let address = ((bits >> 6) & 0x1F) as u8;
+        let command = (bits & 0x3F) as u8 | if field { 0 } else { 1 << 6 };
+        let repeat = self.previous.map_or(false, |(previous, previous_toggle)| {
+            previous_toggle == toggle && previous.address == address && previous.command == command
+        });
+        let frame = RC5Frame {
+            address,
+            command,
+            repeat,
+        };
+        self.previous = Some((frame, toggle));
+        Some(frame)
+    }
+}
+
+impl Stream for RC5 {
+    type Item = RC5Frame;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<RC5Frame>> {
+        let this = self.get_mut();
+        loop {
+            let (edge, interval) = match this.edges.poll_edge(cx) {
+                Poll::Ready(Some(edge)) => edge,
+                Poll::Ready(None) => {
+                    // Edges were lost, so drop the frame
+                    this.len = 0;
+                    continue;
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            if let Some(frame) = this.on_edge(edge, interval) {
+                return Poll::Ready(Some(frame));
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Await any interrupt
+//!
+//! [`wait_for`] unmasks an interrupt, and completes once the interrupt fires. Use it
+//! to await a peripheral that this crate doesn't support. The interrupt is masked when
+//! it fires, so that a level-triggered interrupt doesn't fire again before your task
+//! handles it. Clear the peripheral's interrupt flag before waiting again.
+//!
+//! The crate handles awaited interrupts in the `DefaultHandler`, which runs for every
+//! interrupt that doesn't have its own handler. You can't await an interrupt that has a
+//! handler, including the interrupts that this crate's drivers handle.
+//!
+//! The `"irq"` feature is disabled by default, since it registers the `DefaultHandler`.
+//! With the `"no-isr"` feature, call [`isr::default_handler`](crate::isr::default_handler)
+//! from your own `DefaultHandler`.
+//!
+//! # Example
+//!
+//! Wait for the LPSPI3 to finish a transfer, using your own LPSPI3 setup.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::ral::{self, interrupt};
+//!
+//! let spi3 = ral::lpspi::LPSPI3::take().unwrap();
+//! // Configure the LPSPI3...
+//! ral::write_reg!(ral::lpspi, spi3, IER, TCIE: 1);
+//!
+//! # async {
+//! hal::irq::wait_for(interrupt::LPSPI3).await;
+//! ral::write_reg!(ral::lpspi, spi3, SR, TCF: 1);
+//! # };
+//! ```
+
+use crate::ral::Interrupt;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use cortex_m::interrupt::Nr;
+
+/// The number of interrupts that may be awaited at once
+pub const MAX_WAITS: usize = 8;
+
+/// Awaited interrupt numbers, and their waiting tasks
+///
+/// A `None` slot is free. The handler frees a slot when its interrupt fires.
+static mut SLOTS: [Option<(u8, Waker)>; MAX_WAITS] =
+    [None, None, None, None, None, None, None, None];
+
+/// Wait for `irq` to fire
+///
+/// `wait_for` clears `irq`'s pending flag, then unmasks it once the future is polled.
+/// When the interrupt fires, the interrupt is masked, and the future completes. If the
+/// future drops before the interrupt fires, the interrupt is masked.
+///
+/// # Panics
+///
+/// Panics if more than [`MAX_WAITS`] interrupts are awaited at once.
+pub fn wait_for(irq: Interrupt) -> WaitFor {
+    cortex_m::peripheral::NVIC::unpend(irq);
+    WaitFor {
+        irq,
+        registered: false,
+    }
+}
+
+/// A future that waits for an interrupt
+///
+/// Use [`wait_for`] to create this future.
+pub struct WaitFor {
+    irq: Interrupt,
+    registered: bool,
+}
+
+impl Future for WaitFor {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let nr = self.irq.nr();
+        let registered = self.registered;
+        let poll = cortex_m::interrupt::free(|_| unsafe {
+            let slot = SLOTS
+                .iter_mut()
+                .find(|slot| matches!(slot, Some((irq, _)) if *irq == nr));
+            match slot {
+                // The handler freed the slot, so the interrupt fired
+                None if registered => Poll::Ready(()),
+                Some(slot) => {
+                    *slot = Some((nr, cx.waker().clone()));
+                    Poll::Pending
+                }
+                None => {
+                    let slot = SLOTS
+                        .iter_mut()
+                        .find(|slot| slot.is_none())
+                        .expect("Too many interrupts awaited at once");
+                    *slot = Some((nr, cx.waker().clone()));
+                    cortex_m::peripheral::NVIC::unmask(self.irq);
+                    Poll::Pending
+                }
+            }
+        });
+        self.registered = true;
+        poll
+    }
+}
+
+impl Drop for WaitFor {
+    fn drop(&mut self) {
+        let nr = self.irq.nr();
+        cortex_m::peripheral::NVIC::mask(self.irq);
+        cortex_m::interrupt::free(|_| unsafe {
+            SLOTS
+                .iter_mut()
+                .filter(|slot| matches!(slot, Some((irq, _)) if *irq == nr))
+                .for_each(|slot| *slot = None);
+        });
+    }
+}
+
+#[cfg(not(feature = "no-isr"))]
+#[cfg_attr(all(target_arch = "arm", feature = "rt"), crate::rt::exception)]
+#[cfg_attr(
+    any(not(target_arch = "arm"), not(feature = "rt")),
+    allow(unused, non_snake_case)
+)]
+#[cfg_attr(feature = "itcm", link_section = ".itcm")]
+fn DefaultHandler(irqn: i16) {
+    on_interrupt(irqn);
+}
+
+#[inline(always)]
+pub(crate) fn on_interrupt(irqn: i16) {
+    // Exceptions have negative numbers; they're not awaited
+    if irqn < 0 {
+        return;
+    }
+    let nr = irqn as usize;
+    // Safety: atomic write to a NVIC clear-enable register. It only masks this interrupt.
+    unsafe {
+        let nvic = &*cortex_m::peripheral::NVIC::ptr();
+        nvic.icer[nr / 32].write(1 << (nr % 32));
+    }
+    cortex_m::interrupt::free(|_| unsafe {
+        SLOTS
+            .iter_mut()
+            .filter(|slot| matches!(slot, Some((irq, _)) if usize::from(*irq) == nr))
+            .filter_map(Option::take)
+            .for_each(|(_, waker)| waker.wake());
+    });
+}
@@ -0,0 +1,204 @@
+//! Interrupt entry points
+//!
+//! By default, the crate registers its interrupt handlers with `cortex-m-rt`. If
+//! you need to register the handlers yourself, enable the `"no-isr"` feature. The
+//! crate then doesn't register any interrupt or exception handlers. Instead, call
+//! the functions in this module from your own handlers.
+//!
+//! Each function handles the interrupt of the same name. For instance, call
+//! [`gpio1_combined_0_15`] from the `GPIO1_Combined_0_15` interrupt handler. Only
+//! call these functions from their interrupt handler.
+//!
+//! # Example
+//!
+//! Forward the LPI2C3 interrupt from your own handler.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//!
+//! // Registered as the LPI2C3 interrupt handler
+//! fn my_lpi2c3_handler() {
+//!     // Your work...
+//!     hal::isr::lpi2c3();
+//! }
+//! ```
+
+/// Defines safe functions that forward to crate interrupt handlers
+#[allow(unused_macros)]
+macro_rules! forward {
+    ($($(#[$attr:meta])* $name:ident => $handler:path;)*) => {
+        $(
+            $(#[$attr])*
+            #[inline(always)]
+            pub fn $name() {
+                // Safety: interrupt handlers only access their own
+                // peripheral's status, and their own wakers.
+                #[allow(unused_unsafe)]
+                unsafe { $handler() }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "gpio")]
+forward! {
+    /// GPIO1 pins 0 through 15
+    gpio1_combined_0_15 => crate::gpio::GPIO1_Combined_0_15;
+    /// GPIO1 pins 16 through 31
+    gpio1_combined_16_31 => crate::gpio::GPIO1_Combined_16_31;
+    /// GPIO2 pins 0 through 15
+    gpio2_combined_0_15 => crate::gpio::GPIO2_Combined_0_15;
+    /// GPIO2 pins 16 through 31
+    gpio2_combined_16_31 => crate::gpio::GPIO2_Combined_16_31;
+    /// GPIO3 pins 0 through 15
+    gpio3_combined_0_15 => crate::gpio::GPIO3_Combined_0_15;
+    /// GPIO3 pins 16 through 31
+    gpio3_combined_16_31 => crate::gpio::GPIO3_Combined_16_31;
+    /// GPIO4 pins 0 through 15
+    gpio4_combined_0_15 => crate::gpio::GPIO4_Combined_0_15;
+    /// GPIO4 pins 16 through 31
+    gpio4_combined_16_31 => crate::gpio::GPIO4_Combined_16_31;
+    /// GPIO5 pins 0 through 15
+    gpio5_combined_0_15 => crate::gpio::GPIO5_Combined_0_15;
+    /// GPIO5 pins 16 through 31
+    gpio5_combined_16_31 => crate::gpio::GPIO5_Combined_16_31;
+}
+
+#[cfg(feature = "gpt")]
+forward! {
+    /// GPT1
+    gpt1 => crate::gpt::GPT1;
+    /// GPT2
+    gpt2 => crate::gpt::GPT2;
+}
+
+#[cfg(feature = "pit")]
+forward! {
+    /// PIT
+    pit => crate::pit::PIT;
+}
+
+#[cfg(feature = "i2c")]
+forward! {
+    /// LPI2C1
+    lpi2c1 => crate::i2c::commands::LPI2C1;
+    /// LPI2C2
+    lpi2c2 => crate::i2c::commands::LPI2C2;
+    /// LPI2C3
+    #[cfg(feature = "imxrt1060")]
+    lpi2c3 => crate::i2c::commands::LPI2C3;
+    /// LPI2C4
+    #[cfg(feature = "imxrt1060")]
+    lpi2c4 => crate::i2c::commands::LPI2C4;
+}
+
+#[cfg(feature = "uart")]
+forward! {
+    /// LPUART1
+    lpuart1 => crate::uart::LPUART1;
+    /// LPUART2
+    lpuart2 => crate::uart::LPUART2;
+    /// LPUART3
+    lpuart3 => crate::uart::LPUART3;
+    /// LPUART4
+    lpuart4 => crate::uart::LPUART4;
+    /// LPUART5
+    #[cfg(feature = "imxrt1060")]
+    lpuart5 => crate::uart::LPUART5;
+    /// LPUART6
+    #[cfg(feature = "imxrt1060")]
+    lpuart6 => crate::uart::LPUART6;
+    /// LPUART7
+    #[cfg(feature = "imxrt1060")]
+    lpuart7 => crate::uart::LPUART7;
+    /// LPUART8
+    #[cfg(feature = "imxrt1060")]
+    lpuart8 => crate::uart::LPUART8;
+}
+
+#[cfg(feature = "systick")]
+forward! {
+    /// The SysTick exception
+    systick => crate::systick::on_exception;
+}
+
+#[cfg(all(any(feature = "spi", feature = "uart"), not(feature = "imxrt1010")))]
+forward! {
+    /// DMA channels 0 and 16
+    dma0_dma16 => crate::dma::DMA0_DMA16;
+    /// DMA channels 1 and 17
+    dma1_dma17 => crate::dma::DMA1_DMA17;
+    /// DMA channels 2 and 18
+    dma2_dma18 => crate::dma::DMA2_DMA18;
+    /// DMA channels 3 and 19
+    dma3_dma19 => crate::dma::DMA3_DMA19;
+    /// DMA channels 4 and 20
+    dma4_dma20 => crate::dma::DMA4_DMA20;
+    /// DMA channels 5 and 21
+    dma5_dma21 => crate::dma::DMA5_DMA21;
+    /// DMA channels 6 and 22
+    dma6_dma22 => crate::dma::DMA6_DMA22;
+    /// DMA channels 7 and 23
+    dma7_dma23 => crate::dma::DMA7_DMA23;
+    /// DMA channels 8 and 24
+    dma8_dma24 => crate::dma::DMA8_DMA24;
+    /// DMA channels 9 and 25
+    dma9_dma25 => crate::dma::DMA9_DMA25;
+    /// DMA channels 10 and 26
+    dma10_dma26 => crate::dma::DMA10_DMA26;
+    /// DMA channels 11 and 27
+    dma11_dma27 => crate::dma::DMA11_DMA27;
+    /// DMA channels 12 and 28
+    dma12_dma28 => crate::dma::DMA12_DMA28;
+    /// DMA channels 13 and 29
+    dma13_dma29 => crate::dma::DMA13_DMA29;
+    /// DMA channels 14 and 30
+    dma14_dma30 => crate::dma::DMA14_DMA30;
+    /// DMA channels 15 and 31
+    dma15_dma31 => crate::dma::DMA15_DMA31;
+}
+
+#[cfg(all(any(feature = "spi", feature = "uart"), feature = "imxrt1010"))]
+forward! {
+    /// DMA channel 0
+    dma0 => crate::dma::DMA0;
+    /// DMA channel 1
+    dma1 => crate::dma::DMA1;
+    /// DMA channel 2
+    dma2 => crate::dma::DMA2;
+    /// DMA channel 3
+    dma3 => crate::dma::DMA3;
+    /// DMA channel 4
+    dma4 => crate::dma::DMA4;
+    /// DMA channel 5
+    dma5 => crate::dma::DMA5;
+    /// DMA channel 6
+    dma6 => crate::dma::DMA6;
+    /// DMA channel 7
+    dma7 => crate::dma::DMA7;
+    /// DMA channel 8
+    dma8 => crate::dma::DMA8;
+    /// DMA channel 9
+    dma9 => crate::dma::DMA9;
+    /// DMA channel 10
+    dma10 => crate::dma::DMA10;
+    /// DMA channel 11
+    dma11 => crate::dma::DMA11;
+    /// DMA channel 12
+    dma12 => crate::dma::DMA12;
+    /// DMA channel 13
+    dma13 => crate::dma::DMA13;
+    /// DMA channel 14
+    dma14 => crate::dma::DMA14;
+    /// DMA channel 15
+    dma15 => crate::dma::DMA15;
+}
+
+/// Interrupts awaited with [`irq::wait_for`](crate::irq::wait_for)
+///
+/// Call from your `DefaultHandler` exception handler, with its `irqn` argument.
+#[cfg(feature = "irq")]
+#[inline(always)]
+pub fn default_handler(irqn: i16) {
+    crate::irq::on_interrupt(irqn);
+}
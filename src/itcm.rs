@@ -0,0 +1,61 @@
+//! Instruction TCM (ITCM) placement
+//!
+//! ITCM is tightly-coupled memory that the processor fetches instructions from without
+//! waiting on flash or the caches. With the `"itcm"` feature, this crate places its
+//! interrupt handlers in the `.itcm` link section. You may place your own functions
+//! in the same section:
+//!
+//! ```no_run
+//! #[link_section = ".itcm"]
+//! fn hot_path() { /* ... */ }
+//! ```
+//!
+//! Your linker script must place `.itcm` in ITCM, and load it from flash. It must also
+//! provide these symbols:
+//!
+//! | Symbol    | Description                                        |
+//! | --------- | -------------------------------------------------- |
+//! | `__sitcm` | Start of the `.itcm` section in ITCM               |
+//! | `__eitcm` | End of the `.itcm` section in ITCM                 |
+//! | `__siitcm`| Start of the `.itcm` section's load image in flash |
+//!
+//! Call [`init`] to copy the code into ITCM before any of that code runs. A good place
+//! is your `cortex-m-rt` `pre_init` function. See the Teensy 4 examples for a complete
+//! linker script.
+
+/// Copy the `.itcm` section from flash into ITCM
+///
+/// # Safety
+///
+/// Call `init` once, before calling any function in the `.itcm` section, and before
+/// unmasking any interrupt that this crate handles. The linker symbols must describe
+/// a valid `.itcm` section.
+///
+/// # Example
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+///
+/// #[cortex_m_rt::pre_init]
+/// unsafe fn pre_init() {
+///     hal::itcm::init();
+/// }
+/// ```
+pub unsafe fn init() {
+    extern "C" {
+        static mut __sitcm: u32;
+        static mut __eitcm: u32;
+        static __siitcm: u32;
+    }
+    let start = &mut __sitcm as *mut u32;
+    let end = &mut __eitcm as *mut u32;
+    let load = &__siitcm as *const u32;
+    let len = (end as usize - start as usize) / core::mem::size_of::<u32>();
+    // Volatile writes, so that the compiler cannot assume anything
+    // about memory that's not yet initialized.
+    for idx in 0..len {
+        core::ptr::write_volatile(start.add(idx), core::ptr::read(load.add(idx)));
+    }
+    cortex_m::asm::dsb();
+    cortex_m::asm::isb();
+}
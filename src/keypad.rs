@@ -0,0 +1,286 @@
+//! Key matrix scanning
+//!
+//! A [`Keypad`] scans a matrix of keys with GPIOs. Each row is a GPIO output, and each
+//! column is a GPIO input with a pull up. A key connects its row to its column. The
+//! keypad reports each debounced key press and release as an [`Event`].
+//!
+//! While no key is pressed, the keypad drives every row low, and waits for any column
+//! to go low. The task sleeps until someone presses a key. While a key is pressed, the
+//! keypad scans the matrix once every scan period, using a GPT.
+//!
+//! Enable the `"gpio"` and `"gpt"` features to use this module.
+//!
+//! # Debouncing and ghosting
+//!
+//! A key changes state once two consecutive scans agree. Choose a scan period that's
+//! longer than your keys' bounce time; 5ms to 10ms suits most keys.
+//!
+//! A matrix without diodes can't tell some combinations of three keys from four keys.
+//! When a scan shows two rows with two or more pressed keys in the same columns, the
+//! keypad ignores the scan, and keeps its previous key states.
+//!
+//! Make the rows open drain, so that two pressed keys in the same column can't short an
+//! output that's high to an output that's low.
+//!
+//! # Example
+//!
+//! Scan a 4x4 keypad every 10ms, using a 1MHz GPT.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{gpio::*, iomuxc::gpio::Pin, keypad::{Event, Keypad}, ral};
+//!
+//! fn row<P: Pin>(mut gpio: GPIO<P, Input>) -> ErasedPin<Output> {
+//!     gpio.set_open_drain(true);
+//!     gpio.output().erase()
+//! }
+//!
+//! fn column<P: Pin>(mut gpio: GPIO<P, Input>) -> ErasedPin<Input> {
+//!     gpio.set_pull(Pull::Up22k);
+//!     gpio.erase()
+//! }
+//!
+//! let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let rows = [
+//!     row(GPIO::new(pads.b0.p00)),
+//!     row(GPIO::new(pads.b0.p01)),
+//!     row(GPIO::new(pads.b0.p02)),
+//!     row(GPIO::new(pads.b0.p03)),
+//! ];
+//! let columns = [
+//!     column(GPIO::new(pads.b0.p10)),
+//!     column(GPIO::new(pads.b0.p11)),
+//!     column(GPIO::new(pads.b0.p12)),
+//!     column(GPIO::new(pads.b0.p13)),
+//! ];
+//! let mut keypad = Keypad::new(rows, columns, 10_000);
+//! let (mut gpt, _, _) = hal::GPT::new(ral::gpt::GPT2::take().unwrap());
+//!
+//! # async {
+//! loop {
+//!     match keypad.next(&mut gpt).await {
+//!         Event::Pressed { row, column } => { /* ... */ }
+//!         Event::Released { row, column } => { /* ... */ }
+//!     }
+//! }
+//! # };
+//! ```
+
+use crate::{
+    gpio::{self, ErasedPin, Input, Output, Trigger},
+    gpt::Delay,
+    GPT,
+};
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A key press or release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The key at `row` and `column` was pressed
+    Pressed {
+        /// Row index
+        row: usize,
+        /// Column index
+        column: usize,
+    },
+    /// The key at `row` and `column` was released
+    Released {
+        /// Row index
+        row: usize,
+        /// Column index
+        column: usize,
+    },
+}
+
+/// A matrix of `R` rows and `C` columns of keys
+///
+/// See the [module-level documentation](crate::keypad) for more information.
+pub struct Keypad<const R: usize, const C: usize> {
+    rows: [ErasedPin<Output>; R],
+    columns: [ErasedPin<Input>; C],
+    scan_ticks: u32,
+    /// The reported key states
+    keys: [[bool; C]; R],
+    /// The most recent scan that two consecutive scans agreed on
+    stable: [[bool; C]; R],
+}
+
+impl<const R: usize, const C: usize> Keypad<R, C> {
+    /// Create a keypad that drives the `rows`, and reads the `columns`
+    ///
+    /// `scan_ticks` is the number of GPT clock counts between scans. It should match the
+    /// GPT that you pass to [`next`](Keypad::next()). All keys start released.
+    pub fn new(
+        mut rows: [ErasedPin<Output>; R],
+        columns: [ErasedPin<Input>; C],
+        scan_ticks: u32,
+    ) -> Self {
+        rows.iter_mut().for_each(ErasedPin::clear);
+        Keypad {
+            rows,
+            columns,
+            scan_ticks,
+            keys: [[false; C]; R],
+            stable: [[false; C]; R],
+        }
+    }
+
+    /// Returns `true` if the key at `row` and `column` is pressed
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` or `column` is out of range.
+    pub fn is_pressed(&self, row: usize, column: usize) -> bool {
+        self.keys[row][column]
+    }
+
+    /// Wait for the next key press or release
+    ///
+    /// If several keys changed state in the same scan, each call returns one of the
+    /// changes.
+    pub fn next<'a>(&'a mut self, gpt: &'a mut GPT) -> Next<'a, R, C> {
+        Next {
+            keypad: self,
+            gpt,
+            state: State::Check,
+            _borrows: PhantomData,
+        }
+    }
+
+    /// Report the first key whose stable state differs from its reported state
+    fn take_change(&mut self) -> Option<Event> {
+        for (row, (keys, stable)) in self.keys.iter_mut().zip(self.stable.iter()).enumerate() {
+            for (column, (key, &pressed)) in keys.iter_mut().zip(stable.iter()).enumerate() {
+                if *key != pressed {
+                    *key = pressed;
+                    return Some(if pressed {
+                        Event::Pressed { row, column }
+                    } else {
+                        Event::Released { row, column }
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Read every key, then drive all rows low
+    fn scan(&mut self) -> [[bool; C]; R] {
+        let mut scan = [[false; C]; R];
+        for (active, keys) in scan.iter_mut().enumerate() {
+            for (row, pin) in self.rows.iter_mut().enumerate() {
+                if row == active {
+                    pin.clear();
+                } else {
+                    pin.set();
+                }
+            }
+            // Let the column settle through the pull up
+            cortex_m::asm::delay(100);
+            for (key, column) in keys.iter_mut().zip(self.columns.iter()) {
+                *key = !column.is_set();
+            }
+        }
+        self.rows.iter_mut().for_each(ErasedPin::clear);
+        scan
+    }
+}
+
+/// Returns `true` if two rows share two or more pressed columns
+fn is_ghosted<const R: usize, const C: usize>(scan: &[[bool; C]; R]) -> bool {
+    scan.iter().enumerate().any(|(row, a)| {
+        scan[row + 1..]
+            .iter()
+            .any(|b| a.iter().zip(b.iter()).filter(|(a, b)| **a && **b).count() >= 2)
+    })
+}
+
+/// A future that waits for the next key press or release
+///
+/// Use [`next`](Keypad::next()) to create this future.
+pub struct Next<'a, const R: usize, const C: usize> {
+    /// The keypad and GPT, borrowed for `'a`
+    ///
+    /// The futures in `state` borrow the keypad's columns, or the GPT.
+    keypad: *mut Keypad<R, C>,
+    gpt: *mut GPT,
+    state: State<'a, R, C>,
+    _borrows: PhantomData<(&'a mut Keypad<R, C>, &'a mut GPT)>,
+}
+
+/// The step of a [`Next`] future
+enum State<'a, const R: usize, const C: usize> {
+    /// Check for a change, then wait for a key or scan
+    Check,
+    /// Waiting for any key while all keys are released
+    Wait(gpio::WaitAny<'a>),
+    /// Waiting for the next scan, to compare it with `previous`
+    Scan {
+        previous: [[bool; C]; R],
+        delay: Delay<'a>,
+    },
+}
+
+impl<const R: usize, const C: usize> Future for Next<'_, R, C> {
+    type Output = Event;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Event> {
+        // Safety: the state's futures are never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        loop {
+            match &mut this.state {
+                State::Check => {
+                    // Safety: the state holds no borrow of the keypad.
+                    let keypad = unsafe { &mut *this.keypad };
+                    if let Some(event) = keypad.take_change() {
+                        return Poll::Ready(event);
+                    }
+                    let idle = keypad.keys.iter().flatten().all(|pressed| !pressed);
+                    if idle {
+                        this.state = State::Wait(gpio::wait_any(&mut keypad.columns, Trigger::Low));
+                    } else {
+                        this.start_scan(keypad.scan());
+                    }
+                }
+                State::Wait(wait) => {
+                    futures::ready!(Pin::new(wait).poll(cx));
+                    this.state = State::Check;
+                    // Safety: the wait, which borrowed the columns, is dropped.
+                    let previous = unsafe { &mut *this.keypad }.scan();
+                    this.start_scan(previous);
+                }
+                State::Scan { previous, delay } => {
+                    // Safety: the delay is never moved out of the state.
+                    futures::ready!(unsafe { Pin::new_unchecked(delay) }.poll(cx));
+                    let previous = *previous;
+                    this.state = State::Check;
+                    // Safety: the state holds no borrow of the keypad.
+                    let keypad = unsafe { &mut *this.keypad };
+                    let scan = keypad.scan();
+                    if scan == previous && !is_ghosted(&scan) {
+                        keypad.stable = scan;
+                    } else {
+                        this.start_scan(scan);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, const R: usize, const C: usize> Next<'a, R, C> {
+    /// Wait one scan period, then compare the next scan with `previous`
+    fn start_scan(&mut self, previous: [[bool; C]; R]) {
+        // Safety: the state holds no borrow of the GPT.
+        let keypad = unsafe { &*self.keypad };
+        let gpt = unsafe { &mut *self.gpt };
+        self.state = State::Scan {
+            previous,
+            delay: gpt.delay(keypad.scan_ticks),
+        };
+    }
+}
@@ -52,17 +52,50 @@
 //! the peripheral features from the table. The checkmarks indicate a chip's support for
 //! that peripheral.
 //!
-//! | **Chip**  | `"gpio"` | `"gpt"` | `"i2c"` | `"pit"` | `"spi"` | `"uart"` |
-//! | --------- | -------- | ------- | ------- | ------- | ------- | -------- |
-//! | imxrt1010 |    ✓     |    ✓    |    ✓    |    ✓    |    ✓    |     ✓    |
-//! | imxrt1060 |    ✓     |    ✓    |    ✓    |    ✓    |    ✓    |     ✓    |
+//! | **Chip**  | `"flash"` | `"gpio"` | `"gpt"` | `"i2c"` | `"pit"` | `"spi"` | `"uart"` |
+//! | --------- | --------- | -------- | ------- | ------- | ------- | ------- | -------- |
+//! | imxrt1010 |           |    ✓     |    ✓    |    ✓    |    ✓    |    ✓    |     ✓    |
+//! | imxrt1060 |     ✓     |    ✓     |    ✓    |    ✓    |    ✓    |    ✓    |     ✓    |
+//!
+//! The `"systick"` feature is disabled by default. It provides SysTick delays, and registers
+//! the `SysTick` exception handler.
+//!
+//! The `"irq"` feature is disabled by default. It lets you await any interrupt that
+//! doesn't have a handler, and it registers the `DefaultHandler` exception handler. See
+//! the `irq` module.
 //!
 //! When developing a binary for your embedded system, you should enable this crate's `"rt"`
 //! feature. Otherwise, when developing libraries against the crate, you may skip the
 //! `"rt"` feature.
 //!
+//! Enable the `"no-isr"` feature if you need to register interrupt handlers yourself. The
+//! crate then exports its interrupt handlers in the `isr` module, instead of registering
+//! them.
+//!
+//! Enable the `"itcm"` feature to place the crate's interrupt handlers in ITCM. See the
+//! `itcm` module for the linker script requirements.
+//!
+//! Enable the `"teensy4"` feature for Teensy 4.0 board support. It maps the board's pin
+//! numbers to pads, and it selects the `"imxrt1060"` chip. See the `teensy4` module.
+//!
+//! Enable the `"log"` feature for a [`log`](https://crates.io/crates/log) backend that
+//! writes over a UART, without blocking. See the `logging` module.
+//!
+//! Enable the `"embedded-hal"` feature to implement the
+//! [`embedded-hal`](https://crates.io/crates/embedded-hal) digital pin traits for GPIOs.
+//!
+//! Enable the `"diagnostics"` feature to inspect drivers by printing them. Each driver's
+//! `Debug` output decodes its peripheral's status registers and FIFO counts. Use
+//! `dma::channel_state` to inspect a DMA channel's transfer.
+//!
+//! Enable the `"bench"` feature to measure the CPU cycles spent in DMA setup, interrupt
+//! handlers, I2C FIFO transfers, and sleep. See the `bench` module for more information.
+//!
 //! # Examples
 //!
+//! The [`prelude`] brings the drivers, and the modules that you need to set them up,
+//! into scope.
+//!
 //! Each module contains a small getting started example to demonstrate driver setup and
 //! the driver API. For larger examples that run on hardware, see the `imxrt-async-hal`
 //! repository.
@@ -110,13 +143,16 @@ compile_error!(concat!(
 ))]
 macro_rules! interrupts {
     ($($handlers:item)*) => {
-        #[cfg(all(target_arch = "arm", feature = "rt"))]
+        #[cfg(all(target_arch = "arm", feature = "rt", not(feature = "no-isr")))]
         use crate::ral::interrupt;
         $($handlers)*
     };
 }
 
 /// Decorator helper for an interrupt handler
+///
+/// With the `"no-isr"` feature, the handler is not registered. Instead, it's visible
+/// to the crate, so that the `isr` module can forward to it.
 #[cfg(any(
     feature = "gpio",
     feature = "gpt",
@@ -125,40 +161,134 @@ macro_rules! interrupts {
     feature = "spi",
     feature = "uart",
 ))]
+#[cfg(not(feature = "no-isr"))]
 macro_rules! handler {
     (unsafe fn $isr_name:ident () $body:block) => {
         #[cfg_attr(all(target_arch = "arm", feature = "rt"), crate::rt::interrupt)]
         #[cfg_attr(any(not(target_arch = "arm"), not(feature = "rt")), allow(unused, non_snake_case))]
+        #[cfg_attr(feature = "itcm", link_section = ".itcm")]
         unsafe fn $isr_name() $body
     };
     (fn $isr_name:ident () $ body:block) => {
         #[cfg_attr(all(target_arch = "arm", feature = "rt"), crate::rt::interrupt)]
         #[cfg_attr(any(not(target_arch = "arm"), not(feature = "rt")), allow(unused, non_snake_case))]
+        #[cfg_attr(feature = "itcm", link_section = ".itcm")]
         fn $isr_name() $body
     };
 }
 
+#[cfg(any(
+    feature = "gpio",
+    feature = "gpt",
+    feature = "i2c",
+    feature = "pit",
+    feature = "spi",
+    feature = "uart",
+))]
+#[cfg(feature = "no-isr")]
+macro_rules! handler {
+    (unsafe fn $isr_name:ident () $body:block) => {
+        #[allow(non_snake_case)]
+        #[cfg_attr(feature = "itcm", link_section = ".itcm")]
+        pub(crate) unsafe fn $isr_name() $body
+    };
+    (fn $isr_name:ident () $ body:block) => {
+        #[allow(non_snake_case)]
+        #[cfg_attr(feature = "itcm", link_section = ".itcm")]
+        pub(crate) fn $isr_name() $body
+    };
+}
+
 //
 // Modules
 //
 
+#[cfg(feature = "bench")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bench")))]
+pub mod bench;
+pub mod blocking;
+#[cfg(any(
+    feature = "spi",
+    feature = "uart",
+    all(feature = "flash", feature = "imxrt1060")
+))]
+mod cache;
+pub mod ccm;
 #[cfg(any(feature = "spi", feature = "uart"))]
 #[cfg_attr(docsrs, doc(cfg(any(feature = "spi", feature = "uart"))))]
 pub mod dma;
+#[cfg(feature = "spi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub mod dshot;
+#[cfg(all(feature = "flash", feature = "imxrt1060"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "flash", feature = "imxrt1060"))))]
+pub mod flash;
+#[cfg(feature = "gpt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub mod freqmeter;
 #[cfg(feature = "gpio")]
 #[cfg_attr(docsrs, doc(cfg(feature = "gpio")))]
 pub mod gpio;
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub mod gpr;
 #[cfg(feature = "gpt")]
 pub mod gpt;
+#[cfg(all(feature = "gpio", feature = "gpt"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "gpio", feature = "gpt"))))]
+pub mod hcsr04;
 #[cfg(feature = "i2c")]
 pub mod i2c;
 pub mod instance;
+#[cfg(feature = "gpt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub mod ir;
+#[cfg(feature = "irq")]
+#[cfg_attr(docsrs, doc(cfg(feature = "irq")))]
+pub mod irq;
+#[cfg(feature = "no-isr")]
+#[cfg_attr(docsrs, doc(cfg(feature = "no-isr")))]
+pub mod isr;
+#[cfg(feature = "itcm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "itcm")))]
+pub mod itcm;
+#[cfg(all(feature = "gpio", feature = "gpt"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "gpio", feature = "gpt"))))]
+pub mod keypad;
+#[cfg(all(feature = "uart", feature = "gpt"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "uart", feature = "gpt"))))]
+pub mod lin;
+#[cfg(all(feature = "log", feature = "uart"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "log", feature = "uart"))))]
+pub mod logging;
+pub mod mpu;
+#[cfg(feature = "uart")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub mod onewire;
 #[cfg(feature = "pit")]
 pub mod pit;
+pub mod prelude;
+#[cfg(feature = "gpt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gpt")))]
+pub mod pwm_input;
+#[cfg(feature = "uart")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub mod sbus;
 #[cfg(feature = "spi")]
 mod spi;
+pub mod sync;
+#[cfg(feature = "systick")]
+#[cfg_attr(docsrs, doc(cfg(feature = "systick")))]
+pub mod systick;
+#[cfg(feature = "teensy4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "teensy4")))]
+pub mod teensy4;
+pub mod time;
 #[cfg(feature = "uart")]
 mod uart;
+#[cfg(feature = "spi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub mod ws2812;
 
 pub use imxrt_ral as ral;
 
@@ -168,6 +298,8 @@ use cortex_m_rt as rt;
 //
 // Module re-exports
 //
+#[cfg(feature = "spi")]
+pub use dshot::DSHOT;
 #[cfg(feature = "gpt")]
 pub use gpt::GPT;
 #[cfg(feature = "i2c")]
@@ -175,12 +307,23 @@ pub use i2c::{ClockSpeed as I2CClockSpeed, Error as I2CError, I2C};
 #[cfg(feature = "pit")]
 pub use pit::PIT;
 #[cfg(feature = "spi")]
-pub use spi::{Error as SPIError, Pins as SPIPins, SPI};
+pub use spi::{
+    ChipSelect as SPIChipSelect, Error as SPIError, NoChipSelect as SPINoChipSelect,
+    Pins as SPIPins, Transaction as SPITransaction, SPI,
+};
+#[cfg(feature = "systick")]
+pub use systick::SysTick;
 #[cfg(feature = "uart")]
-pub use uart::{Error as UARTError, UART};
+pub use uart::{
+    Error as UARTError, Flush as UARTFlush, Idle as UARTIdle, Parity as UARTParity,
+    ReadStatic as UARTReadStatic, Receiver as UARTReceiver, StopBits as UARTStopBits,
+    Transmitter as UARTTransmitter, UARTRx, UARTTx, UART,
+};
+#[cfg(feature = "spi")]
+pub use ws2812::WS2812;
 
 /// A `once` sentinel, since it doesn't exist in `core::sync`.
-#[cfg(any(feature = "gpio", feature = "i2c"))]
+#[cfg(any(feature = "gpio", feature = "i2c", feature = "uart"))]
 mod once {
     use core::sync::atomic::{AtomicBool, Ordering};
     pub struct Once(AtomicBool);
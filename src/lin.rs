@@ -0,0 +1,378 @@
+//! LIN bus master
+//!
+//! [`Master`] sends LIN 2.x frame headers on a UART. Each header is a 13-bit break,
+//! the sync byte, then the protected identifier (PID). After the header, the master
+//! either publishes the frame's response, or it receives the response from a slave.
+//!
+//! Connect the UART's TX and RX pins to a LIN transceiver. The transceiver echoes the
+//! master's bytes to RX, and `Master` checks the echo for bus conflicts. `Master` needs
+//! two DMA channels, one for each direction, and a GPT for response timeouts.
+//!
+//! Each frame starts by queueing the break. If the transmit FIFO is full, queueing
+//! the break busy-waits; the previous frame normally leaves the FIFO empty.
+//!
+//! Enable the `"uart"` and `"gpt"` features to use this module.
+//!
+//! # Schedules
+//!
+//! A LIN master runs a schedule table, sending one frame in each slot. Use a
+//! [`Periodic`](crate::gpt::Periodic) timer to start each slot.
+//!
+//! # Example
+//!
+//! Run a schedule with a 10ms slot, using 1MHz GPTs and a 19200 baud bus.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{dma, lin::{self, Checksum}, ral};
+//!
+//! const SOURCE_CLOCK_HZ: u32 = 24_000_000;
+//!
+//! let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let mut channels = dma::channels(
+//!     ral::dma0::DMA0::take().unwrap(),
+//!     ral::dmamux::DMAMUX::take().unwrap(),
+//! );
+//! let uart2 = ral::lpuart::LPUART2::take()
+//!     .and_then(hal::instance::uart)
+//!     .unwrap();
+//! let uart = hal::UART::new(uart2, pads.ad_b1.p02, pads.ad_b1.p03);
+//! let mut master = lin::Master::new(
+//!     uart,
+//!     channels[7].take().unwrap(),
+//!     channels[8].take().unwrap(),
+//!     19_200,
+//!     SOURCE_CLOCK_HZ,
+//! )
+//! .unwrap();
+//! let (mut slots, mut timeouts, _) = hal::GPT::new(ral::gpt::GPT2::take().unwrap());
+//!
+//! # async {
+//! let mut schedule = slots.periodic(10_000);
+//! loop {
+//!     schedule.tick().await;
+//!     master.write(0x10, &[0x01, 0x80], Checksum::Enhanced).await.unwrap();
+//!
+//!     schedule.tick().await;
+//!     let mut status = [0; 4];
+//!     master
+//!         .read(0x20, &mut status, Checksum::Enhanced, &mut timeouts, 5_000)
+//!         .await
+//!         .unwrap();
+//! }
+//! # };
+//! ```
+
+use crate::{dma, gpt::Delay, UARTError, UARTReceiver, UARTRx, UARTTransmitter, UARTTx, GPT, UART};
+use core::{
+    future::Future,
+    marker::{PhantomData, PhantomPinned},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The sync byte that follows the break
+const SYNC: u8 = 0x55;
+/// The largest frame response, in bytes
+pub const MAX_DATA_LEN: usize = 8;
+/// The largest frame identifier
+pub const MAX_ID: u8 = 0x3F;
+
+/// LIN checksum models
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// LIN 1.x checksum, over the data only
+    ///
+    /// Diagnostic frames 0x3C and 0x3D always use the classic checksum.
+    Classic,
+    /// LIN 2.x checksum, over the PID and the data
+    Enhanced,
+}
+
+/// Errors from the LIN master
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A DMA transfer failed
+    DMA(dma::Error),
+    /// The master didn't read back the bytes that it sent
+    ///
+    /// Another node may have sent at the same time, or the transceiver may not be
+    /// connected.
+    Echo,
+    /// No slave responded, or the response was incomplete, before the timeout
+    Timeout,
+    /// The response's checksum is incorrect
+    Checksum,
+}
+
+impl From<dma::Error> for Error {
+    fn from(error: dma::Error) -> Self {
+        Error::DMA(error)
+    }
+}
+
+/// Returns the protected identifier for a frame identifier
+///
+/// The PID is the 6-bit `id`, with two parity bits.
+///
+/// # Panics
+///
+/// Panics if `id` is greater than [`MAX_ID`].
+pub fn pid(id: u8) -> u8 {
+    assert!(id <= MAX_ID, "LIN frame identifier is out of range");
+    let bit = |n: u8| (id >> n) & 1;
+    let p0 = bit(0) ^ bit(1) ^ bit(2) ^ bit(4);
+    let p1 = !(bit(1) ^ bit(3) ^ bit(4) ^ bit(5)) & 1;
+    id | (p0 << 6) | (p1 << 7)
+}
+
+/// Compute the checksum of `data`, for the frame with `pid`
+pub fn checksum(model: Checksum, pid: u8, data: &[u8]) -> u8 {
+    let initial = match model {
+        Checksum::Classic => 0,
+        Checksum::Enhanced => u16::from(pid),
+    };
+    let sum = data.iter().fold(initial, |sum, &byte| {
+        let sum = sum + u16::from(byte);
+        // Add the carry back into the sum
+        (sum & 0xFF) + (sum >> 8)
+    });
+    !(sum as u8)
+}
+
+/// A LIN bus master
+///
+/// See the [module-level documentation](crate::lin) for more information.
+pub struct Master<TX, RX> {
+    tx: UARTTx<TX>,
+    rx: UARTRx<RX>,
+}
+
+impl<TX, RX> Master<TX, RX> {
+    /// Create a LIN master from a UART and two DMA channels
+    ///
+    /// `new` configures the UART for LIN at `baud`. `source_clock_hz` is the UART clock
+    /// root frequency. Returns an error if the UART cannot generate the baud rate from
+    /// the clock.
+    pub fn new(
+        mut uart: UART<TX, RX>,
+        tx_channel: dma::Channel,
+        rx_channel: dma::Channel,
+        baud: u32,
+        source_clock_hz: u32,
+    ) -> Result<Self, UARTError> {
+        uart.set_baud(baud, source_clock_hz)?;
+        uart.set_lin_break(true);
+        let (tx, rx) = uart.with_dma(tx_channel, rx_channel);
+        Ok(Master { tx, rx })
+    }
+
+    /// Release the UART and DMA channels
+    ///
+    /// The return is `(uart, tx_channel, rx_channel)`. The UART keeps its LIN
+    /// configuration.
+    pub fn release(self) -> (UART<TX, RX>, dma::Channel, dma::Channel) {
+        UART::join(self.tx, self.rx)
+    }
+
+    /// Send a frame header for `id`, then publish the `data` response
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is greater than [`MAX_ID`], or if `data` is longer than
+    /// [`MAX_DATA_LEN`].
+    pub fn write(&mut self, id: u8, data: &[u8], model: Checksum) -> WriteFrame<'_, TX, RX> {
+        assert!(data.len() <= MAX_DATA_LEN, "LIN response is too long");
+        let pid = pid(id);
+        let mut frame = [0; FRAME_LEN];
+        let len = data.len() + 3;
+        frame[0] = SYNC;
+        frame[1] = pid;
+        frame[2..len - 1].copy_from_slice(data);
+        frame[len - 1] = checksum(model, pid, data);
+        WriteFrame {
+            exchange: Exchange::new(self, frame, len, len),
+        }
+    }
+
+    /// Send a frame header for `id`, then receive the response into `data`
+    ///
+    /// The slave must send `data.len()` bytes, then the checksum, within `timeout_ticks`
+    /// GPT clock counts of the header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is greater than [`MAX_ID`], or if `data` is longer than
+    /// [`MAX_DATA_LEN`].
+    pub fn read<'a>(
+        &'a mut self,
+        id: u8,
+        data: &'a mut [u8],
+        model: Checksum,
+        gpt: &'a mut GPT,
+        timeout_ticks: u32,
+    ) -> ReadFrame<'a, TX, RX> {
+        assert!(data.len() <= MAX_DATA_LEN, "LIN response is too long");
+        let pid = pid(id);
+        let mut header = [0; FRAME_LEN];
+        header[0] = SYNC;
+        header[1] = pid;
+        // The header echo, the response, then the checksum
+        let len = data.len() + 3;
+        ReadFrame {
+            exchange: Exchange::new(self, header, 2, len),
+            timeout: gpt.delay(timeout_ticks),
+            data,
+            model,
+        }
+    }
+}
+
+/// The largest frame after the break: sync, PID, data, and checksum
+const FRAME_LEN: usize = MAX_DATA_LEN + 3;
+
+/// Sends a break and `output`, while reading `input_len` bytes into `input`
+///
+/// The DMA transfers borrow the buffers, so the exchange only starts once it's pinned.
+struct Exchange<'a, TX, RX> {
+    /// The master, borrowed for `'a`; the transfers borrow its halves
+    master: *mut Master<TX, RX>,
+    output: [u8; FRAME_LEN],
+    output_len: usize,
+    input: [u8; FRAME_LEN],
+    input_len: usize,
+    read: Option<dma::Rx<'a, UARTReceiver, u8>>,
+    write: Option<dma::Tx<'a, UARTTransmitter, u8>>,
+    read_result: Option<Result<(), dma::Error>>,
+    write_result: Option<Result<(), dma::Error>>,
+    started: bool,
+    _master: PhantomData<&'a mut Master<TX, RX>>,
+    _pin: PhantomPinned,
+}
+
+impl<'a, TX, RX> Exchange<'a, TX, RX> {
+    fn new(
+        master: &'a mut Master<TX, RX>,
+        output: [u8; FRAME_LEN],
+        output_len: usize,
+        input_len: usize,
+    ) -> Self {
+        Exchange {
+            master,
+            output,
+            output_len,
+            input: [0; FRAME_LEN],
+            input_len,
+            read: None,
+            write: None,
+            read_result: None,
+            write_result: None,
+            started: false,
+            _master: PhantomData,
+            _pin: PhantomPinned,
+        }
+    }
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        // Safety: the exchange is pinned, so the buffers don't move while the transfers
+        // borrow them. The transfers are never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        if !this.started {
+            this.started = true;
+            // Safety: the master and the buffers are only borrowed by the transfers.
+            let master = unsafe { &mut *this.master };
+            let input = unsafe { &mut *(&mut this.input[..this.input_len] as *mut [u8]) };
+            let output = unsafe { &*(&this.output[..this.output_len] as *const [u8]) };
+            // Drop any response bytes that arrived after an earlier timeout
+            master.rx.clear();
+            master.tx.send_break();
+            this.read = Some(master.rx.read(input));
+            this.write = Some(master.tx.write(output));
+        }
+        if let Some(read) = &mut this.read {
+            if let Poll::Ready(result) = unsafe { Pin::new_unchecked(read) }.poll(cx) {
+                this.read = None;
+                this.read_result = Some(result);
+            }
+        }
+        if let Some(write) = &mut this.write {
+            if let Poll::Ready(result) = unsafe { Pin::new_unchecked(write) }.poll(cx) {
+                this.write = None;
+                this.write_result = Some(result);
+            }
+        }
+        if this.read_result.is_none() || this.write_result.is_none() {
+            return Poll::Pending;
+        }
+        this.read_result.take().unwrap()?;
+        this.write_result.take().unwrap()?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// The bytes that the exchange sent
+    fn output(&self) -> &[u8] {
+        &self.output[..self.output_len]
+    }
+
+    /// The bytes that the exchange received
+    fn input(&self) -> &[u8] {
+        &self.input[..self.input_len]
+    }
+}
+
+/// A future that sends a frame header, then publishes the response
+///
+/// Use [`write`](Master::write()) to create this future.
+pub struct WriteFrame<'a, TX, RX> {
+    exchange: Exchange<'a, TX, RX>,
+}
+
+impl<TX, RX> Future for WriteFrame<'_, TX, RX> {
+    type Output = Result<(), Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the exchange is never moved out of `self`.
+        let mut exchange = unsafe { self.map_unchecked_mut(|this| &mut this.exchange) };
+        futures::ready!(exchange.as_mut().poll(cx))?;
+        if exchange.input() != exchange.output() {
+            return Poll::Ready(Err(Error::Echo));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A future that sends a frame header, then receives the response
+///
+/// Use [`read`](Master::read()) to create this future.
+pub struct ReadFrame<'a, TX, RX> {
+    exchange: Exchange<'a, TX, RX>,
+    timeout: Delay<'a>,
+    data: &'a mut [u8],
+    model: Checksum,
+}
+
+impl<TX, RX> Future for ReadFrame<'_, TX, RX> {
+    type Output = Result<(), Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the exchange and the timeout are never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let exchange = unsafe { Pin::new_unchecked(&mut this.exchange) };
+        if let Poll::Ready(result) = exchange.poll(cx) {
+            result?;
+            let header = this.exchange.output();
+            let input = this.exchange.input();
+            if input[..2] != *header {
+                return Poll::Ready(Err(Error::Echo));
+            }
+            let len = input.len();
+            let response = &input[2..len - 1];
+            if checksum(this.model, header[1], response) != input[len - 1] {
+                return Poll::Ready(Err(Error::Checksum));
+            }
+            this.data.copy_from_slice(response);
+            return Poll::Ready(Ok(()));
+        }
+        futures::ready!(unsafe { Pin::new_unchecked(&mut this.timeout) }.poll(cx));
+        Poll::Ready(Err(Error::Timeout))
+    }
+}
@@ -0,0 +1,219 @@
+//! A `log` backend that writes over a UART
+//!
+//! The logger formats each record into a ring buffer, and returns. It never waits
+//! for the UART. A separate task, [`drain`], uses DMA to write the buffered records
+//! to a UART. If the ring buffer is full, the logger drops the rest of the record,
+//! so logging never blocks a control loop.
+//!
+//! Enable the `"log"` feature to use this module.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//!
+//! async fn run<TX, RX>(uart: &mut hal::UART<TX, RX>, channel: &mut hal::dma::Channel) {
+//!     hal::logging::init(log::LevelFilter::Info).unwrap();
+//!
+//!     let control_loop = async {
+//!         loop {
+//!             log::info!("Control loop is running");
+//!             // ...
+//!         }
+//!     };
+//!     let logging = hal::logging::drain(uart, channel);
+//!     futures::future::join(control_loop, logging).await;
+//! }
+//! ```
+
+use core::{
+    fmt::{self, Write as _},
+    future::Future,
+    marker::{PhantomData, PhantomPinned},
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+/// Size of the ring buffer, in bytes
+pub const BUFFER_SIZE: usize = 1024;
+
+/// The largest formatted record, in bytes
+///
+/// Longer records are truncated.
+const RECORD_SIZE: usize = 128;
+
+/// The largest DMA write, in bytes
+const CHUNK_SIZE: usize = 128;
+
+struct Ring {
+    buffer: [u8; BUFFER_SIZE],
+    /// Next byte to drain
+    head: usize,
+    /// Number of buffered bytes
+    len: usize,
+    waker: Option<Waker>,
+}
+
+/// Only accessed in critical sections
+static mut RING: Ring = Ring {
+    buffer: [0; BUFFER_SIZE],
+    head: 0,
+    len: 0,
+    waker: None,
+};
+
+impl Ring {
+    /// Append as many `bytes` as fit
+    fn push(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter().take(BUFFER_SIZE - self.len) {
+            self.buffer[(self.head + self.len) % BUFFER_SIZE] = byte;
+            self.len += 1;
+        }
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Remove up to `chunk.len()` bytes, returning the number of bytes removed
+    fn pop(&mut self, chunk: &mut [u8]) -> usize {
+        let len = self.len.min(chunk.len());
+        for byte in &mut chunk[..len] {
+            *byte = self.buffer[self.head];
+            self.head = (self.head + 1) % BUFFER_SIZE;
+        }
+        self.len -= len;
+        len
+    }
+}
+
+/// A fixed-size buffer for formatting a record
+struct Record {
+    buffer: [u8; RECORD_SIZE],
+    len: usize,
+}
+
+impl fmt::Write for Record {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let bytes = s.as_bytes();
+        let len = bytes.len().min(RECORD_SIZE - self.len);
+        self.buffer[self.len..self.len + len].copy_from_slice(&bytes[..len]);
+        self.len += len;
+        if len < bytes.len() {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let mut formatted = Record {
+            buffer: [0; RECORD_SIZE],
+            len: 0,
+        };
+        // A truncated record is still worth logging.
+        let _ = write!(
+            formatted,
+            "[{} {}]: {}\r\n",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        // Safety: RING is only accessed in critical sections.
+        cortex_m::interrupt::free(|_| unsafe { RING.push(&formatted.buffer[..formatted.len]) });
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Logger = Logger;
+
+/// Install the logger, and set the maximum log level
+///
+/// Returns an error if another logger is already installed.
+pub fn init(level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Write buffered records to `uart`, using a DMA channel
+///
+/// Run `drain` concurrently with your other tasks. It never returns. If a DMA
+/// write fails, the records in that write are lost.
+pub fn drain<'a, TX, RX>(
+    uart: &'a mut crate::UART<TX, RX>,
+    channel: &'a mut crate::dma::Channel,
+) -> Drain<'a, TX, RX> {
+    Drain {
+        write: None,
+        uart,
+        channel,
+        chunk: [0; CHUNK_SIZE],
+        _borrows: PhantomData,
+        _pin: PhantomPinned,
+    }
+}
+
+/// A future that writes buffered records to a UART
+///
+/// Use [`drain`] to create this future. It never completes.
+pub struct Drain<'a, TX, RX> {
+    /// The DMA write of `chunk`, if one is in progress
+    write: Option<crate::dma::Tx<'a, crate::UART<TX, RX>, u8>>,
+    uart: *mut crate::UART<TX, RX>,
+    channel: *mut crate::dma::Channel,
+    chunk: [u8; CHUNK_SIZE],
+    _borrows: PhantomData<(&'a mut crate::UART<TX, RX>, &'a mut crate::dma::Channel)>,
+    _pin: PhantomPinned,
+}
+
+impl<TX, RX> Future for Drain<'_, TX, RX> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Safety: the future is pinned, so `chunk` doesn't move while the write borrows
+        // it. The write is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        loop {
+            if let Some(write) = &mut this.write {
+                // A failed write loses its records.
+                let _ = futures::ready!(unsafe { Pin::new_unchecked(write) }.poll(cx));
+                this.write = None;
+            }
+            let len = futures::ready!(poll_buffered(&mut this.chunk, cx));
+            // Safety: the UART and channel are borrowed for the future's lifetime, and
+            // only the write uses them. The previous write is dropped.
+            let (uart, channel, chunk) = unsafe {
+                (
+                    &mut *this.uart,
+                    &mut *this.channel,
+                    &*(&this.chunk as *const [u8; CHUNK_SIZE]),
+                )
+            };
+            this.write = Some(uart.dma_write(channel, &chunk[..len]));
+        }
+    }
+}
+
+/// Move buffered bytes into `chunk`, returning the number of bytes
+///
+/// Registers the waker if there are no buffered bytes.
+fn poll_buffered(chunk: &mut [u8], cx: &mut Context<'_>) -> Poll<usize> {
+    // Safety: RING is only accessed in critical sections.
+    cortex_m::interrupt::free(|_| unsafe {
+        match RING.pop(chunk) {
+            0 => {
+                RING.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            len => Poll::Ready(len),
+        }
+    })
+}
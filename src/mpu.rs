@@ -0,0 +1,245 @@
+//! Memory protection unit (MPU) configuration
+//!
+//! The Cortex-M7 MPU assigns memory attributes, like cache policy and access permissions,
+//! to regions of memory. Use a [`Builder`] to describe the regions that your system
+//! needs, then [`apply`](Builder::apply()) the regions to the MPU. The builder checks
+//! each region's size and alignment as you add it.
+//!
+//! Memory that's not covered by a region keeps the processor's default attributes.
+//! If two regions overlap, the region that you added last takes priority.
+//!
+//! # Example
+//!
+//! Make the last 32KiB of OCRAM non-cacheable for DMA buffers, prevent execution from
+//! SDRAM, and trap null pointer accesses.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::mpu::Builder;
+//!
+//! let mut mpu = cortex_m::Peripherals::take().unwrap().MPU;
+//! let builder = Builder::new()
+//!     .non_cacheable(0x2027_8000, 32 * 1024)
+//!     .and_then(|builder| builder.no_execute(0x8000_0000, 32 * 1024 * 1024))
+//!     .and_then(|builder| builder.null_trap(32))
+//!     .unwrap();
+//!
+//! unsafe { builder.apply(&mut mpu) };
+//! ```
+
+use cortex_m::{asm, peripheral::MPU};
+
+/// The number of MPU regions on i.MX RT processors
+pub const REGION_COUNT: usize = 16;
+
+/// The smallest MPU region, in bytes
+pub const MIN_REGION_SIZE: usize = 32;
+
+// MPU_CTRL
+const CTRL_ENABLE: u32 = 1 << 0;
+const CTRL_PRIVDEFENA: u32 = 1 << 2;
+
+// MPU_RASR
+const RASR_ENABLE: u32 = 1 << 0;
+const RASR_SIZE_OFFSET: u32 = 1;
+const RASR_B: u32 = 1 << 16;
+const RASR_C: u32 = 1 << 17;
+const RASR_S: u32 = 1 << 18;
+const RASR_TEX_OFFSET: u32 = 19;
+const RASR_AP_OFFSET: u32 = 24;
+const RASR_XN: u32 = 1 << 28;
+
+/// AP value for full access
+const AP_FULL_ACCESS: u32 = 0b011;
+/// AP value for no access
+const AP_NO_ACCESS: u32 = 0b000;
+
+/// Memory attributes for a region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attributes {
+    /// Normal, write-back cacheable memory
+    Cacheable,
+    /// Normal, shareable, non-cacheable memory
+    ///
+    /// Use this for DMA buffers that should never need cache maintenance.
+    NonCacheable,
+    /// Strongly-ordered memory, for peripheral registers
+    StronglyOrdered,
+    /// Any access faults
+    NoAccess,
+}
+
+impl Attributes {
+    /// Returns the TEX, S, C, B, and AP fields of RASR
+    fn rasr(self) -> u32 {
+        let full_access = AP_FULL_ACCESS << RASR_AP_OFFSET;
+        match self {
+            Attributes::Cacheable => (0b001 << RASR_TEX_OFFSET) | RASR_C | RASR_B | full_access,
+            Attributes::NonCacheable => (0b001 << RASR_TEX_OFFSET) | RASR_S | full_access,
+            Attributes::StronglyOrdered => full_access,
+            Attributes::NoAccess => AP_NO_ACCESS << RASR_AP_OFFSET,
+        }
+    }
+}
+
+/// An MPU region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// Start of the region
+    ///
+    /// The base address must be a multiple of `size`.
+    pub base: usize,
+    /// Size of the region, in bytes
+    ///
+    /// The size must be a power of two, and at least [`MIN_REGION_SIZE`].
+    pub size: usize,
+    /// Memory attributes
+    pub attributes: Attributes,
+    /// `true` if the processor may execute instructions from the region
+    pub execute: bool,
+}
+
+impl Region {
+    fn check(&self) -> Result<(), Error> {
+        if !self.size.is_power_of_two() || self.size < MIN_REGION_SIZE {
+            Err(Error::Size)
+        } else if self.base % self.size != 0 {
+            Err(Error::Alignment)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn rasr(&self) -> u32 {
+        // A region is 2^(SIZE + 1) bytes
+        let size = self.size.trailing_zeros() - 1;
+        let xn = if self.execute { 0 } else { RASR_XN };
+        self.attributes.rasr() | xn | (size << RASR_SIZE_OFFSET) | RASR_ENABLE
+    }
+}
+
+/// Errors when adding a region to a [`Builder`]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The region size is not a power of two, or it's smaller than [`MIN_REGION_SIZE`]
+    Size,
+    /// The region base address is not a multiple of the region size
+    Alignment,
+    /// The builder already has [`REGION_COUNT`] regions
+    TooManyRegions,
+}
+
+/// Builds an MPU configuration
+///
+/// See the [module-level documentation](crate::mpu) for more information.
+#[derive(Debug, Clone)]
+pub struct Builder {
+    regions: [Option<Region>; REGION_COUNT],
+    count: usize,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+impl Builder {
+    /// Create a builder without any regions
+    pub const fn new() -> Self {
+        Builder {
+            regions: [None; REGION_COUNT],
+            count: 0,
+        }
+    }
+
+    /// Add a region
+    ///
+    /// Returns an error if the region's size or alignment is invalid, or if there are
+    /// no more regions available.
+    pub fn region(mut self, region: Region) -> Result<Self, Error> {
+        region.check()?;
+        let slot = self
+            .regions
+            .get_mut(self.count)
+            .ok_or(Error::TooManyRegions)?;
+        *slot = Some(region);
+        self.count += 1;
+        Ok(self)
+    }
+
+    /// Add a non-cacheable, non-executable region for DMA buffers
+    pub fn non_cacheable(self, base: usize, size: usize) -> Result<Self, Error> {
+        self.region(Region {
+            base,
+            size,
+            attributes: Attributes::NonCacheable,
+            execute: false,
+        })
+    }
+
+    /// Add a cacheable region that does not allow execution
+    ///
+    /// Use this for external memory, like SDRAM, that only holds data.
+    pub fn no_execute(self, base: usize, size: usize) -> Result<Self, Error> {
+        self.region(Region {
+            base,
+            size,
+            attributes: Attributes::Cacheable,
+            execute: false,
+        })
+    }
+
+    /// Add a region at address zero that faults on any access
+    ///
+    /// Null pointer reads and writes will then fault.
+    /// On i.MX RT processors, ITCM starts at address zero, so make sure that
+    /// no code or data is placed in the first `size` bytes of ITCM.
+    pub fn null_trap(self, size: usize) -> Result<Self, Error> {
+        self.region(Region {
+            base: 0,
+            size,
+            attributes: Attributes::NoAccess,
+            execute: false,
+        })
+    }
+
+    /// Returns the regions, in the order that they were added
+    pub fn regions(&self) -> impl Iterator<Item = &Region> {
+        self.regions.iter().filter_map(Option::as_ref)
+    }
+
+    /// Write the regions to the MPU, and enable the MPU
+    ///
+    /// `apply` disables all other MPU regions. Memory that's not covered by a region
+    /// keeps the default memory map attributes.
+    ///
+    /// # Safety
+    ///
+    /// The new memory attributes take effect immediately. Make sure that the program
+    /// does not access memory in a way that the new attributes forbid. If you're changing
+    /// a cacheable region to non-cacheable, clean and invalidate the data cache first.
+    pub unsafe fn apply(&self, mpu: &mut MPU) {
+        asm::dmb();
+        mpu.ctrl.write(0);
+
+        for (number, region) in self.regions.iter().enumerate() {
+            mpu.rnr.write(number as u32);
+            match region {
+                Some(region) => {
+                    mpu.rbar.write(region.base as u32);
+                    mpu.rasr.write(region.rasr());
+                }
+                None => {
+                    mpu.rbar.write(0);
+                    mpu.rasr.write(0);
+                }
+            }
+        }
+
+        mpu.ctrl.write(CTRL_PRIVDEFENA | CTRL_ENABLE);
+        asm::dsb();
+        asm::isb();
+    }
+}
@@ -0,0 +1,639 @@
+//! 1-Wire bus master, using a UART
+//!
+//! [`OneWire`] generates 1-Wire time slots with UART frames. A reset pulse is a
+//! 9600 baud frame, and each bit is a 115200 baud frame. The UART receives its
+//! own frames, and the devices on the bus pull the line low to respond.
+//!
+//! Connect the UART's TX and RX pins to the 1-Wire bus, and pull up the bus. Configure
+//! the TX pad as open drain, so that the devices can pull the bus low. `OneWire` needs
+//! two DMA channels, one for each direction.
+//!
+//! Enable the `"uart"` feature to use this module.
+//!
+//! # Example
+//!
+//! Start a temperature conversion on all DS18B20 sensors, then read the scratchpad of
+//! each sensor on the bus.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{dma, onewire::{self, OneWire}, ral};
+//!
+//! const SOURCE_CLOCK_HZ: u32 = 24_000_000;
+//!
+//! let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let mut channels = dma::channels(
+//!     ral::dma0::DMA0::take().unwrap(),
+//!     ral::dmamux::DMAMUX::take().unwrap(),
+//! );
+//! let uart2 = ral::lpuart::LPUART2::take()
+//!     .and_then(hal::instance::uart)
+//!     .unwrap();
+//! let uart = hal::UART::new(uart2, pads.ad_b1.p02, pads.ad_b1.p03);
+//! let mut bus = OneWire::new(
+//!     uart,
+//!     channels[7].take().unwrap(),
+//!     channels[8].take().unwrap(),
+//!     SOURCE_CLOCK_HZ,
+//! )
+//! .unwrap();
+//!
+//! # async {
+//! bus.reset().await.unwrap();
+//! bus.write(&[onewire::SKIP_ROM, 0x44 /* Convert T */]).await.unwrap();
+//! // Wait for the conversion...
+//!
+//! let mut search = onewire::Search::new();
+//! while let Some(rom) = bus.search(&mut search).await.unwrap() {
+//!     bus.reset().await.unwrap();
+//!     bus.select(rom).await.unwrap();
+//!     bus.write(&[0xBE /* Read scratchpad */]).await.unwrap();
+//!     let mut scratchpad = [0; 9];
+//!     bus.read(&mut scratchpad).await.unwrap();
+//!     assert_eq!(onewire::crc8(&scratchpad[..8]), scratchpad[8]);
+//! }
+//! # };
+//! ```
+
+use crate::{dma, UARTError, UARTReceiver, UARTRx, UARTTransmitter, UARTTx, UART};
+use core::{
+    future::Future,
+    marker::{PhantomData, PhantomPinned},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Address one device, using its ROM code
+pub const MATCH_ROM: u8 = 0x55;
+/// Read the ROM code of the only device on the bus
+pub const READ_ROM: u8 = 0x33;
+/// Search for the ROM codes of all devices on the bus
+pub const SEARCH_ROM: u8 = 0xF0;
+/// Address all devices on the bus
+pub const SKIP_ROM: u8 = 0xCC;
+
+/// The baud rate for a reset pulse, and presence detection
+const RESET_BAUD: u32 = 9600;
+/// The reset frame holds the bus low for about 500us
+const RESET_FRAME: u8 = 0xF0;
+/// The baud rate for bit time slots
+const BIT_BAUD: u32 = 115_200;
+/// Holds the bus low for one bit time, then releases it
+const WRITE_1: u8 = 0xFF;
+/// Holds the bus low for the whole frame
+const WRITE_0: u8 = 0x00;
+
+/// Errors from the 1-Wire bus
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The UART could not select a baud rate
+    UART(UARTError),
+    /// A DMA transfer failed
+    DMA(dma::Error),
+    /// The UART received a reset frame that the bus can't produce
+    ///
+    /// Make sure that TX and RX are connected, and that the bus is pulled up.
+    BusShorted,
+}
+
+impl From<UARTError> for Error {
+    fn from(error: UARTError) -> Self {
+        Error::UART(error)
+    }
+}
+
+impl From<dma::Error> for Error {
+    fn from(error: dma::Error) -> Self {
+        Error::DMA(error)
+    }
+}
+
+/// A 1-Wire bus master
+///
+/// See the [module-level documentation](crate::onewire) for more information.
+pub struct OneWire<TX, RX> {
+    /// Always `Some`, except while changing the baud rate
+    halves: Option<(UARTTx<TX>, UARTRx<RX>)>,
+    source_clock_hz: u32,
+}
+
+impl<TX, RX> OneWire<TX, RX> {
+    /// Create a 1-Wire bus master from a UART and two DMA channels
+    ///
+    /// `source_clock_hz` is the UART clock root frequency. Returns an error if the UART
+    /// cannot generate the 1-Wire baud rates from the clock.
+    pub fn new(
+        mut uart: UART<TX, RX>,
+        tx_channel: dma::Channel,
+        rx_channel: dma::Channel,
+        source_clock_hz: u32,
+    ) -> Result<Self, UARTError> {
+        uart.set_baud(RESET_BAUD, source_clock_hz)?;
+        uart.set_baud(BIT_BAUD, source_clock_hz)?;
+        Ok(OneWire {
+            halves: Some(uart.with_dma(tx_channel, rx_channel)),
+            source_clock_hz,
+        })
+    }
+
+    /// Release the UART and DMA channels
+    ///
+    /// The return is `(uart, tx_channel, rx_channel)`.
+    pub fn release(mut self) -> (UART<TX, RX>, dma::Channel, dma::Channel) {
+        let (tx, rx) = self.halves.take().unwrap();
+        UART::join(tx, rx)
+    }
+
+    /// Reset the bus, and returns `true` if one or more devices are present
+    pub fn reset(&mut self) -> Reset<'_, TX, RX> {
+        Reset {
+            exchange: Exchange::new(self),
+            started: false,
+        }
+    }
+
+    /// Write `bytes` to the bus, least-significant bit first
+    pub fn write<'a>(&'a mut self, bytes: &'a [u8]) -> Write<'a, TX, RX> {
+        Write {
+            exchange: Exchange::new(self),
+            bytes: Bytes::Borrowed(bytes),
+            index: 0,
+            started: false,
+        }
+    }
+
+    /// Read bytes from the bus into `buffer`
+    pub fn read<'a>(&'a mut self, buffer: &'a mut [u8]) -> Read<'a, TX, RX> {
+        Read {
+            exchange: Exchange::new(self),
+            buffer,
+            index: 0,
+            started: false,
+        }
+    }
+
+    /// Address the device with the `rom` code
+    ///
+    /// Call `select` after a [`reset`](OneWire::reset()). The next command only
+    /// affects the selected device.
+    pub fn select(&mut self, rom: u64) -> Write<'_, TX, RX> {
+        let mut bytes = [MATCH_ROM; 9];
+        bytes[1..].copy_from_slice(&rom.to_le_bytes());
+        Write {
+            exchange: Exchange::new(self),
+            bytes: Bytes::Select(bytes),
+            index: 0,
+            started: false,
+        }
+    }
+
+    /// Find the next device on the bus, returning its ROM code
+    ///
+    /// Returns `None` once the search has found every device. The search resets the bus
+    /// before each device.
+    pub fn search<'a>(&'a mut self, search: &'a mut Search) -> SearchNext<'a, TX, RX> {
+        SearchNext {
+            exchange: Exchange::new(self),
+            search,
+            step: Step::Start,
+            last_zero: None,
+        }
+    }
+
+    fn set_baud(&mut self, baud: u32) -> Result<(), UARTError> {
+        let (tx, rx) = self.halves.take().unwrap();
+        let (mut uart, tx_channel, rx_channel) = UART::join(tx, rx);
+        let result = uart.set_baud(baud, self.source_clock_hz);
+        self.halves = Some(uart.with_dma(tx_channel, rx_channel));
+        result
+    }
+}
+
+/// Sends UART frames while receiving the bus's frames
+///
+/// The DMA transfers borrow the frame buffers, so an exchange only starts once it's
+/// pinned. If an exchange is dropped during a reset, it restores the bit baud rate.
+struct Exchange<'a, TX, RX> {
+    /// The bus, borrowed for `'a`; the transfers borrow its UART halves
+    bus: *mut OneWire<TX, RX>,
+    output: [u8; 8],
+    input: [u8; 8],
+    len: usize,
+    read: Option<dma::Rx<'a, UARTReceiver, u8>>,
+    write: Option<dma::Tx<'a, UARTTransmitter, u8>>,
+    read_result: Option<Result<(), dma::Error>>,
+    write_result: Option<Result<(), dma::Error>>,
+    /// The exchange is a reset frame, at the reset baud rate
+    resetting: bool,
+    _bus: PhantomData<&'a mut OneWire<TX, RX>>,
+    _pin: PhantomPinned,
+}
+
+impl<'a, TX, RX> Exchange<'a, TX, RX> {
+    fn new(bus: &'a mut OneWire<TX, RX>) -> Self {
+        Exchange {
+            bus,
+            output: [0; 8],
+            input: [0; 8],
+            len: 0,
+            read: None,
+            write: None,
+            read_result: None,
+            write_result: None,
+            resetting: false,
+            _bus: PhantomData,
+            _pin: PhantomPinned,
+        }
+    }
+
+    /// Start sending the `output` frames, and receiving the same number of frames
+    fn start(self: Pin<&mut Self>, output: &[u8]) {
+        // Safety: the exchange is pinned, so the buffers don't move while the transfers
+        // borrow them. The previous transfers are dropped in place.
+        let this = unsafe { self.get_unchecked_mut() };
+        this.read = None;
+        this.write = None;
+        this.read_result = None;
+        this.write_result = None;
+        this.len = output.len();
+        this.output[..this.len].copy_from_slice(output);
+        // Safety: only the transfers borrow the bus and the buffers.
+        let (tx, rx) = unsafe { &mut *this.bus }.halves.as_mut().unwrap();
+        let input = unsafe { &mut *(&mut this.input[..this.len] as *mut [u8]) };
+        let output = unsafe { &*(&this.output[..this.len] as *const [u8]) };
+        this.read = Some(rx.read(input));
+        this.write = Some(tx.write(output));
+    }
+
+    /// Start a reset pulse, and presence detection
+    fn start_reset(mut self: Pin<&mut Self>) -> Result<(), Error> {
+        // Safety: no transfers borrow the bus.
+        let this = unsafe { self.as_mut().get_unchecked_mut() };
+        this.stop();
+        unsafe { &mut *this.bus }.set_baud(RESET_BAUD)?;
+        this.resetting = true;
+        self.start(&[RESET_FRAME]);
+        Ok(())
+    }
+
+    /// Start the eight time slots of `byte`, least-significant bit first
+    fn start_byte(self: Pin<&mut Self>, byte: u8) {
+        let mut output = [WRITE_0; 8];
+        for (bit, frame) in output.iter_mut().enumerate() {
+            if byte & (1 << bit) != 0 {
+                *frame = WRITE_1;
+            }
+        }
+        self.start(&output);
+    }
+
+    /// Start one time slot
+    fn start_bit(self: Pin<&mut Self>, bit: bool) {
+        self.start(&[if bit { WRITE_1 } else { WRITE_0 }]);
+    }
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        // Safety: the transfers are never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        // Poll the read first, so that it's ready for the first frame
+        if let Some(read) = &mut this.read {
+            if let Poll::Ready(result) = unsafe { Pin::new_unchecked(read) }.poll(cx) {
+                this.read = None;
+                this.read_result = Some(result);
+            }
+        }
+        if let Some(write) = &mut this.write {
+            if let Poll::Ready(result) = unsafe { Pin::new_unchecked(write) }.poll(cx) {
+                this.write = None;
+                this.write_result = Some(result);
+            }
+        }
+        if this.read.is_some() || this.write.is_some() {
+            return Poll::Pending;
+        }
+        let read = this
+            .read_result
+            .take()
+            .expect("exchange polled before start");
+        let write = this
+            .write_result
+            .take()
+            .expect("exchange polled before start");
+        if this.resetting {
+            this.resetting = false;
+            // Safety: no transfers borrow the bus.
+            unsafe { &mut *this.bus }.set_baud(BIT_BAUD)?;
+        }
+        read?;
+        write?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Drop any transfers in progress
+    fn stop(&mut self) {
+        self.read = None;
+        self.write = None;
+    }
+
+    /// Returns `true` if a device responded to the reset pulse
+    fn presence(&self) -> Result<bool, Error> {
+        // A presence pulse pulls the bus low after the UART releases it
+        match self.input[0] {
+            RESET_FRAME => Ok(false),
+            frame if frame & !RESET_FRAME == 0 => Ok(true),
+            _ => Err(Error::BusShorted),
+        }
+    }
+
+    /// The byte read in the last eight time slots
+    fn byte(&self) -> u8 {
+        let mut byte = 0;
+        for (bit, &frame) in self.input.iter().enumerate() {
+            if frame == WRITE_1 {
+                byte |= 1 << bit;
+            }
+        }
+        byte
+    }
+
+    /// The bit read in the last time slot
+    fn bit(&self) -> bool {
+        self.input[0] == WRITE_1
+    }
+}
+
+impl<TX, RX> Drop for Exchange<'_, TX, RX> {
+    fn drop(&mut self) {
+        self.stop();
+        if self.resetting {
+            // Safety: no transfers borrow the bus.
+            let _ = unsafe { &mut *self.bus }.set_baud(BIT_BAUD);
+        }
+    }
+}
+
+/// A future that resets the bus
+///
+/// Use [`reset`](OneWire::reset()) to create this future. It resolves to `true` if one
+/// or more devices are present.
+pub struct Reset<'a, TX, RX> {
+    exchange: Exchange<'a, TX, RX>,
+    started: bool,
+}
+
+impl<TX, RX> Future for Reset<'_, TX, RX> {
+    type Output = Result<bool, Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the exchange is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut exchange = unsafe { Pin::new_unchecked(&mut this.exchange) };
+        if !this.started {
+            this.started = true;
+            exchange.as_mut().start_reset()?;
+        }
+        futures::ready!(exchange.as_mut().poll(cx))?;
+        Poll::Ready(exchange.presence())
+    }
+}
+
+/// The bytes of a [`Write`]
+enum Bytes<'a> {
+    Borrowed(&'a [u8]),
+    /// The match ROM command, and the ROM code
+    Select([u8; 9]),
+}
+
+impl Bytes<'_> {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Bytes::Borrowed(bytes) => bytes,
+            Bytes::Select(bytes) => bytes,
+        }
+    }
+}
+
+/// A future that writes bytes to the bus
+///
+/// Use [`write`](OneWire::write()) or [`select`](OneWire::select()) to create this
+/// future.
+pub struct Write<'a, TX, RX> {
+    exchange: Exchange<'a, TX, RX>,
+    bytes: Bytes<'a>,
+    /// The byte that the exchange is writing
+    index: usize,
+    started: bool,
+}
+
+impl<TX, RX> Future for Write<'_, TX, RX> {
+    type Output = Result<(), Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the exchange is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut exchange = unsafe { Pin::new_unchecked(&mut this.exchange) };
+        loop {
+            if this.started {
+                futures::ready!(exchange.as_mut().poll(cx))?;
+                this.index += 1;
+            }
+            let bytes = this.bytes.as_slice();
+            if this.index >= bytes.len() {
+                return Poll::Ready(Ok(()));
+            }
+            exchange.as_mut().start_byte(bytes[this.index]);
+            this.started = true;
+        }
+    }
+}
+
+/// A future that reads bytes from the bus
+///
+/// Use [`read`](OneWire::read()) to create this future.
+pub struct Read<'a, TX, RX> {
+    exchange: Exchange<'a, TX, RX>,
+    buffer: &'a mut [u8],
+    /// The byte that the exchange is reading
+    index: usize,
+    started: bool,
+}
+
+impl<TX, RX> Future for Read<'_, TX, RX> {
+    type Output = Result<(), Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the exchange is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut exchange = unsafe { Pin::new_unchecked(&mut this.exchange) };
+        loop {
+            if this.started {
+                futures::ready!(exchange.as_mut().poll(cx))?;
+                this.buffer[this.index] = exchange.byte();
+                this.index += 1;
+            }
+            if this.index >= this.buffer.len() {
+                return Poll::Ready(Ok(()));
+            }
+            exchange.as_mut().start_byte(0xFF);
+            this.started = true;
+        }
+    }
+}
+
+/// The step of a [`SearchNext`] future
+#[derive(Clone, Copy)]
+enum Step {
+    Start,
+    /// Resetting the bus
+    Reset,
+    /// Sending the search command
+    Command,
+    /// Reading the ROM code bit
+    Id {
+        bit: u32,
+    },
+    /// Reading the complement of the ROM code bit
+    Complement {
+        bit: u32,
+        id: bool,
+    },
+    /// Writing the search direction
+    Direction {
+        bit: u32,
+    },
+}
+
+/// A future that finds the next device on the bus
+///
+/// Use [`search`](OneWire::search()) to create this future.
+pub struct SearchNext<'a, TX, RX> {
+    exchange: Exchange<'a, TX, RX>,
+    search: &'a mut Search,
+    step: Step,
+    /// The last bit where the search took the zero path at a discrepancy
+    last_zero: Option<u32>,
+}
+
+impl<TX, RX> Future for SearchNext<'_, TX, RX> {
+    type Output = Result<Option<u64>, Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the exchange is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut exchange = unsafe { Pin::new_unchecked(&mut this.exchange) };
+        let search = &mut *this.search;
+        loop {
+            if let Step::Start = this.step {
+                if search.done {
+                    return Poll::Ready(Ok(None));
+                }
+                exchange.as_mut().start_reset()?;
+                this.step = Step::Reset;
+                continue;
+            }
+            futures::ready!(exchange.as_mut().poll(cx))?;
+            this.step = match this.step {
+                Step::Start => unreachable!(),
+                Step::Reset => {
+                    if !exchange.presence()? {
+                        return Poll::Ready(Ok(None));
+                    }
+                    exchange.as_mut().start_byte(SEARCH_ROM);
+                    Step::Command
+                }
+                Step::Command => {
+                    exchange.as_mut().start_bit(true);
+                    Step::Id { bit: 0 }
+                }
+                Step::Id { bit } => {
+                    let id = exchange.bit();
+                    exchange.as_mut().start_bit(true);
+                    Step::Complement { bit, id }
+                }
+                Step::Complement { bit, id } => {
+                    let direction = match (id, exchange.bit()) {
+                        // A device left the bus
+                        (true, true) => {
+                            *search = Search::new();
+                            return Poll::Ready(Ok(None));
+                        }
+                        (false, false) => {
+                            let direction = if bit < search.last_discrepancy {
+                                search.rom & (1 << bit) != 0
+                            } else {
+                                bit == search.last_discrepancy
+                            };
+                            if !direction {
+                                this.last_zero = Some(bit);
+                            }
+                            direction
+                        }
+                        (id, _) => id,
+                    };
+                    if direction {
+                        search.rom |= 1 << bit;
+                    } else {
+                        search.rom &= !(1 << bit);
+                    }
+                    exchange.as_mut().start_bit(direction);
+                    Step::Direction { bit }
+                }
+                Step::Direction { bit } if bit < 63 => {
+                    exchange.as_mut().start_bit(true);
+                    Step::Id { bit: bit + 1 }
+                }
+                Step::Direction { .. } => {
+                    match this.last_zero {
+                        Some(bit) => search.last_discrepancy = bit,
+                        None => search.done = true,
+                    }
+                    if crc8(&search.rom.to_le_bytes()) != 0 {
+                        *search = Search::new();
+                        return Poll::Ready(Ok(None));
+                    }
+                    return Poll::Ready(Ok(Some(search.rom)));
+                }
+            };
+        }
+    }
+}
+
+/// The state of a ROM search
+///
+/// Use a new `Search` to start a search. See [`OneWire::search`](OneWire::search())
+/// for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Search {
+    rom: u64,
+    last_discrepancy: u32,
+    done: bool,
+}
+
+impl Search {
+    /// Start a new search
+    pub const fn new() -> Self {
+        Search {
+            rom: 0,
+            last_discrepancy: 64,
+            done: false,
+        }
+    }
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Search::new()
+    }
+}
+
+/// Compute the Dallas / Maxim CRC-8 of `bytes`
+///
+/// The CRC of a ROM code, or of a scratchpad that includes its CRC, is zero.
+pub fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0x8C
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
@@ -47,9 +47,28 @@ pub struct PIT {
     channel: register::ChannelInstance,
 }
 
+#[cfg(feature = "diagnostics")]
+impl core::fmt::Debug for PIT {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (ten, tie) = ral::read_reg!(register, self.channel, TCTRL, TEN, TIE);
+        f.debug_struct("PIT")
+            .field("channel", &self.channel.index())
+            .field("enabled", &(ten != 0))
+            .field("interrupt_enabled", &(tie != 0))
+            .field("load", &ral::read_reg!(register, self.channel, LDVAL))
+            .field("current", &ral::read_reg!(register, self.channel, CVAL))
+            .field(
+                "flag",
+                &ral::read_reg!(register, self.channel, TFLG, TIF == 1),
+            )
+            .finish()
+    }
+}
+
 impl PIT {
     /// Acquire four PIT channels from the RAL's PIT instance
     pub fn new(pit: ral::pit::Instance) -> (PIT, PIT, PIT, PIT) {
+        crate::ccm::assert_gates_on(crate::ccm::Peripheral::PIT);
         ral::write_reg!(ral::pit, pit, MCR, MDIS: MDIS_0);
         // Reset all PIT channels
         //
@@ -78,6 +97,33 @@ impl PIT {
             )
         }
     }
+
+    /// Acquire four PIT channels that use the current PIT configuration
+    ///
+    /// Unlike [`new`](PIT::new()), `steal` does not reset the channels.
+    ///
+    /// # Safety
+    ///
+    /// The original driver may still exist, and it may be in the middle of an operation.
+    /// Only use `steal` when the original owner can no longer run, like in a panic or
+    /// fault handler.
+    pub unsafe fn steal(_: ral::pit::Instance) -> (PIT, PIT, PIT, PIT) {
+        (
+            PIT {
+                channel: register::ChannelInstance::zero(),
+            },
+            PIT {
+                channel: register::ChannelInstance::one(),
+            },
+            PIT {
+                channel: register::ChannelInstance::two(),
+            },
+            PIT {
+                channel: register::ChannelInstance::three(),
+            },
+        )
+    }
+
     /// Wait for the counts to elapse
     ///
     /// The elapsed time is a function of your clock selection and clock frequency.
@@ -145,6 +191,82 @@ fn poll_cancel(channel: &mut register::ChannelInstance) {
     ral::write_reg!(register, channel, TCTRL, 0);
 }
 
+/// A PIT clock with an alarm
+///
+/// An `AlarmClock` uses two PIT channels. One channel counts down from `u32::MAX`, and
+/// wraps around, to keep time. The other channel counts down to the alarm. Both
+/// channels count the PIT clock.
+///
+/// See the [`time`](crate::time) module for more information.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::{pit::AlarmClock, ral, time::TimeDriver, PIT};
+///
+/// let (time, alarm, _, _) = PIT::new(ral::pit::PIT::take().unwrap());
+/// let clock = AlarmClock::new(time, alarm);
+/// let now = clock.now();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "pit")))]
+pub struct AlarmClock {
+    time: PIT,
+    alarm: PIT,
+}
+
+impl AlarmClock {
+    /// Create a clock that keeps time with the `time` channel, and schedules alarms
+    /// with the `alarm` channel
+    ///
+    /// The time starts at zero.
+    pub fn new(time: PIT, mut alarm: PIT) -> Self {
+        ral::write_reg!(register, time.channel, TCTRL, 0);
+        ral::write_reg!(register, time.channel, TFLG, TIF: 1);
+        ral::write_reg!(register, time.channel, LDVAL, u32::MAX);
+        ral::write_reg!(register, time.channel, TCTRL, TEN: 1);
+        poll_cancel(&mut alarm.channel);
+        AlarmClock { time, alarm }
+    }
+
+    /// Stop the clock, and return the `(time, alarm)` channels
+    pub fn release(mut self) -> (PIT, PIT) {
+        crate::time::TimeDriver::clear_alarm(&mut self);
+        poll_cancel(&mut self.time.channel);
+        (self.time, self.alarm)
+    }
+}
+
+impl crate::time::TimeDriver for AlarmClock {
+    fn now(&self) -> u32 {
+        // The channel counts down
+        !ral::read_reg!(register, self.time.channel, CVAL)
+    }
+
+    fn set_alarm(&mut self, at: u32, alarm_waker: &Waker) -> bool {
+        crate::time::TimeDriver::clear_alarm(self);
+        let now = self.now();
+        if !crate::time::is_future(at, now) {
+            return false;
+        }
+        let ticks = at.wrapping_sub(now);
+        // Safety: the channel's interrupt is disabled, so the interrupt handler
+        // doesn't touch the waker.
+        unsafe { WAKERS[self.alarm.channel.index()] = Some(alarm_waker.clone()) };
+        // The channel counts LDVAL + 1 clock counts. The time advanced since we read
+        // it, so the alarm may be a few counts late, but never early.
+        ral::write_reg!(register, self.alarm.channel, LDVAL, ticks - 1);
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+        ral::write_reg!(register, self.alarm.channel, TCTRL, TIE: 1, TEN: 1);
+        true
+    }
+
+    fn clear_alarm(&mut self) {
+        poll_cancel(&mut self.alarm.channel);
+        ral::write_reg!(register, self.alarm.channel, TFLG, TIF: 1);
+        // Safety: the channel's interrupt is disabled
+        unsafe { WAKERS[self.alarm.channel.index()] = None };
+    }
+}
+
 interrupts! {
     handler!{unsafe fn PIT() {
         use register::ChannelInstance;
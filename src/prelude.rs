@@ -0,0 +1,42 @@
+//! Common imports
+//!
+//! The prelude re-exports the drivers, the traits that you need to use them, and the
+//! modules that you'll use to set them up. Glob import the prelude to bring them all
+//! into scope.
+//!
+//! ```no_run
+//! use imxrt_async_hal::prelude::*;
+//!
+//! let pads = iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let uart2 = ral::lpuart::LPUART2::take()
+//!     .and_then(instance::uart)
+//!     .unwrap();
+//! let uart = UART::new(uart2, pads.ad_b1.p02, pads.ad_b1.p03);
+//! ```
+//!
+//! The prelude only includes the drivers that are enabled by feature flags.
+
+pub use crate::{instance, instance::Inst as _, iomuxc, ral};
+
+#[cfg(any(feature = "spi", feature = "uart"))]
+pub use crate::dma;
+
+#[cfg(all(feature = "flash", feature = "imxrt1060"))]
+pub use crate::flash::{storage::Record, Flash};
+#[cfg(feature = "gpio")]
+pub use crate::gpio::GPIO;
+#[cfg(feature = "systick")]
+pub use crate::SysTick;
+#[cfg(feature = "gpt")]
+pub use crate::GPT;
+#[cfg(feature = "pit")]
+pub use crate::PIT;
+#[cfg(feature = "uart")]
+pub use crate::UART;
+#[cfg(feature = "i2c")]
+pub use crate::{
+    i2c::{Backoff, RegisterMap},
+    I2CClockSpeed, I2C,
+};
+#[cfg(feature = "spi")]
+pub use crate::{SPIPins, DSHOT, SPI, WS2812};
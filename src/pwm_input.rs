@@ -0,0 +1,203 @@
+//! PWM input measurement
+//!
+//! A [`PWMInput`] measures the period and high time of a PWM signal with a GPT input
+//! [`Capture`]. The GPT timestamps three edges in hardware: a rising edge, the next
+//! falling edge, and the next rising edge. Use it to read fan tachometers, PWM sensors,
+//! and RC receivers.
+//!
+//! The timestamps don't include interrupt or task latency. However, the interrupt
+//! handler selects the next edge to capture after each capture, so the high and low
+//! times must each be longer than the interrupt latency, typically a few
+//! microseconds.
+//!
+//! Enable the `"gpt"` feature to use this module.
+//!
+//! # Example
+//!
+//! Read an RC receiver channel, using a 1MHz GPT. The receiver connects to a pad
+//! that's routed to `GPT2_CAPTURE1`.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{gpt::{Capture, InputCapture}, pwm_input::PWMInput, ral};
+//!
+//! let (mut gpt, _, _) = hal::GPT::new(ral::gpt::GPT2::take().unwrap());
+//! // Route the receiver pad to GPT2_CAPTURE1...
+//! let input = Capture::new(&gpt, InputCapture::Channel1).unwrap();
+//! let mut channel = PWMInput::new(input, 50_000);
+//!
+//! # async {
+//! let measurement = channel.measure(&mut gpt).await.unwrap();
+//! let pulse_us = measurement.high_ticks;
+//! let duty_permille = measurement.duty_permille();
+//! # };
+//! ```
+
+use crate::{
+    gpt::{Capture, CaptureEdge, Delay, Edge},
+    GPT,
+};
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Errors from a PWM input measurement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An edge didn't occur before the timeout
+    ///
+    /// The input may be stuck at 0% or 100% duty cycle, or it may be disconnected.
+    Timeout,
+}
+
+/// The result of a [`PWMInput`] measurement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    /// The signal's period, in GPT clock counts
+    pub period_ticks: u32,
+    /// The signal's high time, in GPT clock counts
+    pub high_ticks: u32,
+}
+
+impl Measurement {
+    /// Returns the duty cycle, in tenths of a percent
+    pub fn duty_permille(&self) -> u32 {
+        if self.period_ticks == 0 {
+            return 0;
+        }
+        (u64::from(self.high_ticks) * 1_000 / u64::from(self.period_ticks)) as u32
+    }
+}
+
+/// Measures the period and duty cycle of a PWM signal
+///
+/// See the [module-level documentation](crate::pwm_input) for more information.
+pub struct PWMInput {
+    input: Capture,
+    timeout_ticks: u32,
+}
+
+impl PWMInput {
+    /// Measure the PWM signal on the `input` capture
+    ///
+    /// `timeout_ticks` is the longest wait for each edge, in GPT clock counts. It should
+    /// be longer than the signal's period.
+    pub fn new(input: Capture, timeout_ticks: u32) -> Self {
+        PWMInput {
+            input,
+            timeout_ticks,
+        }
+    }
+
+    /// Measure one period of the signal
+    ///
+    /// `gpt` times the timeouts; it may be any GPT timer with the same clock as the
+    /// input capture.
+    pub fn measure<'a>(&'a mut self, gpt: &'a mut GPT) -> Measure<'a> {
+        Measure {
+            pwm: self,
+            gpt,
+            state: State::Start,
+            edges: [0; 3],
+            count: 0,
+            _gpt: PhantomData,
+        }
+    }
+
+    /// Release the input capture
+    pub fn release(self) -> Capture {
+        self.input
+    }
+}
+
+/// A future that measures one period of a PWM signal
+///
+/// Use [`measure`](PWMInput::measure()) to create this future. If you drop the future
+/// before it completes, the input stops timestamping edges.
+pub struct Measure<'a> {
+    pwm: &'a mut PWMInput,
+    /// The GPT, borrowed for `'a`; the timeout in `state` borrows it
+    gpt: *mut GPT,
+    state: State<'a>,
+    /// The timestamps of a rising edge, the next falling edge, and the next rising edge
+    edges: [u32; 3],
+    /// The number of timestamps in `edges`
+    count: usize,
+    _gpt: PhantomData<&'a mut GPT>,
+}
+
+/// The step of a [`Measure`] future
+enum State<'a> {
+    /// Nothing started
+    Start,
+    /// Waiting for the next edge, or the timeout
+    Edge(Delay<'a>),
+    /// The measurement completed
+    Done,
+}
+
+impl<'a> Measure<'a> {
+    /// Wait for the next edge, with a new timeout
+    fn next_edge(&mut self) {
+        // Drop the previous timeout before borrowing the GPT again.
+        self.state = State::Done;
+        // Safety: the state holds no borrow of the GPT.
+        let gpt = unsafe { &mut *self.gpt };
+        self.state = State::Edge(gpt.delay(self.pwm.timeout_ticks));
+    }
+
+    fn finish(&mut self, result: Result<Measurement, Error>) -> Poll<Result<Measurement, Error>> {
+        self.state = State::Done;
+        self.pwm.input.stop();
+        Poll::Ready(result)
+    }
+}
+
+impl Future for Measure<'_> {
+    type Output = Result<Measurement, Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the timeout is never moved out of the state.
+        let this = unsafe { self.get_unchecked_mut() };
+        loop {
+            match &mut this.state {
+                State::Start => {
+                    this.pwm.input.start(CaptureEdge::Both {
+                        first: Edge::Rising,
+                    });
+                    this.next_edge();
+                }
+                State::Edge(timeout) => {
+                    if let Poll::Ready(captured) = this.pwm.input.poll_capture(cx) {
+                        this.edges[this.count] = captured.ticks;
+                        this.count += 1;
+                        if this.count < this.edges.len() {
+                            this.next_edge();
+                            continue;
+                        }
+                        let [rise, fall, next_rise] = this.edges;
+                        return this.finish(Ok(Measurement {
+                            period_ticks: next_rise.wrapping_sub(rise),
+                            high_ticks: fall.wrapping_sub(rise),
+                        }));
+                    }
+                    futures::ready!(unsafe { Pin::new_unchecked(timeout) }.poll(cx));
+                    return this.finish(Err(Error::Timeout));
+                }
+                State::Done => panic!("Measure polled after completion"),
+            }
+        }
+    }
+}
+
+impl Drop for Measure<'_> {
+    fn drop(&mut self) {
+        if let State::Edge(_) = self.state {
+            self.state = State::Done;
+            self.pwm.input.stop();
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! SBUS radio control receiver input
+//!
+//! [`SBUS`] reads and decodes SBUS frames from a UART. SBUS is an inverted serial
+//! protocol, at 100000 baud, with even parity and two stop bits. Each 25-byte frame
+//! carries sixteen 11-bit channels, two digital channels, and failsafe flags.
+//!
+//! Connect the receiver's SBUS output to the UART's RX pin. The `UART` driver still
+//! requires a TX pin, but `SBUS` doesn't use it.
+//!
+//! SBUS frames have no unique start byte; the header value is also a valid channel data
+//! byte. Instead, `SBUS` finds the start of a frame from the gap between frames. It waits
+//! for the receive line to be [idle](crate::UART::idle()) for about 2ms, then reads a
+//! frame. If a frame has an invalid header or footer, `SBUS` waits for the next gap.
+//!
+//! Use [`frames`](SBUS::frames()) for a [`Stream`] of frames, or [`next`](SBUS::next())
+//! to wait for one frame.
+//!
+//! Enable the `"uart"` feature to use this module.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{dma, ral, sbus::SBUS};
+//!
+//! const SOURCE_CLOCK_HZ: u32 = 24_000_000;
+//!
+//! let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let mut channels = dma::channels(
+//!     ral::dma0::DMA0::take().unwrap(),
+//!     ral::dmamux::DMAMUX::take().unwrap(),
+//! );
+//! let uart2 = ral::lpuart::LPUART2::take()
+//!     .and_then(hal::instance::uart)
+//!     .unwrap();
+//! let uart = hal::UART::new(uart2, pads.ad_b1.p02, pads.ad_b1.p03);
+//! let mut sbus = SBUS::new(uart, channels[7].take().unwrap(), SOURCE_CLOCK_HZ).unwrap();
+//!
+//! # async {
+//! loop {
+//!     let frame = sbus.next().await.unwrap();
+//!     if frame.failsafe {
+//!         // Stop the motors...
+//!     } else {
+//!         let throttle = frame.channels[2];
+//!     }
+//! }
+//! # };
+//! ```
+
+use crate::{dma, UARTError, UARTIdle, UARTParity, UARTStopBits, UART};
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures::stream::Stream;
+
+/// SBUS baud rate
+const BAUD: u32 = 100_000;
+/// Bytes in an SBUS frame
+const FRAME_LEN: usize = 25;
+/// The first byte of every frame
+const HEADER: u8 = 0x0F;
+/// Idle character times that mark the gap between frames
+///
+/// At 100000 baud, a character is 120us, so 16 characters is 1.92ms. Frames are sent
+/// back-to-back, and the gap between frames is at least 3ms.
+const GAP_CHARACTERS: u8 = 16;
+
+/// A decoded SBUS frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// The sixteen proportional channels
+    ///
+    /// Each channel is 11 bits. Most transmitters send values from about 172 to 1811.
+    pub channels: [u16; 16],
+    /// Digital channel 17
+    pub channel17: bool,
+    /// Digital channel 18
+    pub channel18: bool,
+    /// The receiver lost a frame from the transmitter
+    pub frame_lost: bool,
+    /// The receiver lost the transmitter, and it's in failsafe
+    pub failsafe: bool,
+}
+
+impl Frame {
+    /// Decode a frame, returning `None` if the header or footer is invalid
+    fn decode(bytes: &[u8; FRAME_LEN]) -> Option<Self> {
+        // SBUS2 receivers send footers 0x04, 0x14, 0x24, and 0x34
+        let footer = bytes[FRAME_LEN - 1];
+        if bytes[0] != HEADER || (footer != 0x00 && footer & 0x0F != 0x04) {
+            return None;
+        }
+
+        let mut channels = [0; 16];
+        let mut bits = 0u32;
+        let mut len = 0;
+        let mut data = bytes[1..23].iter();
+        for channel in &mut channels {
+            while len < 11 {
+                bits |= u32::from(*data.next()?) << len;
+                len += 8;
+            }
+            *channel = (bits & 0x7FF) as u16;
+            bits >>= 11;
+            len -= 11;
+        }
+
+        let flags = bytes[23];
+        Some(Frame {
+            channels,
+            channel17: flags & (1 << 0) != 0,
+            channel18: flags & (1 << 1) != 0,
+            frame_lost: flags & (1 << 2) != 0,
+            failsafe: flags & (1 << 3) != 0,
+        })
+    }
+}
+
+/// An SBUS receiver input
+///
+/// See the [module-level documentation](crate::sbus) for more information.
+pub struct SBUS<TX, RX> {
+    uart: UART<TX, RX>,
+    channel: dma::Channel,
+    buffer: [u8; FRAME_LEN],
+}
+
+impl<TX, RX> SBUS<TX, RX> {
+    /// Create an SBUS input from a UART and a DMA channel
+    ///
+    /// `new` configures the UART for SBUS. `source_clock_hz` is the UART clock root
+    /// frequency. Returns an error if the UART cannot generate the SBUS baud rate from
+    /// the clock.
+    pub fn new(
+        mut uart: UART<TX, RX>,
+        mut channel: dma::Channel,
+        source_clock_hz: u32,
+    ) -> Result<Self, UARTError> {
+        uart.set_baud(BAUD, source_clock_hz)?;
+        uart.set_parity(Some(UARTParity::Even));
+        uart.set_stop_bits(UARTStopBits::Two);
+        uart.set_inversion(true);
+        uart.set_idle_characters(GAP_CHARACTERS);
+        channel.set_interrupt_on_completion(true);
+        Ok(SBUS {
+            uart,
+            channel,
+            buffer: [0; FRAME_LEN],
+        })
+    }
+
+    /// Return the UART and the DMA channel
+    ///
+    /// The UART keeps its SBUS configuration.
+    pub fn release(self) -> (UART<TX, RX>, dma::Channel) {
+        (self.uart, self.channel)
+    }
+
+    /// Returns a stream of valid frames
+    ///
+    /// The stream never ends. It yields an error if the DMA transfer fails, then
+    /// resynchronizes with the next gap between frames. It skips frames with an invalid
+    /// header or footer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use imxrt_async_hal as hal;
+    /// # use hal::{dma, ral, sbus::SBUS};
+    /// use futures::stream::StreamExt;
+    /// # let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+    /// # let mut channels = dma::channels(
+    /// #     ral::dma0::DMA0::take().unwrap(),
+    /// #     ral::dmamux::DMAMUX::take().unwrap(),
+    /// # );
+    /// # let uart2 = ral::lpuart::LPUART2::take()
+    /// #     .and_then(hal::instance::uart)
+    /// #     .unwrap();
+    /// # let uart = hal::UART::new(uart2, pads.ad_b1.p02, pads.ad_b1.p03);
+    /// let mut sbus = SBUS::new(uart, channels[7].take().unwrap(), 24_000_000).unwrap();
+    ///
+    /// # async {
+    /// let mut frames = sbus.frames();
+    /// while let Some(Ok(frame)) = frames.next().await {
+    ///     let throttle = frame.channels[2];
+    /// }
+    /// # };
+    /// ```
+    pub fn frames(&mut self) -> Frames<'_, TX, RX> {
+        Frames {
+            sbus: self,
+            state: State::Start { resync: false },
+            _sbus: PhantomData,
+        }
+    }
+
+    /// Wait for the next valid frame
+    ///
+    /// `next` waits for the gap between frames, then reads the next frame. It skips
+    /// frames with an invalid header or footer.
+    pub fn next(&mut self) -> Next<'_, TX, RX> {
+        Next {
+            frames: self.frames(),
+        }
+    }
+}
+
+/// A stream of SBUS frames
+///
+/// Use [`frames`](SBUS::frames()) to create the stream.
+pub struct Frames<'a, TX, RX> {
+    /// The input, borrowed for `'a`
+    ///
+    /// The futures in `state` borrow the input's UART, DMA channel and buffer. `state`
+    /// holds at most one of those futures at a time.
+    sbus: *mut SBUS<TX, RX>,
+    state: State<'a, TX, RX>,
+    _sbus: PhantomData<&'a mut SBUS<TX, RX>>,
+}
+
+/// The step of a [`Frames`] stream
+enum State<'a, TX, RX> {
+    /// Waiting for nothing. If `resync`, skip the next gap before reading a frame.
+    Start { resync: bool },
+    /// Waiting for a gap between frames
+    Gap { idle: UARTIdle<'a>, resync: bool },
+    /// Reading a frame
+    Read(dma::Rx<'a, UART<TX, RX>, u8>),
+}
+
+impl<'a, TX, RX> Frames<'a, TX, RX> {
+    fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<Result<Frame, dma::Error>> {
+        loop {
+            match &mut self.state {
+                State::Start { resync } => {
+                    let resync = *resync;
+                    // Safety: the state holds no borrow of the input.
+                    let sbus = unsafe { &mut *self.sbus };
+                    self.state = State::Gap {
+                        idle: sbus.uart.idle(),
+                        resync,
+                    };
+                }
+                State::Gap { idle, resync } => {
+                    futures::ready!(Pin::new(idle).poll(cx));
+                    // After a bad frame, the idle flag may be from a gap in the middle
+                    // of the bad read. Skip it, and wait for the next gap.
+                    let resync = *resync;
+                    self.state = State::Start { resync: false };
+                    if !resync {
+                        // Safety: the state holds no borrow of the input.
+                        let sbus = unsafe { &mut *self.sbus };
+                        self.state =
+                            State::Read(sbus.uart.dma_read(&mut sbus.channel, &mut sbus.buffer));
+                    }
+                }
+                State::Read(rx) => {
+                    // Safety: the read is never moved out of the state, and the
+                    // state is never moved while the read is in progress.
+                    let result = futures::ready!(unsafe { Pin::new_unchecked(rx) }.poll(cx));
+                    // Drop the read before looking at the buffer.
+                    self.state = State::Start { resync: true };
+                    result?;
+                    // Safety: the state holds no borrow of the input.
+                    let sbus = unsafe { &*self.sbus };
+                    if let Some(frame) = Frame::decode(&sbus.buffer) {
+                        self.state = State::Start { resync: false };
+                        return Poll::Ready(Ok(frame));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<TX, RX> Stream for Frames<'_, TX, RX> {
+    type Item = Result<Frame, dma::Error>;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Safety: the state is only replaced in place, never moved.
+        let this = unsafe { self.get_unchecked_mut() };
+        this.poll_frame(cx).map(Some)
+    }
+}
+
+/// A future that waits for the next valid SBUS frame
+///
+/// Use [`next`](SBUS::next()) to create this future.
+pub struct Next<'a, TX, RX> {
+    frames: Frames<'a, TX, RX>,
+}
+
+impl<TX, RX> Future for Next<'_, TX, RX> {
+    type Output = Result<Frame, dma::Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the stream is never moved out of the future.
+        let this = unsafe { self.get_unchecked_mut() };
+        this.frames.poll_frame(cx)
+    }
+}
@@ -115,6 +115,33 @@ pub struct Pins<SDO, SDI, SCK, PCS0> {
 pub struct SPI<Pins> {
     pins: Pins,
     spi: ral::lpspi::Instance,
+    tx_watermark: u32,
+}
+
+#[cfg(feature = "diagnostics")]
+impl<Pins> core::fmt::Debug for SPI<Pins> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use instance::Inst;
+        let men = ral::read_reg!(ral::lpspi, self.spi, CR, MEN);
+        let (tdf, rdf, tcf, tef, ref_, mbf) =
+            ral::read_reg!(ral::lpspi, self.spi, SR, TDF, RDF, TCF, TEF, REF, MBF);
+        let (txcount, rxcount) = ral::read_reg!(ral::lpspi, self.spi, FSR, TXCOUNT, RXCOUNT);
+        let (tdde, rdde) = ral::read_reg!(ral::lpspi, self.spi, DER, TDDE, RDDE);
+        f.debug_struct("SPI")
+            .field("instance", &self.spi.inst())
+            .field("enabled", &(men != 0))
+            .field("busy", &(mbf != 0))
+            .field("tx_dma", &(tdde != 0))
+            .field("rx_dma", &(rdde != 0))
+            .field("tx_data", &(tdf != 0))
+            .field("rx_data", &(rdf != 0))
+            .field("transfer_complete", &(tcf != 0))
+            .field("tx_underrun", &(tef != 0))
+            .field("rx_overrun", &(ref_ != 0))
+            .field("tx_fifo_count", &txcount)
+            .field("rx_fifo_count", &rxcount)
+            .finish()
+    }
 }
 
 impl<SDO, SDI, SCK, PCS0, M> SPI<Pins<SDO, SDI, SCK, PCS0>>
@@ -132,6 +159,7 @@ where
     ///
     /// The clock speed is unspecified. Make sure you change your clock speed with `set_clock_speed`.
     pub fn new(mut pins: Pins<SDO, SDI, SCK, PCS0>, spi: instance::SPI<M>) -> Self {
+        crate::ccm::assert_gates_on(crate::ccm::Peripheral::SPI(M::USIZE));
         iomuxc::spi::prepare(&mut pins.sdo);
         iomuxc::spi::prepare(&mut pins.sdi);
         iomuxc::spi::prepare(&mut pins.sck);
@@ -146,17 +174,72 @@ where
         ral::write_reg!(ral::lpspi, spi, FCR, RXWATER: 0xF, TXWATER: 0xF);
         ral::write_reg!(ral::lpspi, spi, CR, MEN: MEN_1);
 
-        SPI { pins, spi }
+        let (tx_fifo, _) = fifo_sizes(&spi);
+        SPI {
+            pins,
+            spi,
+            tx_watermark: tx_fifo / 2,
+        }
+    }
+
+    /// Create a `SPI` that uses the current peripheral and pin configuration
+    ///
+    /// Unlike [`new`](SPI::new()), `steal` does not reset the peripheral or configure the
+    /// pins, so the clock speed is unchanged. The transmit FIFO watermark is the default.
+    ///
+    /// # Safety
+    ///
+    /// The original driver may still exist, and it may be in the middle of an operation.
+    /// Only use `steal` when the original owner can no longer run, like in a panic or
+    /// fault handler.
+    pub unsafe fn steal(pins: Pins<SDO, SDI, SCK, PCS0>, spi: instance::SPI<M>) -> Self {
+        let spi = spi.release();
+        let (tx_fifo, _) = fifo_sizes(&spi);
+        SPI {
+            pins,
+            spi,
+            tx_watermark: tx_fifo / 2,
+        }
     }
 }
 
 impl<Pins> SPI<Pins> {
     /// Return the pins and SPI instance that are used in this `SPI`
     /// driver
+    ///
+    /// `release` disables the SPI peripheral. You may prepare the returned pins for
+    /// another function.
     pub fn release(self) -> (Pins, ral::lpspi::Instance) {
+        ral::modify_reg!(ral::lpspi, self.spi, CR, MEN: MEN_0);
         (self.pins, self.spi)
     }
 
+    /// Set the transmit FIFO watermark used by DMA operations
+    ///
+    /// The SPI peripheral requests more transmit data once the transmit FIFO holds `tx` or
+    /// fewer elements. A larger `tx` keeps the FIFO full, so the serial clock runs without
+    /// gaps between frames. A `tx` of zero waits for an empty FIFO before requesting more
+    /// data, which adds idle time to every frame at high clock speeds. The default `tx` is
+    /// half of the transmit FIFO.
+    ///
+    /// The watermark is limited by the FIFO size. Values that are too large are clamped.
+    ///
+    /// The receive watermark is always zero. The SPI peripheral only requests a receive
+    /// transfer while the receive FIFO holds more elements than the watermark, and each
+    /// request moves one element. With any other receive watermark, the last elements of a
+    /// receive would never be transferred, and the receive would never complete.
+    pub fn set_tx_watermark(&mut self, tx: u8) {
+        let (tx_fifo, _) = fifo_sizes(&self.spi);
+        self.tx_watermark = u32::from(tx).min(tx_fifo - 1);
+    }
+
+    /// Returns the transmit FIFO watermark
+    ///
+    /// See [`set_tx_watermark`](SPI::set_tx_watermark()) for more information.
+    pub fn tx_watermark(&self) -> u8 {
+        self.tx_watermark as u8
+    }
+
     fn set_frame_size<W>(&mut self) {
         ral::modify_reg!(ral::lpspi, self.spi, TCR, FRAMESZ: ((core::mem::size_of::<W>() * 8 - 1) as u32));
     }
@@ -188,6 +271,196 @@ impl<Pins> SPI<Pins> {
     ) -> dma::FullDuplex<'a, Self, E> {
         dma::full_duplex(rx_channel, tx_channel, self, buffer)
     }
+
+    /// Hold chip select across multiple DMA operations
+    ///
+    /// Chip select stays asserted from the first operation in the returned [`Transaction`]
+    /// until the `Transaction` is dropped. Use a `Transaction` for device command sequences,
+    /// like a flash command followed by its data, that must not release chip select.
+    ///
+    /// # Example
+    ///
+    /// Send a flash read command, then read the data.
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::dma;
+    ///
+    /// async fn read_flash<P>(
+    ///     spi: &mut hal::SPI<P>,
+    ///     rx_channel: &mut dma::Channel,
+    ///     tx_channel: &mut dma::Channel,
+    ///     address: u32,
+    ///     buffer: &mut [u8],
+    /// ) -> Result<(), dma::Error> {
+    ///     let [_, a2, a1, a0] = address.to_be_bytes();
+    ///     let mut transaction = spi.transaction();
+    ///     let mut command = [0x03, a2, a1, a0];
+    ///     transaction.dma_full_duplex(rx_channel, tx_channel, &mut command).await?;
+    ///     transaction.dma_full_duplex(rx_channel, tx_channel, buffer).await
+    ///     // Chip select is released when transaction drops
+    /// }
+    /// ```
+    pub fn transaction(&mut self) -> Transaction<'_, Pins> {
+        self.start_transaction();
+        Transaction {
+            spi: self,
+            cs: None,
+        }
+    }
+
+    /// Hold a software chip select across multiple DMA operations
+    ///
+    /// Use `transaction_with_cs` when your device's chip select is a GPIO, rather than
+    /// the SPI peripheral's PCS0 pin. The returned [`Transaction`] drives `cs` low
+    /// immediately. Once the `Transaction` is dropped, it waits for the last frame to
+    /// leave the SPI peripheral, then drives `cs` high. Otherwise, it behaves like
+    /// [`transaction`](SPI::transaction()).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::dma;
+    ///
+    /// async fn command<P>(
+    ///     spi: &mut hal::SPI<P>,
+    ///     cs: &mut impl hal::SPIChipSelect,
+    ///     rx_channel: &mut dma::Channel,
+    ///     tx_channel: &mut dma::Channel,
+    ///     buffer: &mut [u8],
+    /// ) -> Result<(), dma::Error> {
+    ///     let mut transaction = spi.transaction_with_cs(cs);
+    ///     transaction.dma_full_duplex(rx_channel, tx_channel, buffer).await
+    ///     // cs is driven high when transaction drops
+    /// }
+    /// ```
+    pub fn transaction_with_cs<'a, CS: ChipSelect>(
+        &'a mut self,
+        cs: &'a mut CS,
+    ) -> Transaction<'a, Pins, CS> {
+        self.start_transaction();
+        cs.select();
+        Transaction {
+            spi: self,
+            cs: Some(cs),
+        }
+    }
+
+    fn start_transaction(&mut self) {
+        // The first command starts a continuous transfer; commands written by the operations
+        // continue that transfer, so chip select stays asserted between them.
+        ral::modify_reg!(ral::lpspi, self.spi, TCR, CONT: CONT_1, CONTC: CONTC_0);
+        ral::modify_reg!(ral::lpspi, self.spi, TCR, CONTC: CONTC_1);
+    }
+}
+
+/// A software chip select for a [`Transaction`]
+///
+/// Chip select is active low. With the `"embedded-hal"` feature, every
+/// `embedded_hal` `OutputPin` is a `ChipSelect`; `OutputPin` errors are ignored.
+/// Otherwise, with the `"gpio"` feature, every output [`GPIO`](crate::gpio::GPIO) is a
+/// `ChipSelect`. You may implement `ChipSelect` for your own chip select.
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub trait ChipSelect {
+    /// Select the device
+    fn select(&mut self);
+    /// Release the device
+    fn release(&mut self);
+}
+
+/// The chip select of a [`Transaction`] that uses the hardware PCS0 pin
+///
+/// `NoChipSelect` does nothing.
+#[derive(Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub enum NoChipSelect {}
+
+impl ChipSelect for NoChipSelect {
+    fn select(&mut self) {}
+    fn release(&mut self) {}
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<P> ChipSelect for P
+where
+    P: embedded_hal::digital::v2::OutputPin,
+{
+    fn select(&mut self) {
+        let _ = self.set_low();
+    }
+    fn release(&mut self) {
+        let _ = self.set_high();
+    }
+}
+
+#[cfg(all(feature = "gpio", not(feature = "embedded-hal")))]
+impl<P> ChipSelect for crate::gpio::GPIO<P, crate::gpio::Output>
+where
+    P: crate::iomuxc::gpio::Pin,
+{
+    fn select(&mut self) {
+        self.clear();
+    }
+    fn release(&mut self) {
+        self.set();
+    }
+}
+
+/// A sequence of SPI operations that holds chip select
+///
+/// Use [`transaction`](SPI::transaction()) to create a `Transaction` that holds the
+/// hardware chip select, or [`transaction_with_cs`](SPI::transaction_with_cs()) to
+/// create a `Transaction` that holds a software [`ChipSelect`]. Dropping the
+/// `Transaction` releases chip select.
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub struct Transaction<'a, Pins, CS: ChipSelect = NoChipSelect> {
+    spi: &'a mut SPI<Pins>,
+    cs: Option<&'a mut CS>,
+}
+
+impl<Pins, CS: ChipSelect> Transaction<'_, Pins, CS> {
+    /// Use a DMA channel to read data from the SPI peripheral
+    pub fn dma_read<'a, E: dma::Element>(
+        &'a mut self,
+        channel: &'a mut dma::Channel,
+        buffer: &'a mut [E],
+    ) -> dma::Rx<'a, SPI<Pins>, E> {
+        self.spi.dma_read(channel, buffer)
+    }
+
+    /// Use a DMA channel to write data to the SPI peripheral
+    pub fn dma_write<'a, E: dma::Element>(
+        &'a mut self,
+        channel: &'a mut dma::Channel,
+        buffer: &'a [E],
+    ) -> dma::Tx<'a, SPI<Pins>, E> {
+        self.spi.dma_write(channel, buffer)
+    }
+
+    /// Use two DMA channels to perform a full-duplex transfer
+    pub fn dma_full_duplex<'a, E: dma::Element>(
+        &'a mut self,
+        rx_channel: &'a mut dma::Channel,
+        tx_channel: &'a mut dma::Channel,
+        buffer: &'a mut [E],
+    ) -> dma::FullDuplex<'a, SPI<Pins>, E> {
+        self.spi.dma_full_duplex(rx_channel, tx_channel, buffer)
+    }
+}
+
+impl<Pins, CS: ChipSelect> Drop for Transaction<'_, Pins, CS> {
+    fn drop(&mut self) {
+        // A command that's not continuous ends the transfer, and releases chip select.
+        ral::modify_reg!(ral::lpspi, self.spi.spi, TCR, CONT: CONT_0, CONTC: CONTC_0);
+        if let Some(cs) = self.cs.take() {
+            // Hold the software chip select until the last frame leaves the FIFO.
+            while ral::read_reg!(ral::lpspi, self.spi.spi, FSR, TXCOUNT) != 0
+                || ral::read_reg!(ral::lpspi, self.spi.spi, SR, MBF == 1)
+            {}
+            cs.release();
+        }
+    }
 }
 
 /// Errors propagated from a [`SPI`] device
@@ -223,6 +496,12 @@ impl<Pins> SPI<Pins> {
     }
 }
 
+/// Returns the transmit and receive FIFO sizes, in elements
+fn fifo_sizes(spi: &ral::lpspi::Instance) -> (u32, u32) {
+    let (tx, rx) = ral::read_reg!(ral::lpspi, spi, PARAM, TXFIFO, RXFIFO);
+    (1 << tx, 1 << rx)
+}
+
 /// Must be called while SPI is disabled
 fn set_clock_speed(spi: &ral::lpspi::Instance, base: u32, hz: u32) {
     let mut div = base / hz;
@@ -262,6 +541,7 @@ unsafe impl<E: dma::Element, Pins> dma::Source<E> for SPI<Pins> {
     }
     fn enable_source(&mut self) {
         self.set_frame_size::<E>();
+        // See set_tx_watermark() for why the receive watermark is zero.
         ral::modify_reg!(ral::lpspi, self.spi, FCR, RXWATER: 0);
         ral::modify_reg!(ral::lpspi, self.spi, DER, RDDE: 1);
     }
@@ -281,7 +561,7 @@ unsafe impl<E: dma::Element, Pins> dma::Destination<E> for SPI<Pins> {
     }
     fn enable_destination(&mut self) {
         self.set_frame_size::<E>();
-        ral::modify_reg!(ral::lpspi, self.spi, FCR, TXWATER: 0);
+        ral::modify_reg!(ral::lpspi, self.spi, FCR, TXWATER: self.tx_watermark);
         ral::modify_reg!(ral::lpspi, self.spi, DER, TDDE: 1);
     }
     fn disable_destination(&mut self) {
@@ -0,0 +1,375 @@
+//! Async synchronization primitives
+//!
+//! A [`Mutex`] shares a value between tasks, and a [`Semaphore`] limits how many tasks
+//! may use a resource at once. Use them to share a bus, like a [`SPI`](crate::SPI) or an
+//! [`I2C`](crate::I2C) driver, between tasks, without an external runtime. An [`Event`]
+//! lets an interrupt handler wake a task.
+//!
+//! Both primitives update their state in a critical section. You may release a
+//! semaphore's permits from an interrupt handler, so a handler can signal a task.
+//!
+//! Each primitive tracks up to [`MAX_WAITERS`] waiting tasks. Additional waiters still
+//! make progress, but they poll continuously instead of sleeping.
+//!
+//! # Example
+//!
+//! Share a counter between two tasks. Each task holds the lock across an `await`.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::sync::Mutex;
+//!
+//! # async fn work() {}
+//! async fn task(shared: &Mutex<u32>) {
+//!     let mut count = shared.lock().await;
+//!     work().await;
+//!     *count += 1;
+//! }
+//!
+//! static COUNT: Mutex<u32> = Mutex::new(0);
+//!
+//! # async {
+//! futures::future::join(task(&COUNT), task(&COUNT)).await;
+//! # };
+//! ```
+
+use core::{
+    cell::{RefCell, UnsafeCell},
+    future::Future,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use cortex_m::interrupt::{self, Mutex as CriticalSection};
+
+/// The number of waiting tasks that each primitive tracks
+pub const MAX_WAITERS: usize = 8;
+
+struct State {
+    permits: usize,
+    wakers: [Option<Waker>; MAX_WAITERS],
+}
+
+/// An async counting semaphore
+///
+/// See the [module-level documentation](crate::sync) for more information.
+pub struct Semaphore {
+    state: CriticalSection<RefCell<State>>,
+}
+
+impl Semaphore {
+    /// Create a semaphore with `permits` available permits
+    pub const fn new(permits: usize) -> Self {
+        Semaphore {
+            state: CriticalSection::new(RefCell::new(State {
+                permits,
+                wakers: [None, None, None, None, None, None, None, None],
+            })),
+        }
+    }
+
+    /// Wait for a permit
+    ///
+    /// The permit returns to the semaphore when it drops. Use [`Permit::forget`] to keep
+    /// the permit out of the semaphore.
+    pub fn acquire(&self) -> Acquire<'_> {
+        Acquire { semaphore: self }
+    }
+
+    /// Take a permit, if one is available
+    pub fn try_acquire(&self) -> Option<Permit<'_>> {
+        interrupt::free(|cs| {
+            let mut state = self.state.borrow(cs).borrow_mut();
+            if state.permits > 0 {
+                state.permits -= 1;
+                Some(Permit { semaphore: self })
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Add `permits` to the semaphore, and wake the waiting tasks
+    ///
+    /// You may call `release` from an interrupt handler.
+    pub fn release(&self, permits: usize) {
+        interrupt::free(|cs| {
+            let mut state = self.state.borrow(cs).borrow_mut();
+            state.permits += permits;
+            state
+                .wakers
+                .iter_mut()
+                .filter_map(Option::take)
+                .for_each(Waker::wake);
+        })
+    }
+
+    /// Returns the number of available permits
+    pub fn available(&self) -> usize {
+        interrupt::free(|cs| self.state.borrow(cs).borrow().permits)
+    }
+
+    fn poll_acquire(&self, cx: &mut Context<'_>) -> Poll<()> {
+        interrupt::free(|cs| {
+            let mut state = self.state.borrow(cs).borrow_mut();
+            if state.permits > 0 {
+                state.permits -= 1;
+                return Poll::Ready(());
+            }
+            let registered = state
+                .wakers
+                .iter()
+                .flatten()
+                .any(|waker| waker.will_wake(cx.waker()));
+            if !registered {
+                match state.wakers.iter_mut().find(|waker| waker.is_none()) {
+                    Some(slot) => *slot = Some(cx.waker().clone()),
+                    // Too many waiters; poll again
+                    None => cx.waker().wake_by_ref(),
+                }
+            }
+            Poll::Pending
+        })
+    }
+}
+
+/// A future that waits for a [`Semaphore`] permit
+///
+/// Use [`acquire`](Semaphore::acquire()) to create this future.
+pub struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = Permit<'a>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore = self.semaphore;
+        semaphore.poll_acquire(cx).map(|_| Permit { semaphore })
+    }
+}
+
+/// A permit from a [`Semaphore`]
+///
+/// The permit returns to its semaphore when it drops.
+pub struct Permit<'a> {
+    semaphore: &'a Semaphore,
+}
+
+impl Permit<'_> {
+    /// Drop the permit, without returning it to the semaphore
+    pub fn forget(self) {
+        core::mem::forget(self);
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.release(1);
+    }
+}
+
+/// An async mutex
+///
+/// A `Mutex` may be a `static`. See the [module-level documentation](crate::sync) for
+/// an example.
+pub struct Mutex<T> {
+    semaphore: Semaphore,
+    value: UnsafeCell<T>,
+}
+
+// Safety: the semaphore ensures that only one guard accesses the value at a time.
+unsafe impl<T: Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    /// Create an unlocked mutex that holds `value`
+    pub const fn new(value: T) -> Self {
+        Mutex {
+            semaphore: Semaphore::new(1),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Wait to lock the mutex
+    ///
+    /// The mutex unlocks when the guard drops.
+    pub fn lock(&self) -> Lock<'_, T> {
+        Lock { mutex: self }
+    }
+
+    /// Lock the mutex, if it's unlocked
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.semaphore.try_acquire().map(|permit| {
+            permit.forget();
+            MutexGuard { mutex: self }
+        })
+    }
+
+    /// Returns a mutable reference to the value
+    ///
+    /// The exclusive borrow guarantees that the mutex is unlocked.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Returns the value
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+/// A future that waits to lock a [`Mutex`]
+///
+/// Use [`lock`](Mutex::lock()) to create this future.
+pub struct Lock<'a, T> {
+    mutex: &'a Mutex<T>,
+}
+
+impl<'a, T> Future for Lock<'a, T> {
+    type Output = MutexGuard<'a, T>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mutex = self.mutex;
+        mutex
+            .semaphore
+            .poll_acquire(cx)
+            .map(|_| MutexGuard { mutex })
+    }
+}
+
+/// Exclusive access to a [`Mutex`]'s value
+///
+/// The mutex unlocks when the guard drops.
+pub struct MutexGuard<'a, T> {
+    mutex: &'a Mutex<T>,
+}
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // Safety: the guard holds the mutex's only permit.
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the guard holds the mutex's only permit.
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.semaphore.release(1);
+    }
+}
+
+/// A notification from an interrupt handler, or another task, to a task
+///
+/// [`signal`](Event::signal()) sets the event, and wakes the waiting task. The task's
+/// [`wait`](Event::wait()) completes once the event is set, and it clears the event.
+/// Signals that happen while nothing is waiting are remembered, but multiple signals
+/// before a wait are combined into one.
+///
+/// An `Event` wakes one task. If multiple tasks wait on the same event, only the most
+/// recent waiter is woken.
+///
+/// # Example
+///
+/// Wake a task from your own interrupt handler.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::sync::Event;
+///
+/// static BUTTON: Event = Event::new();
+///
+/// // Call from your interrupt handler
+/// fn on_button_interrupt() {
+///     // Clear the interrupt flag...
+///     BUTTON.signal();
+/// }
+///
+/// # async {
+/// loop {
+///     BUTTON.wait().await;
+///     // Handle the button press...
+/// }
+/// # };
+/// ```
+pub struct Event {
+    state: CriticalSection<RefCell<EventState>>,
+}
+
+struct EventState {
+    signaled: bool,
+    waker: Option<Waker>,
+}
+
+impl Event {
+    /// Create an event that isn't set
+    pub const fn new() -> Self {
+        Event {
+            state: CriticalSection::new(RefCell::new(EventState {
+                signaled: false,
+                waker: None,
+            })),
+        }
+    }
+
+    /// Set the event, and wake the waiting task
+    ///
+    /// You may call `signal` from an interrupt handler.
+    pub fn signal(&self) {
+        interrupt::free(|cs| {
+            let mut state = self.state.borrow(cs).borrow_mut();
+            state.signaled = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        })
+    }
+
+    /// Wait for the event to be set, then clear it
+    pub fn wait(&self) -> Wait<'_> {
+        Wait { event: self }
+    }
+
+    /// Returns `true` if the event is set
+    pub fn is_set(&self) -> bool {
+        interrupt::free(|cs| self.state.borrow(cs).borrow().signaled)
+    }
+
+    /// Clear the event
+    pub fn clear(&self) {
+        interrupt::free(|cs| self.state.borrow(cs).borrow_mut().signaled = false)
+    }
+}
+
+impl Default for Event {
+    fn default() -> Self {
+        Event::new()
+    }
+}
+
+/// A future that waits for an [`Event`]
+///
+/// Use [`wait`](Event::wait()) to create this future.
+pub struct Wait<'a> {
+    event: &'a Event,
+}
+
+impl Future for Wait<'_> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        interrupt::free(|cs| {
+            let mut state = self.event.state.borrow(cs).borrow_mut();
+            if state.signaled {
+                state.signaled = false;
+                Poll::Ready(())
+            } else {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+    }
+}
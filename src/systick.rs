@@ -0,0 +1,275 @@
+//! SysTick timer delays
+//!
+//! [`SysTick`] provides the same `delay` API as the [`GPT`](crate::GPT) timers, but it
+//! uses the Cortex-M SysTick timer. Use `SysTick` if you need to keep both GPTs free for
+//! other work.
+//!
+//! The SysTick counter is 24 bits wide. Longer delays are split into multiple counter
+//! periods, so any `u32` tick count is supported. The elapsed time depends on the
+//! clock source: the core clock, or the external reference clock.
+//!
+//! The `"systick"` feature is disabled by default, since it registers the `SysTick`
+//! exception handler.
+//!
+//! # Example
+//!
+//! Use the core clock, running at 600MHz, to wait for 10ms.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::SysTick;
+//! use cortex_m::peripheral::syst::SystClkSource;
+//!
+//! let syst = cortex_m::Peripherals::take().unwrap().SYST;
+//! let mut systick = SysTick::new(syst, SystClkSource::Core);
+//!
+//! # async {
+//! systick.delay(600_000_000 / 100).await;
+//! # };
+//! ```
+
+use core::{
+    future::Future,
+    marker::PhantomPinned,
+    pin::Pin,
+    sync::atomic::{self, AtomicBool, AtomicU32, Ordering},
+    task::{Context, Poll, Waker},
+};
+use cortex_m::peripheral::{syst::SystClkSource, SYST};
+
+/// The largest number of ticks in one SysTick counter period
+const MAX_PERIOD: u32 = 1 << 24;
+
+/// Ticks that remain in the active delay
+static REMAINING: AtomicU32 = AtomicU32::new(0);
+static mut WAKER: Option<Waker> = None;
+
+/// `true` while a [`Clock`] owns the SysTick timer
+static CLOCK: AtomicBool = AtomicBool::new(false);
+/// The [`Clock`] time, in clock periods
+static TIME: AtomicU32 = AtomicU32::new(0);
+/// The [`Clock`] alarm's tick. Only accessed in critical sections, or in the exception.
+static mut ALARM: Option<(u32, Waker)> = None;
+
+/// The SysTick timer
+///
+/// See the [module-level documentation](crate::systick) for more information.
+#[cfg_attr(docsrs, doc(cfg(feature = "systick")))]
+pub struct SysTick {
+    syst: SYST,
+}
+
+impl SysTick {
+    /// Create a `SysTick` timer that counts `source` clock ticks
+    pub fn new(mut syst: SYST, source: SystClkSource) -> Self {
+        syst.disable_interrupt();
+        syst.disable_counter();
+        syst.set_clock_source(source);
+        REMAINING.store(0, Ordering::Relaxed);
+        CLOCK.store(false, Ordering::Relaxed);
+        SysTick { syst }
+    }
+
+    /// Return the SysTick peripheral
+    pub fn release(self) -> SYST {
+        self.syst
+    }
+
+    /// Wait for `ticks` clock counts to elapse
+    ///
+    /// The elapsed time depends on your clock source.
+    pub fn delay(&mut self, ticks: u32) -> Delay<'_> {
+        Delay {
+            syst: &mut self.syst,
+            ticks,
+            started: false,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+/// Start a counter period that covers as many of `ticks` as possible
+fn start(syst: &mut SYST, ticks: u32) {
+    // A reload value of zero never fires, so the shortest period is two ticks.
+    let period = ticks.max(2).min(MAX_PERIOD);
+    syst.disable_counter();
+    syst.set_reload(period - 1);
+    syst.clear_current();
+    syst.enable_interrupt();
+    syst.enable_counter();
+}
+
+/// A future that waits for the SysTick timer to elapse
+pub struct Delay<'a> {
+    syst: &'a mut SYST,
+    ticks: u32,
+    started: bool,
+    _pin: PhantomPinned,
+}
+
+impl<'a> Future for Delay<'a> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: no fields are moved out of the pinned future.
+        let this = unsafe { self.get_unchecked_mut() };
+        if 0 == this.ticks {
+            Poll::Ready(())
+        } else if this.started {
+            if 0 == REMAINING.load(Ordering::Acquire) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        } else {
+            unsafe { WAKER = Some(cx.waker().clone()) };
+            REMAINING.store(this.ticks, Ordering::Relaxed);
+            atomic::compiler_fence(Ordering::Release);
+            start(this.syst, this.ticks);
+            this.started = true;
+            Poll::Pending
+        }
+    }
+}
+
+impl<'a> Drop for Delay<'a> {
+    fn drop(&mut self) {
+        self.syst.disable_interrupt();
+        self.syst.disable_counter();
+        REMAINING.store(0, Ordering::Relaxed);
+    }
+}
+
+/// A SysTick clock with an alarm
+///
+/// A `Clock` interrupts once every period, and counts the periods in software. Its
+/// ticks are periods, not SysTick clock counts. The alarm is checked once per period,
+/// so choose a period that's as short as your alarms need, like one millisecond.
+///
+/// See the [`time`](crate::time) module for more information.
+///
+/// ```no_run
+/// use imxrt_async_hal as hal;
+/// use hal::{systick::Clock, time::TimeDriver};
+/// use cortex_m::peripheral::syst::SystClkSource;
+///
+/// let syst = cortex_m::Peripherals::take().unwrap().SYST;
+/// // 600MHz core clock, 1ms period
+/// let clock = Clock::new(syst, SystClkSource::Core, 600_000);
+/// let now_ms = clock.now();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "systick")))]
+pub struct Clock {
+    syst: SYST,
+}
+
+impl Clock {
+    /// Create a clock that ticks once every `period` `source` clock counts
+    ///
+    /// The time starts at zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is less than 2, or greater than 2^24.
+    pub fn new(mut syst: SYST, source: SystClkSource, period: u32) -> Self {
+        assert!(
+            (2..=MAX_PERIOD).contains(&period),
+            "SysTick clock period must be between 2 and 2^24"
+        );
+        syst.disable_interrupt();
+        syst.disable_counter();
+        syst.set_clock_source(source);
+        cortex_m::interrupt::free(|_| unsafe { ALARM = None });
+        TIME.store(0, Ordering::Relaxed);
+        CLOCK.store(true, Ordering::Release);
+        syst.set_reload(period - 1);
+        syst.clear_current();
+        syst.enable_interrupt();
+        syst.enable_counter();
+        Clock { syst }
+    }
+
+    /// Stop the clock, and return the SysTick peripheral
+    pub fn release(mut self) -> SYST {
+        self.syst.disable_interrupt();
+        self.syst.disable_counter();
+        CLOCK.store(false, Ordering::Relaxed);
+        cortex_m::interrupt::free(|_| unsafe { ALARM = None });
+        self.syst
+    }
+}
+
+impl crate::time::TimeDriver for Clock {
+    fn now(&self) -> u32 {
+        TIME.load(Ordering::Acquire)
+    }
+
+    fn set_alarm(&mut self, at: u32, waker: &Waker) -> bool {
+        // Safety: the critical section excludes the exception
+        cortex_m::interrupt::free(|_| unsafe {
+            if crate::time::is_future(at, TIME.load(Ordering::Relaxed)) {
+                ALARM = Some((at, waker.clone()));
+                true
+            } else {
+                ALARM = None;
+                false
+            }
+        })
+    }
+
+    fn clear_alarm(&mut self) {
+        // Safety: the critical section excludes the exception
+        cortex_m::interrupt::free(|_| unsafe { ALARM = None });
+    }
+}
+
+/// Count a [`Clock`] period, and wake the alarm if it's due
+#[inline(always)]
+fn on_clock_tick() {
+    let now = TIME.load(Ordering::Relaxed).wrapping_add(1);
+    TIME.store(now, Ordering::Release);
+    // Safety: tasks only access the alarm in critical sections
+    unsafe {
+        if let Some((at, _)) = &ALARM {
+            if !crate::time::is_future(*at, now) {
+                if let Some((_, waker)) = ALARM.take() {
+                    waker.wake();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "no-isr"))]
+#[cfg_attr(all(target_arch = "arm", feature = "rt"), crate::rt::exception)]
+#[cfg_attr(
+    any(not(target_arch = "arm"), not(feature = "rt")),
+    allow(unused, non_snake_case)
+)]
+#[cfg_attr(feature = "itcm", link_section = ".itcm")]
+fn SysTick() {
+    on_exception();
+}
+
+#[inline(always)]
+pub(crate) fn on_exception() {
+    if CLOCK.load(Ordering::Acquire) {
+        on_clock_tick();
+        return;
+    }
+    // Safety: the SysTick driver, or its delay, is borrowing the SYST
+    // peripheral, and it doesn't touch the peripheral while the
+    // interrupt is enabled.
+    let mut syst = unsafe { cortex_m::Peripherals::steal() }.SYST;
+    let elapsed = SYST::get_reload() + 1;
+    let remaining = REMAINING.load(Ordering::Relaxed).saturating_sub(elapsed);
+    REMAINING.store(remaining, Ordering::Release);
+    if 0 == remaining {
+        syst.disable_interrupt();
+        syst.disable_counter();
+        if let Some(waker) = unsafe { WAKER.take() } {
+            waker.wake();
+        }
+    } else {
+        start(&mut syst, remaining);
+    }
+}
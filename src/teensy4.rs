@@ -0,0 +1,136 @@
+//! Teensy 4.0 and 4.1 board support
+//!
+//! The `teensy4` module maps the processor pads to Teensy 4.0 pin numbers, and
+//! it wires drivers to the board's pins. It also provides the GPT setup that
+//! the project's examples use for delays.
+//!
+//! Enable the `"teensy4"` feature to use this module. The feature selects the
+//! `"imxrt1060"` chip.
+//!
+//! # Example
+//!
+//! Blink the LED every 250ms.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{ral, teensy4};
+//!
+//! let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let pins = teensy4::into_pins(pads);
+//! let mut led = teensy4::led(pins.p13);
+//!
+//! let ccm = ral::ccm::CCM::take().unwrap();
+//! let (mut gpt, _, _) = teensy4::new_gpt(ral::gpt::GPT2::take().unwrap(), &ccm);
+//!
+//! # async {
+//! loop {
+//!     teensy4::gpt_delay_ms(&mut gpt, 250).await;
+//!     led.toggle();
+//! }
+//! # };
+//! ```
+//!
+//! For the Teensy 4.1 pinout, see [`t41`].
+
+#[cfg(feature = "gpt")]
+use crate::ral;
+#[cfg(any(feature = "spi", feature = "uart"))]
+use crate::{instance, iomuxc::consts};
+
+pub mod t41;
+
+pub use teensy4_pins::t40::{into_pins, Pins, P10, P11, P12, P13, P14, P15};
+
+/// The LED on pin 13
+#[cfg(feature = "gpio")]
+pub type LED = crate::gpio::GPIO<P13, crate::gpio::Output>;
+
+/// Prepare the LED on pin 13 as an output
+///
+/// Pin 13 is also the SPI serial clock. You may use either the LED or [`spi`], but
+/// not both.
+#[cfg(feature = "gpio")]
+pub fn led(p13: P13) -> LED {
+    crate::gpio::GPIO::new(p13).output()
+}
+
+/// A UART using LPUART2, with TX on pin 14 and RX on pin 15
+#[cfg(feature = "uart")]
+pub type UART = crate::UART<P14, P15>;
+
+/// Create a [`UART`] with TX on pin 14 and RX on pin 15
+///
+/// You're responsible for the LPUART clock configuration, and the baud rate.
+#[cfg(feature = "uart")]
+pub fn uart(lpuart2: instance::UART<consts::U2>, p14: P14, p15: P15) -> UART {
+    crate::UART::new(lpuart2, p14, p15)
+}
+
+/// A SPI using LPSPI4, with SDO on pin 11, SDI on pin 12, SCK on pin 13,
+/// and PCS0 on pin 10
+#[cfg(feature = "spi")]
+pub type SPI = crate::SPI<crate::SPIPins<P11, P12, P13, P10>>;
+
+/// Create a [`SPI`] on pins 10 through 13
+///
+/// You're responsible for the LPSPI clock configuration, and the serial clock speed.
+#[cfg(feature = "spi")]
+pub fn spi(lpspi4: instance::SPI<consts::U4>, p11: P11, p12: P12, p13: P13, p10: P10) -> SPI {
+    crate::SPI::new(
+        crate::SPIPins {
+            sdo: p11,
+            sdi: p12,
+            sck: p13,
+            pcs0: p10,
+        },
+        lpspi4,
+    )
+}
+
+/// Configure a GPT to count at 200KHz
+///
+/// The timers know their counter frequency, so you may use
+/// [`delay_ms`](crate::GPT::delay_ms()) and [`delay_us`](crate::GPT::delay_us()), or
+/// [`gpt_delay_ms`] and [`gpt_delay_us`], to wait with the timers.
+#[cfg(feature = "gpt")]
+pub fn new_gpt(
+    gpt: ral::gpt::Instance,
+    ccm: &ral::ccm::Instance,
+) -> (crate::GPT, crate::GPT, crate::GPT) {
+    // Select 24MHz crystal oscillator, divide by 24 == 1MHz clock
+    ral::modify_reg!(ral::ccm, ccm, CSCMR1, PERCLK_PODF: DIVIDE_24, PERCLK_CLK_SEL: 1);
+
+    // Enable GPT clock gates...
+    match &*gpt as *const _ {
+        ral::gpt::GPT1 => ral::modify_reg!(ral::ccm, ccm, CCGR1, CG10: 0b11, CG11: 0b11),
+        ral::gpt::GPT2 => ral::modify_reg!(ral::ccm, ccm, CCGR0, CG12: 0b11, CG13: 0b11),
+        _ => unreachable!("There are only two GPT peripherals"),
+    }
+
+    // 1MHz peripheral clock / 5 == 200KHz
+    crate::GPT::with_clock(
+        gpt,
+        crate::gpt::ClockSource::Peripheral { hz: PERCLK_HZ },
+        PERCLK_HZ / GPT_CLOCK_HZ,
+    )
+}
+
+/// The peripheral clock frequency that [`new_gpt`] selects
+#[cfg(feature = "gpt")]
+const PERCLK_HZ: u32 = 1_000_000;
+
+/// The counter frequency of a GPT from [`new_gpt`]
+#[cfg(feature = "gpt")]
+const GPT_CLOCK_HZ: u32 = 200_000;
+
+/// Use a GPT from [`new_gpt`] to delay `ms` milliseconds
+#[cfg(feature = "gpt")]
+pub fn gpt_delay_ms(gpt: &mut crate::GPT, ms: u32) -> crate::gpt::Delay<'_> {
+    gpt.delay_ms(ms)
+}
+
+/// Use a GPT from [`new_gpt`] to delay `us` microseconds
+#[cfg(feature = "gpt")]
+pub fn gpt_delay_us(gpt: &mut crate::GPT, us: u32) -> crate::gpt::Delay<'_> {
+    gpt.delay_us(us)
+}
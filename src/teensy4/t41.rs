@@ -0,0 +1,70 @@
+//! Teensy 4.1 pins
+//!
+//! The Teensy 4.1 has the same pins 0 through 33 as the Teensy 4.0, so the
+//! drivers in the [`teensy4`](crate::teensy4) module work with either board.
+//! [`into_pins`] also provides the 4.1's additional pins, including the SD card
+//! pads (pins 42 through 47) and the bottom-side pads.
+//!
+//! The Ethernet PHY connects to pads that aren't broken out to pins. Use [`split`]
+//! to acquire both the pins, and the [`Ethernet`] pads.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{ral, teensy4::t41};
+//!
+//! let pads = hal::iomuxc::new(ral::iomuxc::IOMUXC::take().unwrap());
+//! let (pins, ethernet) = t41::split(pads);
+//! ```
+
+use crate::iomuxc::pads::{b1::*, Pads};
+
+pub use teensy4_pins::t41::{into_pins, Pins};
+
+/// Ethernet PHY pads
+///
+/// The Teensy 4.1 connects these pads to its Ethernet PHY using RMII.
+pub struct Ethernet {
+    /// Receive data 0
+    pub rxd0: B1_04,
+    /// Receive data 1
+    pub rxd1: B1_05,
+    /// Carrier sense and receive data valid
+    pub crs_dv: B1_06,
+    /// Transmit data 0
+    pub txd0: B1_07,
+    /// Transmit data 1
+    pub txd1: B1_08,
+    /// Transmit enable
+    pub tx_en: B1_09,
+    /// 50MHz reference clock
+    pub ref_clk: B1_10,
+    /// Receive error
+    pub rx_er: B1_11,
+    /// Management data clock
+    pub mdc: B1_14,
+    /// Management data I/O
+    pub mdio: B1_15,
+}
+
+/// Convert the processor pads into Teensy 4.1 pins, and Ethernet pads
+pub fn split(pads: Pads) -> (Pins, Ethernet) {
+    let pins = into_pins(pads);
+    // Safety: into_pins consumed all of the pads, and it does not
+    // include the Ethernet pads in the pins. Nothing else can
+    // reference these pads.
+    let ethernet = unsafe {
+        Ethernet {
+            rxd0: B1_04::new(),
+            rxd1: B1_05::new(),
+            crs_dv: B1_06::new(),
+            txd0: B1_07::new(),
+            txd1: B1_08::new(),
+            tx_en: B1_09::new(),
+            ref_clk: B1_10::new(),
+            rx_er: B1_11::new(),
+            mdc: B1_14::new(),
+            mdio: B1_15::new(),
+        }
+    };
+    (pins, ethernet)
+}
@@ -0,0 +1,61 @@
+//! Timer integration for executors
+//!
+//! A [`TimeDriver`] keeps time, and wakes a task at a scheduled tick. An executor can
+//! use a `TimeDriver` as its tickless scheduling source: it reads the time with
+//! [`now`](TimeDriver::now()), and it sets one alarm for the next deadline with
+//! [`set_alarm`](TimeDriver::set_alarm()).
+//!
+//! Each [`GPT`](crate::GPT) timer implements `TimeDriver`. The three timers from one GPT
+//! share a free-running counter, and each timer has its own alarm.
+//!
+//! The PIT and SysTick timers count down, and reload when they reach zero, so one of
+//! their timers can't both keep time and schedule an alarm. Instead,
+//!
+//! - a [`pit::AlarmClock`](crate::pit::AlarmClock) pairs two PIT channels: one keeps
+//!   time, and the other schedules the alarm.
+//! - a `systick::Clock`, available with the `"systick"` feature, interrupts
+//!   periodically, and counts periods in software. Its ticks are periods.
+//!
+//! # Example
+//!
+//! Wake a task 10ms from now, using a 1MHz GPT.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{ral, time::TimeDriver};
+//! use core::task::Waker;
+//!
+//! # fn waker() -> Waker { unimplemented!() }
+//! let (mut gpt, _, _) = hal::GPT::new(ral::gpt::GPT2::take().unwrap());
+//!
+//! let deadline = gpt.now().wrapping_add(10_000);
+//! if !gpt.set_alarm(deadline, &waker()) {
+//!     // The deadline already passed; run the task now...
+//! }
+//! ```
+
+use core::task::Waker;
+
+/// A timer that keeps time, and wakes a task at a scheduled tick
+///
+/// Ticks are `u32`s that wrap around. A tick is in the future if it's less than half of
+/// the counter's range after [`now`](TimeDriver::now()); otherwise, it's in the past.
+///
+/// See the [module-level documentation](crate::time) for more information.
+pub trait TimeDriver {
+    /// Returns the current tick
+    fn now(&self) -> u32;
+    /// Wake `waker` once the timer reaches tick `at`
+    ///
+    /// The alarm replaces any earlier alarm. Returns `false` if `at` isn't in the future,
+    /// and the alarm isn't set. The caller should handle the deadline now.
+    fn set_alarm(&mut self, at: u32, waker: &Waker) -> bool;
+    /// Cancel the alarm
+    fn clear_alarm(&mut self);
+}
+
+/// Returns `true` if tick `at` is in the future, relative to tick `now`
+pub(crate) fn is_future(at: u32, now: u32) -> bool {
+    let ahead = at.wrapping_sub(now);
+    ahead != 0 && ahead <= u32::MAX / 2
+}
@@ -1,7 +1,12 @@
 //! UART serial driver
 
 use crate::{dma, instance::Inst, iomuxc, ral};
-use core::fmt;
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
 
 /// UART Serial driver
 ///
@@ -69,12 +74,55 @@ pub struct UART<TX, RX> {
     rx: RX,
 }
 
+#[cfg(not(feature = "diagnostics"))]
 impl<TX, RX> fmt::Debug for UART<TX, RX> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "UART{}", self.uart.inst())
     }
 }
 
+#[cfg(feature = "diagnostics")]
+impl<TX, RX> fmt::Debug for UART<TX, RX> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (sbr, osr) = ral::read_reg!(ral::lpuart, self.uart, BAUD, SBR, OSR);
+        let (te, re) = ral::read_reg!(ral::lpuart, self.uart, CTRL, TE, RE);
+        let (tdre, tc, rdrf, idle, or, nf, fe, pf) = ral::read_reg!(
+            ral::lpuart,
+            self.uart,
+            STAT,
+            TDRE,
+            TC,
+            RDRF,
+            IDLE,
+            OR,
+            NF,
+            FE,
+            PF
+        );
+        let (txcount, rxcount) = ral::read_reg!(ral::lpuart, self.uart, WATER, TXCOUNT, RXCOUNT);
+        let (tdmae, rdmae) = ral::read_reg!(ral::lpuart, self.uart, BAUD, TDMAE, RDMAE);
+        f.debug_struct("UART")
+            .field("instance", &self.uart.inst())
+            .field("sbr", &sbr)
+            .field("osr", &(osr + 1))
+            .field("tx_enabled", &(te != 0))
+            .field("rx_enabled", &(re != 0))
+            .field("tx_dma", &(tdmae != 0))
+            .field("rx_dma", &(rdmae != 0))
+            .field("tx_data_empty", &(tdre != 0))
+            .field("tx_complete", &(tc != 0))
+            .field("rx_data_full", &(rdrf != 0))
+            .field("idle", &(idle != 0))
+            .field("overrun", &(or != 0))
+            .field("noise", &(nf != 0))
+            .field("framing_error", &(fe != 0))
+            .field("parity_error", &(pf != 0))
+            .field("tx_fifo_count", &txcount)
+            .field("rx_fifo_count", &rxcount)
+            .finish()
+    }
+}
+
 impl<TX, RX, M> UART<TX, RX>
 where
     TX: iomuxc::uart::Pin<Direction = iomuxc::uart::TX, Module = M>,
@@ -86,6 +134,7 @@ where
     /// The baud rate of the returned `UART` is unspecified. Make sure you use [`set_baud`](UART::set_baud())
     /// to properly configure the driver.
     pub fn new(uart: crate::instance::UART<M>, mut tx: TX, mut rx: RX) -> UART<TX, RX> {
+        crate::ccm::assert_gates_on(crate::ccm::Peripheral::UART(M::USIZE));
         crate::iomuxc::uart::prepare(&mut tx);
         crate::iomuxc::uart::prepare(&mut rx);
 
@@ -95,8 +144,50 @@ where
             rx,
         };
         ral::modify_reg!(ral::lpuart, uart.uart, CTRL, TE: TE_1, RE: RE_1);
+
+        static ONCE: crate::once::Once = crate::once::new();
+        ONCE.call(|| unsafe {
+            #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
+            compile_error!("Ensure that LPUART interrupts are unmasked");
+
+            // imxrt1010, imxrt1060
+            cortex_m::peripheral::NVIC::unmask(crate::ral::interrupt::LPUART1);
+            // imxrt1010, imxrt1060
+            cortex_m::peripheral::NVIC::unmask(crate::ral::interrupt::LPUART2);
+            // imxrt1010, imxrt1060
+            cortex_m::peripheral::NVIC::unmask(crate::ral::interrupt::LPUART3);
+            // imxrt1010, imxrt1060
+            cortex_m::peripheral::NVIC::unmask(crate::ral::interrupt::LPUART4);
+            #[cfg(feature = "imxrt1060")]
+            cortex_m::peripheral::NVIC::unmask(crate::ral::interrupt::LPUART5);
+            #[cfg(feature = "imxrt1060")]
+            cortex_m::peripheral::NVIC::unmask(crate::ral::interrupt::LPUART6);
+            #[cfg(feature = "imxrt1060")]
+            cortex_m::peripheral::NVIC::unmask(crate::ral::interrupt::LPUART7);
+            #[cfg(feature = "imxrt1060")]
+            cortex_m::peripheral::NVIC::unmask(crate::ral::interrupt::LPUART8);
+        });
+
         uart
     }
+
+    /// Create a `UART` that uses the current peripheral and pin configuration
+    ///
+    /// Unlike [`new`](UART::new()), `steal` does not configure the pins or the
+    /// peripheral, so the baud rate is unchanged.
+    ///
+    /// # Safety
+    ///
+    /// The original driver may still exist, and it may be in the middle of an operation.
+    /// Only use `steal` when the original owner can no longer run, like in a panic or
+    /// fault handler.
+    pub unsafe fn steal(uart: crate::instance::UART<M>, tx: TX, rx: RX) -> UART<TX, RX> {
+        UART {
+            uart: uart.release(),
+            tx,
+            rx,
+        }
+    }
 }
 
 impl<TX, RX> UART<TX, RX> {
@@ -118,6 +209,142 @@ impl<TX, RX> UART<TX, RX> {
         Ok(())
     }
 
+    /// Set the parity, or disable parity with `None`
+    ///
+    /// With parity, each frame has eight data bits, then a parity bit. The default is
+    /// no parity.
+    pub fn set_parity(&mut self, parity: Option<Parity>) {
+        self.while_disabled(|this| match parity {
+            None => ral::modify_reg!(ral::lpuart, this.uart, CTRL, M: M_0, PE: PE_0),
+            Some(Parity::Even) => {
+                ral::modify_reg!(ral::lpuart, this.uart, CTRL, M: M_1, PE: PE_1, PT: PT_0)
+            }
+            Some(Parity::Odd) => {
+                ral::modify_reg!(ral::lpuart, this.uart, CTRL, M: M_1, PE: PE_1, PT: PT_1)
+            }
+        });
+    }
+
+    /// Set the number of stop bits
+    ///
+    /// The default is [`StopBits::One`].
+    pub fn set_stop_bits(&mut self, stop_bits: StopBits) {
+        let sbns = match stop_bits {
+            StopBits::One => 0,
+            StopBits::Two => 1,
+        };
+        self.while_disabled(|this| ral::modify_reg!(ral::lpuart, this.uart, BAUD, SBNS: sbns));
+    }
+
+    /// Invert the TX and RX signals
+    ///
+    /// When `inverted` is `true`, the idle line is low. Use inversion for protocols
+    /// like SBUS, or for transceivers that invert the signal. The default is no
+    /// inversion.
+    pub fn set_inversion(&mut self, inverted: bool) {
+        let inverted = inverted as u32;
+        self.while_disabled(|this| {
+            ral::modify_reg!(ral::lpuart, this.uart, CTRL, TXINV: inverted);
+            modify_stat(&this.uart, |stat| {
+                use ral::lpuart::STAT::RXINV;
+                (stat & !RXINV::mask) | (inverted << RXINV::offset)
+            });
+        });
+    }
+
+    /// Enable or disable the IrDA infrared encoder and decoder
+    ///
+    /// When enabled, the transmitter sends a narrow pulse for each zero bit, and the
+    /// receiver decodes narrow pulses. The pulse width is as close as possible to the
+    /// IrDA SIR width, 3/16 of a bit. Call `set_irda` after [`set_baud`](UART::set_baud()),
+    /// since the width depends on the baud rate's oversampling ratio. At low oversampling
+    /// ratios, the pulse is wider than 3/16 of a bit; at high ratios, it's narrower.
+    ///
+    /// The receiver expects active-high pulses. If your IrDA transceiver's output is
+    /// active-low, use [`set_inversion`](UART::set_inversion()). The default is disabled.
+    pub fn set_irda(&mut self, enable: bool) {
+        self.while_disabled(|this| {
+            if enable {
+                let osr = ral::read_reg!(ral::lpuart, this.uart, BAUD, OSR) + 1;
+                // Round 3/16 of the oversampling ratio to the closest supported width
+                let samples = ((3 * osr + 8) / 16).max(1).min(4);
+                ral::modify_reg!(ral::lpuart, this.uart, MODIR, TNP: samples - 1, IREN: 1);
+            } else {
+                ral::modify_reg!(ral::lpuart, this.uart, MODIR, IREN: 0);
+            }
+        });
+    }
+
+    /// Configure breaks for LIN
+    ///
+    /// When `enable` is `true`, [`send_break`](UART::send_break()) sends a 13-bit break,
+    /// and the receiver detects breaks, instead of receiving them as zero bytes. The
+    /// default is disabled.
+    pub fn set_lin_break(&mut self, enable: bool) {
+        let enable = enable as u32;
+        self.while_disabled(|this| {
+            modify_stat(&this.uart, |stat| {
+                use ral::lpuart::STAT::{BRK13, LBKDE};
+                (stat & !(BRK13::mask | LBKDE::mask))
+                    | (enable << BRK13::offset)
+                    | (enable << LBKDE::offset)
+            })
+        });
+    }
+
+    /// Set how long the receive line must be idle before the receiver reports it idle
+    ///
+    /// `characters` is the idle time, in character times, counted from the last stop
+    /// bit. It must be a power of two, from 1 to 128. The default is one character.
+    /// See [`idle`](UART::idle()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `characters` isn't a power of two from 1 to 128.
+    pub fn set_idle_characters(&mut self, characters: u8) {
+        assert!(
+            characters.is_power_of_two(),
+            "Idle characters must be a power of two"
+        );
+        let idlecfg = characters.trailing_zeros();
+        self.while_disabled(|this| {
+            ral::modify_reg!(ral::lpuart, this.uart, CTRL, IDLECFG: idlecfg, ILT: 1);
+        });
+    }
+
+    /// Wait for the receive line to go idle
+    ///
+    /// The receiver reports an idle line once it's been idle for the
+    /// [idle time](UART::set_idle_characters()) after at least one character. If that
+    /// happened since the last `idle` completed, `idle` completes right away.
+    ///
+    /// Once idle, `idle` discards the data in the receive FIFO, and clears a receive
+    /// overrun, so the next read starts with the first character after the idle line.
+    /// Use `idle` to find the start of a message, for protocols that separate messages
+    /// with gaps.
+    pub fn idle(&mut self) -> Idle<'_> {
+        Idle { uart: &self.uart }
+    }
+
+    /// Queue a break character
+    ///
+    /// The break follows any data that's already in the transmit FIFO. If the FIFO is
+    /// full, `send_break` busy-waits for space; it doesn't yield to other tasks. To avoid
+    /// the wait, [`flush`](UART::flush()) the transmitter first.
+    pub fn send_break(&mut self) {
+        send_break(&self.uart);
+    }
+
+    /// Wait for the transmitter to finish sending
+    ///
+    /// A [`dma_write`](UART::dma_write()) completes once its data is in the transmit FIFO.
+    /// `flush` completes once the FIFO is empty, and the last stop bit is on the wire.
+    /// Use `flush` before you turn around an RS-485 transceiver, or before you power down
+    /// the UART.
+    pub fn flush(&mut self) -> Flush<'_> {
+        Flush { uart: &self.uart }
+    }
+
     fn while_disabled<F: FnMut(&mut Self) -> R, R>(&mut self, mut act: F) -> R {
         ral::modify_reg!(
             ral::lpuart,
@@ -134,7 +361,11 @@ impl<TX, RX> UART<TX, RX> {
     }
 
     /// Return the pins and RAL instance that comprise the UART driver
+    ///
+    /// `release` disables the transmitter and receiver. You may prepare the returned
+    /// pins for another function, like a [`GPIO`](crate::gpio::GPIO).
     pub fn release(self) -> (TX, RX, ral::lpuart::Instance) {
+        ral::modify_reg!(ral::lpuart, self.uart, CTRL, TE: TE_0, RE: RE_0);
         (self.tx, self.rx, self.uart)
     }
 
@@ -160,6 +391,201 @@ impl<TX, RX> UART<TX, RX> {
     ) -> dma::Rx<'a, Self, u8> {
         dma::receive(channel, self, buffer)
     }
+
+    /// Use a DMA channel to read data from the UART peripheral into a static buffer
+    ///
+    /// Completes when `buffer` is filled, and returns `buffer`. Unlike [`dma_read`](UART::dma_read()),
+    /// the buffer outlives the future, so the DMA transfer cannot corrupt memory if the future is
+    /// forgotten. If you drop or forget the future before it completes, `buffer` is lost.
+    pub fn dma_read_static<'a>(
+        &'a mut self,
+        channel: &'a mut dma::Channel,
+        buffer: &'static mut [u8],
+    ) -> ReadStatic<'a, TX, RX> {
+        let len = buffer.len();
+        let ptr = buffer.as_mut_ptr();
+        ReadStatic {
+            rx: Some(dma::receive(channel, self, buffer)),
+            buffer: Some((ptr, len)),
+        }
+    }
+}
+
+/// A future that reads UART data into a static buffer
+///
+/// Use [`dma_read_static`](UART::dma_read_static()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub struct ReadStatic<'a, TX, RX> {
+    /// The DMA read, dropped once it completes
+    rx: Option<dma::Rx<'a, UART<TX, RX>, u8>>,
+    /// The static buffer, returned once the read completes
+    buffer: Option<(*mut u8, usize)>,
+}
+
+impl<'a, TX, RX> Future for ReadStatic<'a, TX, RX>
+where
+    dma::Rx<'a, UART<TX, RX>, u8>: Future<Output = Result<(), dma::Error>>,
+{
+    type Output = Result<&'static mut [u8], dma::Error>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the inner future is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut rx = unsafe { Pin::new_unchecked(&mut this.rx) };
+        let result = match rx
+            .as_mut()
+            .as_pin_mut()
+            .expect("ReadStatic polled after completion")
+            .poll(cx)
+        {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(result) => result,
+        };
+        // Drop the read in place, which invalidates the buffer, before handing the
+        // buffer back to the caller.
+        rx.set(None);
+        let (ptr, len) = this.buffer.take().unwrap();
+        // Safety: the pointer and length came from a `&'static mut [u8]`
+        // that the caller gave up, and the DMA read is dropped.
+        result
+            .map(|_| unsafe { core::slice::from_raw_parts_mut(ptr, len) })
+            .into()
+    }
+}
+
+/// A future that waits for the transmitter to finish sending
+///
+/// Use [`flush`](UART::flush()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub struct Flush<'a> {
+    uart: &'a ral::lpuart::Instance,
+}
+
+impl Future for Flush<'_> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if ral::read_reg!(ral::lpuart, self.uart, STAT, TC == 1) {
+            return Poll::Ready(());
+        }
+        cortex_m::interrupt::free(|_| {
+            *waker(self.uart) = Some(cx.waker().clone());
+            ral::modify_reg!(ral::lpuart, self.uart, CTRL, TCIE: 1);
+        });
+        Poll::Pending
+    }
+}
+
+impl Drop for Flush<'_> {
+    fn drop(&mut self) {
+        cortex_m::interrupt::free(|_| {
+            ral::modify_reg!(ral::lpuart, self.uart, CTRL, TCIE: 0);
+            *waker(self.uart) = None;
+        });
+    }
+}
+
+/// A future that waits for the receive line to go idle
+///
+/// Use [`idle`](UART::idle()) to create this future.
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub struct Idle<'a> {
+    uart: &'a ral::lpuart::Instance,
+}
+
+impl Future for Idle<'_> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if ral::read_reg!(ral::lpuart, self.uart, STAT, IDLE == 1) {
+            use ral::lpuart::STAT::{IDLE, OR};
+            ral::modify_reg!(ral::lpuart, self.uart, FIFO, RXFLUSH: RXFLUSH_1);
+            clear_stat(self.uart, IDLE::mask | OR::mask);
+            return Poll::Ready(());
+        }
+        cortex_m::interrupt::free(|_| {
+            *waker(self.uart) = Some(cx.waker().clone());
+            ral::modify_reg!(ral::lpuart, self.uart, CTRL, ILIE: 1);
+        });
+        Poll::Pending
+    }
+}
+
+impl Drop for Idle<'_> {
+    fn drop(&mut self) {
+        cortex_m::interrupt::free(|_| {
+            ral::modify_reg!(ral::lpuart, self.uart, CTRL, ILIE: 0);
+            *waker(self.uart) = None;
+        });
+    }
+}
+
+/// Returns the transmit complete and idle line waker for this LPUART instance
+fn waker(uart: &ral::lpuart::Instance) -> &'static mut Option<Waker> {
+    static mut WAKERS: [Option<Waker>; 8] = [None, None, None, None, None, None, None, None];
+    unsafe { &mut WAKERS[uart.inst() - 1] }
+}
+
+#[inline(always)]
+fn on_interrupt(uart: &ral::lpuart::Instance) {
+    if ral::read_reg!(ral::lpuart, uart, CTRL, TCIE == 1)
+        && ral::read_reg!(ral::lpuart, uart, STAT, TC == 1)
+    {
+        ral::modify_reg!(ral::lpuart, uart, CTRL, TCIE: 0);
+        if let Some(waker) = waker(uart).take() {
+            waker.wake();
+        }
+    }
+    // The idle future clears the flag, along with the receive FIFO.
+    if ral::read_reg!(ral::lpuart, uart, CTRL, ILIE == 1)
+        && ral::read_reg!(ral::lpuart, uart, STAT, IDLE == 1)
+    {
+        ral::modify_reg!(ral::lpuart, uart, CTRL, ILIE: 0);
+        if let Some(waker) = waker(uart).take() {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
+compile_error!("Ensure that LPUART interrupts are correctly defined");
+
+interrupts! {
+    handler!{unsafe fn LPUART1() {
+        on_interrupt(&ral::lpuart::LPUART1::steal());
+    }}
+
+
+    handler!{unsafe fn LPUART2() {
+        on_interrupt(&ral::lpuart::LPUART2::steal());
+    }}
+
+
+    handler!{unsafe fn LPUART3() {
+        on_interrupt(&ral::lpuart::LPUART3::steal());
+    }}
+
+
+    handler!{unsafe fn LPUART4() {
+        on_interrupt(&ral::lpuart::LPUART4::steal());
+    }}
+
+    #[cfg(feature = "imxrt1060")]
+    handler!{unsafe fn LPUART5() {
+        on_interrupt(&ral::lpuart::LPUART5::steal());
+    }}
+
+    #[cfg(feature = "imxrt1060")]
+    handler!{unsafe fn LPUART6() {
+        on_interrupt(&ral::lpuart::LPUART6::steal());
+    }}
+
+    #[cfg(feature = "imxrt1060")]
+    handler!{unsafe fn LPUART7() {
+        on_interrupt(&ral::lpuart::LPUART7::steal());
+    }}
+
+    #[cfg(feature = "imxrt1060")]
+    handler!{unsafe fn LPUART8() {
+        on_interrupt(&ral::lpuart::LPUART8::steal());
+    }}
 }
 
 /// An opaque type that describes timing configurations
@@ -173,6 +599,30 @@ struct Timings {
     sbr: u16,
 }
 
+/// UART parity
+///
+/// See [`set_parity`](UART::set_parity()) for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub enum Parity {
+    /// Even parity
+    Even,
+    /// Odd parity
+    Odd,
+}
+
+/// UART stop bits
+///
+/// See [`set_stop_bits`](UART::set_stop_bits()) for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub enum StopBits {
+    /// One stop bit
+    One,
+    /// Two stop bits
+    Two,
+}
+
 /// Errors propagated from a [`UART`] device
 #[non_exhaustive]
 #[derive(Debug)]
@@ -225,71 +675,351 @@ fn timings(effective_clock: u32, baud: u32) -> Result<Timings, Error> {
 
 unsafe impl<TX, RX> dma::Destination<u8> for UART<TX, RX> {
     fn destination_signal(&self) -> u32 {
-        use dma::Source;
-        self.source_signal() - 1
+        tx_signal(&self.uart)
     }
     fn destination_address(&self) -> *const u8 {
-        &self.uart.DATA as *const _ as *const u8
+        data_address(&self.uart)
     }
     fn enable_destination(&mut self) {
-        ral::modify_reg!(ral::lpuart, self.uart, BAUD, TDMAE: 1);
+        enable_tx_dma(&self.uart);
     }
     fn disable_destination(&mut self) {
-        while ral::read_reg!(ral::lpuart, self.uart, BAUD, TDMAE == 1) {
-            ral::modify_reg!(ral::lpuart, self.uart, BAUD, TDMAE: 0);
-        }
+        disable_tx_dma(&self.uart);
     }
 }
 
 unsafe impl<TX, RX> dma::Source<u8> for UART<TX, RX> {
     fn source_signal(&self) -> u32 {
-        // Make sure that the match expression will never hit the unreachable!() case.
-        // The comments and conditional compiles show what we're currently considering in
-        // that match. If your chip isn't listed, it's not something we considered.
-        #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
-        compile_error!("Ensure that LPUART DMAMUX RX channels are correct");
-
-        // See table 4-3 of the iMXRT1060 Reference Manual (Rev 2)
-        match &*self.uart as *const _ {
-            // imxrt1010, imxrt1060
-            ral::lpuart::LPUART1 => 3,
-            // imxrt1010, imxrt1060
-            ral::lpuart::LPUART2 => 67,
-            // imxrt1010, imxrt1060
-            ral::lpuart::LPUART3 => 5,
-            // imxrt1010, imxrt1060
-            ral::lpuart::LPUART4 => 69,
+        rx_signal(&self.uart)
+    }
+    fn source_address(&self) -> *const u8 {
+        data_address(&self.uart)
+    }
+    fn enable_source(&mut self) {
+        enable_rx_dma(&self.uart);
+    }
+    fn disable_source(&mut self) {
+        disable_rx_dma(&self.uart);
+    }
+}
+
+/// The UART transmitter, used as a DMA destination
+///
+/// See [`UARTTx`] for more information.
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub struct Transmitter {
+    uart: ral::lpuart::Instance,
+}
+
+/// The UART receiver, used as a DMA source
+///
+/// See [`UARTRx`] for more information.
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub struct Receiver {
+    uart: ral::lpuart::Instance,
+}
+
+unsafe impl dma::Destination<u8> for Transmitter {
+    fn destination_signal(&self) -> u32 {
+        tx_signal(&self.uart)
+    }
+    fn destination_address(&self) -> *const u8 {
+        data_address(&self.uart)
+    }
+    fn enable_destination(&mut self) {
+        enable_tx_dma(&self.uart);
+    }
+    fn disable_destination(&mut self) {
+        disable_tx_dma(&self.uart);
+    }
+}
+
+unsafe impl dma::Source<u8> for Receiver {
+    fn source_signal(&self) -> u32 {
+        rx_signal(&self.uart)
+    }
+    fn source_address(&self) -> *const u8 {
+        data_address(&self.uart)
+    }
+    fn enable_source(&mut self) {
+        enable_rx_dma(&self.uart);
+    }
+    fn disable_source(&mut self) {
+        disable_rx_dma(&self.uart);
+    }
+}
+
+/// The transmit half of a UART, with its own DMA channel
+///
+/// Use [`with_dma`](UART::with_dma()) to create the transmit and receive halves.
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub struct UARTTx<TX> {
+    transmitter: Transmitter,
+    channel: dma::Channel,
+    tx: TX,
+}
+
+/// The receive half of a UART, with its own DMA channel
+///
+/// Use [`with_dma`](UART::with_dma()) to create the transmit and receive halves.
+#[cfg_attr(docsrs, doc(cfg(feature = "uart")))]
+pub struct UARTRx<RX> {
+    receiver: Receiver,
+    channel: dma::Channel,
+    rx: RX,
+}
+
+impl<TX, RX> UART<TX, RX> {
+    /// Split the UART into transmit and receive halves, each with its own DMA channel
+    ///
+    /// A [`write`](UARTTx::write()) and a [`read`](UARTRx::read()) may then be in flight at
+    /// the same time. `with_dma` enables the interrupt on completion for both channels.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use imxrt_async_hal as hal;
+    /// use hal::{dma, iomuxc, UART, instance};
+    /// use hal::ral::{dma0::DMA0, dmamux::DMAMUX, iomuxc::IOMUXC, lpuart::LPUART2};
+    ///
+    /// let pads = IOMUXC::take().map(iomuxc::new).unwrap();
+    /// let mut channels = dma::channels(DMA0::take().unwrap(), DMAMUX::take().unwrap());
+    /// let uart2 = LPUART2::take().and_then(instance::uart).unwrap();
+    /// let uart = UART::new(uart2, pads.ad_b1.p02, pads.ad_b1.p03);
+    ///
+    /// let (mut tx, mut rx) = uart.with_dma(
+    ///     channels[7].take().unwrap(),
+    ///     channels[8].take().unwrap(),
+    /// );
+    ///
+    /// # async {
+    /// let mut input = [0; 4];
+    /// let output = [1, 2, 3, 4];
+    /// let (read, write) = futures::future::join(rx.read(&mut input), tx.write(&output)).await;
+    /// # };
+    /// ```
+    pub fn with_dma(
+        self,
+        mut tx_channel: dma::Channel,
+        mut rx_channel: dma::Channel,
+    ) -> (UARTTx<TX>, UARTRx<RX>) {
+        tx_channel.set_interrupt_on_completion(true);
+        rx_channel.set_interrupt_on_completion(true);
+        (
+            UARTTx {
+                transmitter: Transmitter {
+                    uart: steal(&self.uart),
+                },
+                channel: tx_channel,
+                tx: self.tx,
+            },
+            UARTRx {
+                receiver: Receiver { uart: self.uart },
+                channel: rx_channel,
+                rx: self.rx,
+            },
+        )
+    }
+
+    /// Join the transmit and receive halves, returning the UART and both DMA channels
+    ///
+    /// The return is `(uart, tx_channel, rx_channel)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the halves came from different UARTs.
+    pub fn join(tx: UARTTx<TX>, rx: UARTRx<RX>) -> (Self, dma::Channel, dma::Channel) {
+        assert_eq!(
+            tx.transmitter.uart.inst(),
+            rx.receiver.uart.inst(),
+            "UART halves are from different UARTs"
+        );
+        (
+            UART {
+                uart: rx.receiver.uart,
+                tx: tx.tx,
+                rx: rx.rx,
+            },
+            tx.channel,
+            rx.channel,
+        )
+    }
+}
+
+impl<TX> UARTTx<TX> {
+    /// Use the DMA channel to write data to the UART peripheral
+    ///
+    /// Completes when all data in `buffer` has been written to the UART
+    /// peripheral.
+    pub fn write<'a>(&'a mut self, buffer: &'a [u8]) -> dma::Tx<'a, Transmitter, u8> {
+        dma::transfer(&mut self.channel, buffer, &mut self.transmitter)
+    }
+
+    /// Queue a break character
+    ///
+    /// See [`UART::send_break`](UART::send_break()) for more information.
+    pub fn send_break(&mut self) {
+        send_break(&self.transmitter.uart);
+    }
+
+    /// Wait for the transmitter to finish sending
+    ///
+    /// See [`UART::flush`](UART::flush()) for more information.
+    pub fn flush(&mut self) -> Flush<'_> {
+        Flush {
+            uart: &self.transmitter.uart,
+        }
+    }
+}
+
+impl<RX> UARTRx<RX> {
+    /// Use the DMA channel to read data from the UART peripheral
+    ///
+    /// Completes when `buffer` is filled.
+    pub fn read<'a>(&'a mut self, buffer: &'a mut [u8]) -> dma::Rx<'a, Receiver, u8> {
+        dma::receive(&mut self.channel, &mut self.receiver, buffer)
+    }
+
+    /// Discard any data in the receive FIFO, and clear a receiver overrun
+    ///
+    /// Use `clear` after you cancel a [`read`](UARTRx::read()), so that the next read
+    /// doesn't start with leftover bytes.
+    pub fn clear(&mut self) {
+        let uart = &self.receiver.uart;
+        ral::modify_reg!(ral::lpuart, uart, FIFO, RXFLUSH: RXFLUSH_1);
+        ral::write_reg!(ral::lpuart, uart, STAT, OR: 1);
+    }
+}
+
+/// Modify the STAT control bits, without clearing STAT's flags
+///
+/// STAT's flags clear when written with 1, so `modify_reg!` would clear any pending
+/// flags. `f` receives STAT with the flags masked off.
+fn modify_stat(uart: &ral::lpuart::Instance, f: impl FnOnce(u32) -> u32) {
+    let stat = ral::read_reg!(ral::lpuart, uart, STAT) & !STAT_FLAGS;
+    ral::write_reg!(ral::lpuart, uart, STAT, f(stat) & !STAT_FLAGS);
+}
+
+/// Clear the STAT flags in `flags`, without changing the control bits or other flags
+fn clear_stat(uart: &ral::lpuart::Instance, flags: u32) {
+    let stat = ral::read_reg!(ral::lpuart, uart, STAT) & !STAT_FLAGS;
+    ral::write_reg!(ral::lpuart, uart, STAT, stat | (flags & STAT_FLAGS));
+}
+
+/// The STAT flags that clear when written with 1
+const STAT_FLAGS: u32 = {
+    use ral::lpuart::STAT::*;
+    LBKDIF::mask
+        | RXEDGIF::mask
+        | IDLE::mask
+        | OR::mask
+        | NF::mask
+        | FE::mask
+        | PF::mask
+        | MA1F::mask
+        | MA2F::mask
+};
+
+fn send_break(uart: &ral::lpuart::Instance) {
+    while ral::read_reg!(ral::lpuart, uart, STAT, TDRE == 0) {}
+    ral::write_reg!(ral::lpuart, uart, DATA, FRETSC: 1);
+}
+
+/// Returns another handle to the same LPUART instance
+fn steal(uart: &ral::lpuart::Instance) -> ral::lpuart::Instance {
+    // Safety: the transmit and receive halves use separate DMA enable
+    // bits, and they modify the shared BAUD register in critical sections.
+    unsafe {
+        match &**uart as *const _ {
+            ral::lpuart::LPUART1 => ral::lpuart::LPUART1::steal(),
+            ral::lpuart::LPUART2 => ral::lpuart::LPUART2::steal(),
+            ral::lpuart::LPUART3 => ral::lpuart::LPUART3::steal(),
+            ral::lpuart::LPUART4 => ral::lpuart::LPUART4::steal(),
             #[cfg(feature = "imxrt1060")]
-            ral::lpuart::LPUART5 => 7,
+            ral::lpuart::LPUART5 => ral::lpuart::LPUART5::steal(),
             #[cfg(feature = "imxrt1060")]
-            ral::lpuart::LPUART6 => 71,
+            ral::lpuart::LPUART6 => ral::lpuart::LPUART6::steal(),
             #[cfg(feature = "imxrt1060")]
-            ral::lpuart::LPUART7 => 9,
+            ral::lpuart::LPUART7 => ral::lpuart::LPUART7::steal(),
             #[cfg(feature = "imxrt1060")]
-            ral::lpuart::LPUART8 => 73,
+            ral::lpuart::LPUART8 => ral::lpuart::LPUART8::steal(),
             _ => unreachable!(),
         }
     }
-    fn source_address(&self) -> *const u8 {
-        &self.uart.DATA as *const _ as *const u8
+}
+
+fn rx_signal(uart: &ral::lpuart::Instance) -> u32 {
+    // Make sure that the match expression will never hit the unreachable!() case.
+    // The comments and conditional compiles show what we're currently considering in
+    // that match. If your chip isn't listed, it's not something we considered.
+    #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
+    compile_error!("Ensure that LPUART DMAMUX RX channels are correct");
+
+    // See table 4-3 of the iMXRT1060 Reference Manual (Rev 2)
+    match &**uart as *const _ {
+        // imxrt1010, imxrt1060
+        ral::lpuart::LPUART1 => 3,
+        // imxrt1010, imxrt1060
+        ral::lpuart::LPUART2 => 67,
+        // imxrt1010, imxrt1060
+        ral::lpuart::LPUART3 => 5,
+        // imxrt1010, imxrt1060
+        ral::lpuart::LPUART4 => 69,
+        #[cfg(feature = "imxrt1060")]
+        ral::lpuart::LPUART5 => 7,
+        #[cfg(feature = "imxrt1060")]
+        ral::lpuart::LPUART6 => 71,
+        #[cfg(feature = "imxrt1060")]
+        ral::lpuart::LPUART7 => 9,
+        #[cfg(feature = "imxrt1060")]
+        ral::lpuart::LPUART8 => 73,
+        _ => unreachable!(),
     }
-    fn enable_source(&mut self) {
-        // Clear all status flags
-        ral::modify_reg!(
-            ral::lpuart,
-            self.uart,
-            STAT,
-            IDLE: IDLE_1,
-            OR: OR_1,
-            NF: NF_1,
-            FE: FE_1,
-            PF: PF_1
-        );
-        ral::modify_reg!(ral::lpuart, self.uart, BAUD, RDMAE: 1);
+}
+
+fn tx_signal(uart: &ral::lpuart::Instance) -> u32 {
+    rx_signal(uart) - 1
+}
+
+fn data_address(uart: &ral::lpuart::Instance) -> *const u8 {
+    &uart.DATA as *const _ as *const u8
+}
+
+fn enable_tx_dma(uart: &ral::lpuart::Instance) {
+    cortex_m::interrupt::free(|_| {
+        ral::modify_reg!(ral::lpuart, uart, BAUD, TDMAE: 1);
+    });
+}
+
+fn disable_tx_dma(uart: &ral::lpuart::Instance) {
+    while ral::read_reg!(ral::lpuart, uart, BAUD, TDMAE == 1) {
+        cortex_m::interrupt::free(|_| {
+            ral::modify_reg!(ral::lpuart, uart, BAUD, TDMAE: 0);
+        });
     }
-    fn disable_source(&mut self) {
-        while ral::read_reg!(ral::lpuart, self.uart, BAUD, RDMAE == 1) {
-            ral::modify_reg!(ral::lpuart, self.uart, BAUD, RDMAE: 0);
-        }
+}
+
+fn enable_rx_dma(uart: &ral::lpuart::Instance) {
+    // Clear all status flags
+    ral::modify_reg!(
+        ral::lpuart,
+        uart,
+        STAT,
+        IDLE: IDLE_1,
+        OR: OR_1,
+        NF: NF_1,
+        FE: FE_1,
+        PF: PF_1
+    );
+    cortex_m::interrupt::free(|_| {
+        ral::modify_reg!(ral::lpuart, uart, BAUD, RDMAE: 1);
+    });
+}
+
+fn disable_rx_dma(uart: &ral::lpuart::Instance) {
+    while ral::read_reg!(ral::lpuart, uart, BAUD, RDMAE == 1) {
+        cortex_m::interrupt::free(|_| {
+            ral::modify_reg!(ral::lpuart, uart, BAUD, RDMAE: 0);
+        });
     }
 }
@@ -0,0 +1,160 @@
+//! WS2812 / NeoPixel addressable LEDs
+//!
+//! The [`WS2812`] driver generates the WS2812 single-wire timing with a [`SPI`](crate::SPI)
+//! peripheral. The driver stretches each WS2812 bit across four SPI bits, and a DMA
+//! channel feeds the encoded data to the SPI peripheral. Once the data is queued, the
+//! CPU is free to run other tasks.
+//!
+//! Connect the LED strip's data input to the SPI's SDO pin. The other SPI pins are
+//! unused by the LEDs, but the `SPI` driver still requires them.
+//!
+//! # Timing
+//!
+//! Set the SPI clock to [`SPI_CLOCK_HZ`] using [`set_clock_speed`](crate::SPI::set_clock_speed()).
+//! At this rate, each WS2812 bit is 1.25us. SPI frame delays only stretch the low
+//! part of a WS2812 bit, which the LEDs tolerate.
+//!
+//! The LEDs latch their colors once the data line is low for the WS2812 reset time.
+//! [`write`](WS2812::write()) does not insert this delay. If you're writing LED colors
+//! back-to-back, use a timer to wait at least 300us between writes.
+//!
+//! # Example
+//!
+//! Set the first three LEDs in a strip to red, green, and blue, using SPI4.
+//!
+//! ```no_run
+//! use imxrt_async_hal as hal;
+//! use hal::{dma, instance, iomuxc, SPI, SPIPins, ws2812::{self, RGB8, WS2812}};
+//! use hal::ral::{dma0::DMA0, dmamux::DMAMUX, iomuxc::IOMUXC, lpspi::LPSPI4};
+//!
+//! // Effective LPSPI source clock (PLL2 / 5)
+//! const SOURCE_CLOCK_HZ: u32 = 528_000_000 / 5;
+//!
+//! let pads = IOMUXC::take().map(iomuxc::new).unwrap();
+//! let mut channels = dma::channels(
+//!     DMA0::take().unwrap(),
+//!     DMAMUX::take().unwrap(),
+//! );
+//!
+//! let spi_pins = SPIPins {
+//!     sdo: pads.b0.p02,
+//!     sdi: pads.b0.p01,
+//!     sck: pads.b0.p03,
+//!     pcs0: pads.b0.p00,
+//! };
+//! let spi4 = LPSPI4::take().and_then(instance::spi).unwrap();
+//! let mut spi = SPI::new(spi_pins, spi4);
+//! spi.set_clock_speed(ws2812::SPI_CLOCK_HZ, SOURCE_CLOCK_HZ).unwrap();
+//!
+//! static mut BUFFER: [u32; 3 * 8] = [0; 3 * 8];
+//! let mut leds = WS2812::new(spi, channels[8].take().unwrap(), unsafe { &mut BUFFER });
+//!
+//! # async {
+//! let colors = [
+//!     RGB8 { r: 0xFF, g: 0, b: 0 },
+//!     RGB8 { r: 0, g: 0xFF, b: 0 },
+//!     RGB8 { r: 0, g: 0, b: 0xFF },
+//! ];
+//! leds.write(&colors).await.unwrap();
+//! # };
+//! ```
+
+use crate::{dma, SPI};
+
+/// The SPI serial clock speed that produces WS2812 timing
+///
+/// See the [module-level documentation](crate::ws2812) for more information.
+pub const SPI_CLOCK_HZ: u32 = 3_200_000;
+
+/// An 8-bit red, green, and blue color
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub struct RGB8 {
+    /// Red intensity
+    pub r: u8,
+    /// Green intensity
+    pub g: u8,
+    /// Blue intensity
+    pub b: u8,
+}
+
+/// A WS2812 LED strip driven by a SPI peripheral
+///
+/// `WS2812` encodes colors into a user-supplied buffer of `u32`s. The buffer needs three
+/// `u32`s for each LED in the strip. See the [module-level documentation](crate::ws2812)
+/// for more information.
+#[cfg_attr(docsrs, doc(cfg(feature = "spi")))]
+pub struct WS2812<'b, Pins> {
+    spi: SPI<Pins>,
+    channel: dma::Channel,
+    buffer: &'b mut [u32],
+}
+
+impl<'b, Pins> WS2812<'b, Pins> {
+    /// Create a `WS2812` driver from a SPI peripheral, a DMA channel, and an encoding buffer
+    ///
+    /// The driver supports `buffer.len() / 3` LEDs. Make sure that the SPI clock speed
+    /// is [`SPI_CLOCK_HZ`].
+    pub fn new(spi: SPI<Pins>, mut channel: dma::Channel, buffer: &'b mut [u32]) -> Self {
+        channel.set_interrupt_on_completion(true);
+        WS2812 {
+            spi,
+            channel,
+            buffer,
+        }
+    }
+
+    /// Return the SPI driver, the DMA channel, and the encoding buffer
+    pub fn release(self) -> (SPI<Pins>, dma::Channel, &'b mut [u32]) {
+        (self.spi, self.channel, self.buffer)
+    }
+
+    /// Returns the maximum number of LEDs that the driver can write
+    pub fn capacity(&self) -> usize {
+        self.buffer.len() / 3
+    }
+
+    /// Write `colors` to the LED strip
+    ///
+    /// The first color goes to the LED nearest to the SDO pin. Completes once all
+    /// data is queued in the SPI peripheral.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `colors` has more elements than the driver's [`capacity`](WS2812::capacity()).
+    pub fn write<'a>(&'a mut self, colors: &[RGB8]) -> dma::Tx<'a, SPI<Pins>, u32> {
+        assert!(
+            colors.len() <= self.capacity(),
+            "Too many colors for the WS2812 buffer"
+        );
+        use core::iter::once;
+
+        let len = colors.len() * 3;
+        colors
+            .iter()
+            // WS2812 LEDs expect green, then red, then blue
+            .flat_map(|color| once(color.g).chain(once(color.r)).chain(once(color.b)))
+            .zip(self.buffer.iter_mut())
+            .for_each(|(byte, word)| *word = encode(byte));
+        self.spi.dma_write(&mut self.channel, &self.buffer[..len])
+    }
+}
+
+/// Stretch each bit of `byte` into four SPI bits
+///
+/// A `1` becomes `0b1110`, and a `0` becomes `0b1000`. The most-significant bit
+/// is sent first.
+const fn encode(byte: u8) -> u32 {
+    let mut word = 0;
+    let mut bit = 0;
+    while bit < 8 {
+        let nibble = if byte & (0x80 >> bit) != 0 {
+            0b1110
+        } else {
+            0b1000
+        };
+        word = (word << 4) | nibble;
+        bit += 1;
+    }
+    word
+}